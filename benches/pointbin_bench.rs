@@ -1,6 +1,6 @@
 use bucket_search::PointBin3D;
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
-use ndarray::{array, Array1, Array2};
+use ndarray::{array, Array2};
 
 fn create_random_points(n: usize) -> Array2<f64> {
     // Create a simple deterministic pattern instead of random for consistency
@@ -74,5 +74,25 @@ fn bench_multiple_searches(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, bench_construction, bench_radius_search, bench_multiple_searches);
+fn bench_radius_pairs(c: &mut Criterion) {
+    let mut group = c.benchmark_group("radius_pairs");
+    group.sample_size(10);
+
+    let points = create_random_points(1_000_000);
+    let bin_widths = array![1.0, 1.0, 1.0];
+    let point_bin = PointBin3D::new(points, bin_widths);
+
+    group.bench_function("serial", |b| {
+        b.iter(|| point_bin.radius_pairs(black_box(1.0)));
+    });
+
+    #[cfg(feature = "rayon")]
+    group.bench_function("parallel", |b| {
+        b.iter(|| point_bin.par_radius_pairs(black_box(1.0)));
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_construction, bench_radius_search, bench_multiple_searches, bench_radius_pairs);
 criterion_main!(benches);
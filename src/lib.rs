@@ -31,9 +31,35 @@
 
 mod utils;
 mod pointbin;
+mod xyzrgb;
+#[cfg(feature = "linalg")]
+mod linalg;
+#[cfg(any(feature = "potree", feature = "json"))]
+mod json;
+#[cfg(feature = "potree")]
+mod potree;
+#[cfg(feature = "rosbag")]
+mod rosbag;
+#[cfg(feature = "mmap")]
+mod mmap;
 
-pub use pointbin::PointBin3D;
+pub use pointbin::{BinMetric, BinStats, BucketError, BinsByDistanceIter, CappedSearchResult, DeserializeError, DimensionMismatch, GridMetadata, NeighborStream, PointBin3D, PointBin3DBuilder, ProximityGraph, QueryCostProfile, RadiusAgg, RemoveError, SharedPointBin3D, SwapError};
+#[cfg(feature = "tree")]
+pub use pointbin::OctreeNode;
 pub use utils::{max_along_axis0, min_along_axis0};
+pub use xyzrgb::ParseError;
+#[cfg(feature = "pcd")]
+pub use pointbin::PcdError;
+#[cfg(feature = "lru")]
+pub use pointbin::CachedQueries;
+#[cfg(feature = "json")]
+pub use pointbin::JsonParseError;
+#[cfg(feature = "mesh")]
+pub use pointbin::{HalfEdge, HalfEdgeMesh};
+#[cfg(feature = "potree")]
+pub use potree::PotreeError;
+#[cfg(feature = "rosbag")]
+pub use rosbag::RosBagError;
 
 // Python bindings
 #[cfg(feature = "python")]
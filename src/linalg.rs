@@ -0,0 +1,90 @@
+//! Small dense linear algebra routines for 3x3 matrices
+//!
+//! Only what the statistics need: a symmetric eigen-decomposition, done with cyclic
+//! Jacobi rotations so no LAPACK is required. `ndarray-linalg` would have to link a
+//! LAPACK backend (OpenBLAS, Netlib or MKL) on every platform the wheels are built
+//! for; for a covariance matrix the SVD and the eigen-decomposition coincide, and a
+//! few Jacobi sweeps reach full precision on a 3x3 matrix.
+
+use ndarray::{Array1, Array2};
+
+/// Eigen-decomposition of a symmetric 3x3 matrix
+///
+/// # Returns
+/// `(eigenvectors, eigenvalues)` with unit eigenvectors as columns, sorted by
+/// descending eigenvalue
+pub(crate) fn symmetric_eigen3(matrix: &Array2<f64>) -> (Array2<f64>, Array1<f64>) {
+    let mut a = matrix.clone();
+    let mut v = Array2::<f64>::eye(3);
+
+    for _sweep in 0..50 {
+        let off_diagonal = a[[0, 1]].powi(2) + a[[0, 2]].powi(2) + a[[1, 2]].powi(2);
+        let scale = a[[0, 0]].powi(2) + a[[1, 1]].powi(2) + a[[2, 2]].powi(2);
+        if off_diagonal <= f64::EPSILON * f64::EPSILON * scale || off_diagonal == 0.0 {
+            break;
+        }
+
+        for (p, q) in [(0, 1), (0, 2), (1, 2)] {
+            if a[[p, q]] == 0.0 {
+                continue;
+            }
+
+            // Rotation angle that zeroes a[p][q]
+            let theta = (a[[q, q]] - a[[p, p]]) / (2.0 * a[[p, q]]);
+            let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+            let t = if theta == 0.0 { 1.0 } else { t };
+            let c = 1.0 / (t * t + 1.0).sqrt();
+            let s = t * c;
+
+            for k in 0..3 {
+                let (akp, akq) = (a[[k, p]], a[[k, q]]);
+                a[[k, p]] = c * akp - s * akq;
+                a[[k, q]] = s * akp + c * akq;
+            }
+            for k in 0..3 {
+                let (apk, aqk) = (a[[p, k]], a[[q, k]]);
+                a[[p, k]] = c * apk - s * aqk;
+                a[[q, k]] = s * apk + c * aqk;
+            }
+            for k in 0..3 {
+                let (vkp, vkq) = (v[[k, p]], v[[k, q]]);
+                v[[k, p]] = c * vkp - s * vkq;
+                v[[k, q]] = s * vkp + c * vkq;
+            }
+        }
+    }
+
+    let mut order = [0, 1, 2];
+    order.sort_by(|&i, &j| a[[j, j]].total_cmp(&a[[i, i]]));
+
+    let eigenvalues = Array1::from_iter(order.iter().map(|&i| a[[i, i]]));
+    let eigenvectors = Array2::from_shape_fn((3, 3), |(row, col)| v[[row, order[col]]]);
+    (eigenvectors, eigenvalues)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    #[test]
+    fn test_symmetric_eigen3() {
+        let matrix = array![
+            [4.0, 1.0, 0.5],
+            [1.0, 3.0, 0.0],
+            [0.5, 0.0, 1.0],
+        ];
+        let (vectors, values) = symmetric_eigen3(&matrix);
+
+        assert!(values.windows(2).into_iter().all(|w| w[0] >= w[1]));
+        for k in 0..3 {
+            let v = vectors.column(k);
+            let mv = matrix.dot(&v);
+            for j in 0..3 {
+                assert!((mv[j] - values[k] * v[j]).abs() < 1e-10);
+            }
+            assert!((v.dot(&v) - 1.0).abs() < 1e-12);
+        }
+        assert!((values.sum() - 8.0).abs() < 1e-10);
+    }
+}
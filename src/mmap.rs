@@ -0,0 +1,90 @@
+//! Building a PointBin3D from a memory-mapped file of raw coordinates
+
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use memmap2::Mmap;
+use ndarray::{Array1, Array2};
+
+use crate::PointBin3D;
+
+impl PointBin3D {
+    /// Build a PointBin3D from a memory-mapped file of little-endian f64 coordinates
+    ///
+    /// The file must hold exactly `n_points` records of `[x, y, z]`, with no header.
+    /// The coordinates are decoded from the mapping straight into the structure's copy
+    /// of the original points, without reading the file into an intermediate buffer.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the raw coordinate file
+    /// * `n_points` - Number of points stored in the file
+    /// * `bin_widths` - 1D array of shape (3,) with bin widths for x, y, z
+    ///
+    /// # Returns
+    /// A new PointBin3D, an `InvalidData` error if the file size doesn't match `n_points`,
+    /// or an `InvalidInput` error wrapping the `BucketError` for input `try_new` rejects
+    pub fn from_mmap<P: AsRef<Path>>(path: P, n_points: usize, bin_widths: Array1<f64>) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // Safety: the mapping is only read while this function runs; as with any
+        // memory map, the file must not be truncated concurrently
+        let mmap = unsafe { Mmap::map(&file)? };
+
+        let expected_len = n_points.checked_mul(3 * std::mem::size_of::<f64>());
+        if expected_len != Some(mmap.len()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("file has {} bytes, expected {} points of 24 bytes", mmap.len(), n_points),
+            ));
+        }
+
+        let mut original_points = Array2::<f64>::zeros((n_points, 3));
+        for (value, bytes) in original_points.iter_mut().zip(mmap.chunks_exact(8)) {
+            *value = f64::from_le_bytes(bytes.try_into().unwrap());
+        }
+        Self::try_new(original_points, bin_widths).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    #[test]
+    fn test_from_mmap_matches_in_memory() {
+        let mut points = Array2::<f64>::zeros((200, 3));
+        for i in 0..200 {
+            let fi = i as f64;
+            points[[i, 0]] = (fi * 0.37) % 7.0;
+            points[[i, 1]] = (fi * 0.91) % 5.0;
+            points[[i, 2]] = (fi * 1.53) % 3.0;
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("points.f64");
+        let bytes: Vec<u8> = points.iter().flat_map(|v| v.to_le_bytes()).collect();
+        std::fs::write(&path, bytes).unwrap();
+
+        let bin_widths = array![0.5, 0.5, 0.5];
+        let mut mapped = PointBin3D::from_mmap(&path, 200, bin_widths.clone()).unwrap();
+        let mut in_memory = PointBin3D::new(points, bin_widths);
+        assert_eq!(mapped.original_points(), in_memory.original_points());
+        assert_eq!(mapped.to_count_grid(), in_memory.to_count_grid());
+
+        let query = array![3.0, 2.5, 1.5];
+        mapped.radius_search(&query.view(), 1.2);
+        in_memory.radius_search(&query.view(), 1.2);
+        assert_eq!(mapped.found_indices(), in_memory.found_indices());
+
+        let err = PointBin3D::from_mmap(&path, 201, array![0.5, 0.5, 0.5]).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let err = PointBin3D::from_mmap(&path, 200, array![0.5, 0.0, 0.5]).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        let empty = dir.path().join("empty.f64");
+        std::fs::write(&empty, []).unwrap();
+        let err = PointBin3D::from_mmap(&empty, 0, array![0.5, 0.5, 0.5]).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+}
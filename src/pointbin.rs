@@ -1,7 +1,64 @@
 //! Core PointBin3D data structure for efficient spatial indexing
 
-use ndarray::{Array1, Array2, Array3, ArrayView1};
-use crate::utils::{max_along_axis0_i64, min_along_axis0};
+use std::collections::{HashMap, HashSet};
+use std::ops::ControlFlow;
+
+use ndarray::{Array1, Array2, Array3, ArrayView1, ArrayView2, Axis};
+use crate::utils::{inverse_3x3, is_bin_sorted, is_column_major, max_along_axis0, max_along_axis0_i64, min_along_axis0, ravel_bin_index, squared_distance};
+
+mod batch;
+mod binary;
+mod bins;
+mod builder;
+mod capped;
+mod diagnostics;
+mod error;
+mod grid;
+mod knn;
+mod pairs;
+mod remove;
+mod shared;
+mod stats;
+mod swap;
+#[cfg(feature = "geometry")]
+mod geometry;
+#[cfg(feature = "tree")]
+mod octree;
+#[cfg(feature = "image")]
+mod raster;
+#[cfg(feature = "rand")]
+mod random;
+#[cfg(feature = "pcd")]
+mod pcd;
+#[cfg(feature = "lru")]
+mod cached;
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "mesh")]
+mod mesh;
+
+pub use binary::DeserializeError;
+pub use bins::{BinsByDistanceIter, NeighborStream};
+pub use builder::PointBin3DBuilder;
+pub use capped::CappedSearchResult;
+pub use diagnostics::{BinStats, QueryCostProfile};
+pub use error::BucketError;
+pub use grid::{BinMetric, GridMetadata};
+pub use pairs::ProximityGraph;
+pub use remove::RemoveError;
+pub use shared::SharedPointBin3D;
+pub use stats::RadiusAgg;
+pub use swap::{DimensionMismatch, SwapError};
+#[cfg(feature = "tree")]
+pub use octree::OctreeNode;
+#[cfg(feature = "pcd")]
+pub use pcd::PcdError;
+#[cfg(feature = "lru")]
+pub use cached::CachedQueries;
+#[cfg(feature = "json")]
+pub use json::JsonParseError;
+#[cfg(feature = "mesh")]
+pub use mesh::{HalfEdge, HalfEdgeMesh};
 
 /// A 3D spatial indexing structure using binning/bucketing for efficient radius searches
 ///
@@ -15,28 +72,55 @@ pub struct PointBin3D {
     /// Width of each bin in x, y, z dimensions (3,)
     bin_widths: Array1<f64>,
     /// Origin point (minimum corner) of the binning grid (3,)
-    origin: Array1<f64>,
+    grid_origin: Array1<f64>,
+    /// Origin reported by `origin()`, `grid_origin` moved by `bin_offset` whole bins (3,)
+    reported_origin: Array1<f64>,
+    /// Whole bins `shift_origin_by_bins` has moved the reported origin by, along x, y, z
+    bin_offset: [i64; 3],
     /// Maps sorted index back to original index (n_points,)
     original_indices: Array1<i64>,
+    /// `(raveled bin key, start, len)` of each occupied bin's range of sorted points,
+    /// in bin order, as laid out at construction
+    bin_offsets: Vec<(u64, usize, usize)>,
     /// Shape of the bin grid (3,)
     bin_shape: Array1<i64>,
     /// Head of linked list for each bin (bin_shape[0], bin_shape[1], bin_shape[2])
     first_member: Array3<i64>,
     /// Next pointer in linked list (n_points,)
     next_member: Array1<i64>,
-    /// Backup of first_member for reset (bin_shape[0], bin_shape[1], bin_shape[2])
-    original_first_member: Array3<i64>,
-    /// Backup of next_member for reset (n_points,)
-    original_next_member: Array1<i64>,
-    /// Buffer for storing found indices during search (n_points,)
-    found_indices_buffer: Array1<i64>,
-    /// Count of found points in current search
-    found_count: usize,
+    /// Backup of first_member for reset, unless disabled by the builder
+    original_first_member: Option<Array3<i64>>,
+    /// Backup of next_member for reset, unless disabled by the builder
+    original_next_member: Option<Array1<i64>>,
+    /// Sorted indices of found points, preallocated to n_points
+    ///
+    /// Destructive searches report each point at most once between resets, so they
+    /// never grow it past its initial capacity. Keep-mode searches can report a point
+    /// many times and grow it without bound.
+    found_indices_buffer: Vec<i64>,
+    /// Confidence of each point found by `radius_search_confidence`, in search order
+    found_confidences_buffer: Vec<f64>,
+    /// Sorted indices removed at full resolution by `lod_search`
+    found_full_buffer: Vec<i64>,
+    /// Sorted indices removed from the decimated shell by `lod_search`
+    found_lod_buffer: Vec<i64>,
+    /// Pass that last tagged each point with `radius_search_tag`, 0 if untagged,
+    /// in sorted order (n_points,)
+    found_pass_buffer: Array1<u8>,
+    /// Number of points removed by searches since the last reset
+    removed_count: usize,
+    /// Optional per-point influence radius, in sorted order (n_points,)
+    point_radii: Option<Array1<f64>>,
+    /// Largest entry of `point_radii`, bounding the bins a reverse search must visit
+    max_point_radius: f64,
 }
 
 impl PointBin3D {
     /// Create a new PointBin3D structure
     ///
+    /// Points may be row-major or column-major (Fortran order); either is read in its
+    /// own memory order without a transposing copy.
+    ///
     /// # Arguments
     /// * `original_points` - 2D array of shape (n_points, 3) with point coordinates
     /// * `bin_widths` - 1D array of shape (3,) with bin widths for x, y, z
@@ -45,10 +129,33 @@ impl PointBin3D {
     /// A new PointBin3D instance with points organized into bins
     ///
     /// # Panics
-    /// Panics if points don't have exactly 3 columns or bin_widths doesn't have length 3
+    /// Panics on input `try_new` rejects: points without exactly 3 columns, bin_widths
+    /// without length 3, a bin width that isn't positive, or no points
     pub fn new(original_points: Array2<f64>, bin_widths: Array1<f64>) -> Self {
-        assert_eq!(original_points.ncols(), 3, "Points must have 3 dimensions");
-        assert_eq!(bin_widths.len(), 3, "Bin widths must have 3 dimensions");
+        Self::try_new(original_points, bin_widths).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Create a new PointBin3D structure, reporting invalid input as an error
+    ///
+    /// The fallible form of `new`, for points and bin widths that come from users.
+    ///
+    /// # Arguments
+    /// * `original_points` - 2D array of shape (n_points, 3) with point coordinates
+    /// * `bin_widths` - 1D array of shape (3,) with bin widths for x, y, z
+    ///
+    /// # Returns
+    /// The new structure, or the first problem found with the input
+    pub fn try_new(original_points: Array2<f64>, bin_widths: Array1<f64>) -> Result<Self, BucketError> {
+        BucketError::check(&original_points, &bin_widths)?;
+        Ok(Self::build_index(original_points, bin_widths, true))
+    }
+
+    /// Bin the points, optionally skipping the backups `reset` restores from
+    ///
+    /// Callers validate the input with `BucketError::check` first.
+    fn build_index(original_points: Array2<f64>, bin_widths: Array1<f64>, keep_reset_backups: bool) -> Self {
+        debug_assert_eq!(original_points.ncols(), 3, "Points must have 3 dimensions");
+        debug_assert_eq!(bin_widths.len(), 3, "Bin widths must have 3 dimensions");
 
         let n_points = original_points.nrows();
 
@@ -56,28 +163,137 @@ impl PointBin3D {
         let origin = min_along_axis0(&original_points.view());
 
         let mut bin_indices = Array2::<i64>::zeros((n_points, 3));
-        for i in 0..n_points {
+        let mut bin_pass = |i: usize, j: usize| {
+            bin_indices[[i, j]] = ((original_points[[i, j]] - origin[j]) / bin_widths[j]).floor() as i64;
+        };
+        // Walk the input in its memory order so Fortran-ordered points are read contiguously
+        if is_column_major(&original_points.view()) {
+            for j in 0..3 {
+                for i in 0..n_points {
+                    bin_pass(i, j);
+                }
+            }
+        } else {
+            for i in 0..n_points {
+                for j in 0..3 {
+                    bin_pass(i, j);
+                }
+            }
+        }
+
+        let bin_shape = max_along_axis0_i64(&bin_indices.view()) + 1;
+        Self::index_from_bins(original_points, bin_widths, origin, bin_indices, bin_shape, keep_reset_backups)
+    }
+
+    /// Create a new PointBin3D from bin assignments computed elsewhere
+    ///
+    /// Skips the pass that divides every coordinate by the bin widths, trusting
+    /// `bin_indices` instead, e.g. when an upstream GPU pass has already computed them.
+    /// With `origin` set to the column-wise minimum of the points and each row of
+    /// `bin_indices` equal to `floor((point - origin) / bin_widths)`, the result is
+    /// identical to `new`. Debug builds check that every bin lies inside the grid the
+    /// points span.
+    ///
+    /// # Arguments
+    /// * `original_points` - 2D array of shape (n_points, 3) with point coordinates
+    /// * `bin_widths` - 1D array of shape (3,) with bin widths for x, y, z
+    /// * `origin` - 1D array of shape (3,) with the minimum corner of the grid
+    /// * `bin_indices` - 2D array of shape (n_points, 3) with each point's bin
+    ///
+    /// # Panics
+    /// Panics under the same conditions as `new`, or if origin or bin_indices have the
+    /// wrong shape
+    pub fn new_with_bins(
+        original_points: Array2<f64>,
+        bin_widths: Array1<f64>,
+        origin: Array1<f64>,
+        bin_indices: Array2<i64>,
+    ) -> Self {
+        assert_eq!(original_points.ncols(), 3, "Points must have 3 dimensions");
+        assert_eq!(bin_widths.len(), 3, "Bin widths must have 3 dimensions");
+        assert_eq!(origin.len(), 3, "Origin must have 3 dimensions");
+        assert_eq!(
+            bin_indices.dim(),
+            (original_points.nrows(), 3),
+            "Bin indices must have one (ix, iy, iz) row per point"
+        );
+
+        if cfg!(debug_assertions) && original_points.nrows() > 0 {
+            let max_point = max_along_axis0(&original_points.view());
             for j in 0..3 {
-                bin_indices[[i, j]] = ((original_points[[i, j]] - origin[j]) / bin_widths[j]).floor() as i64;
+                let implied_shape = ((max_point[j] - origin[j]) / bin_widths[j]).floor() as i64 + 1;
+                assert!(
+                    bin_indices.column(j).iter().all(|&b| (0..implied_shape).contains(&b)),
+                    "Bin indices must lie within the grid spanned by the points"
+                );
             }
         }
 
         let bin_shape = max_along_axis0_i64(&bin_indices.view()) + 1;
+        Self::index_from_bins(original_points, bin_widths, origin, bin_indices, bin_shape, true)
+    }
+
+    /// Bin points on a grid read back from a snapshot
+    ///
+    /// The stored `bin_shape` only bounds the grid: like `new_with_bins`, the shape is
+    /// derived from the bins the points fall in, so a corrupt header can't size the
+    /// allocation. Returns `None` if a point lies outside the stored grid.
+    pub(crate) fn bins_on_stored_grid(
+        original_points: &Array2<f64>,
+        bin_widths: &Array1<f64>,
+        origin: &Array1<f64>,
+        bin_shape: &Array1<i64>,
+    ) -> Option<(Array2<i64>, Array1<i64>)> {
+        let mut bin_indices = Array2::<i64>::zeros((original_points.nrows(), 3));
+        for i in 0..original_points.nrows() {
+            for j in 0..3 {
+                let bin = ((original_points[[i, j]] - origin[j]) / bin_widths[j]).floor() as i64;
+                if !(0..bin_shape[j]).contains(&bin) {
+                    return None;
+                }
+                bin_indices[[i, j]] = bin;
+            }
+        }
+        let shape = max_along_axis0_i64(&bin_indices.view()) + 1;
+        Some((bin_indices, shape))
+    }
+
+    /// Sort the points by bin and build the linked lists from known bin assignments
+    pub(crate) fn index_from_bins(
+        original_points: Array2<f64>,
+        bin_widths: Array1<f64>,
+        origin: Array1<f64>,
+        bin_indices: Array2<i64>,
+        bin_shape: Array1<i64>,
+        keep_reset_backups: bool,
+    ) -> Self {
+        let n_points = original_points.nrows();
 
         // 2. Sort points by bin for cache efficiency
         // Create sorting keys based on bin indices
-        let mut keys: Vec<(i64, usize)> = Vec::with_capacity(n_points);
+        let bin_of_point = |i: usize| [bin_indices[[i, 0]], bin_indices[[i, 1]], bin_indices[[i, 2]]];
+        let mut keys: Vec<(i128, usize)> = Vec::with_capacity(n_points);
         for i in 0..n_points {
-            let key = bin_indices[[i, 0]] * bin_shape[1] * bin_shape[2]
-                    + bin_indices[[i, 1]] * bin_shape[2]
-                    + bin_indices[[i, 2]];
-            keys.push((key, i));
+            keys.push((ravel_bin_index(bin_of_point(i), &bin_shape), i));
         }
         keys.sort_by_key(|&(k, _)| k);
 
         // Extract sort order
         let sort_order: Vec<usize> = keys.iter().map(|&(_, idx)| idx).collect();
 
+        // Points of a bin are now contiguous; record each occupied bin's range
+        let mut bin_offsets: Vec<(u64, usize, usize)> = Vec::new();
+        for (start, &(key, _)) in keys.iter().enumerate() {
+            match bin_offsets.last_mut() {
+                Some((last_key, _, len)) if *last_key == key as u64 => *len += 1,
+                _ => bin_offsets.push((key as u64, start, 1)),
+            }
+        }
+        debug_assert!(
+            is_bin_sorted(&sort_order.iter().map(|&i| bin_of_point(i)).collect::<Vec<_>>()),
+            "Points are not sorted by bin; the sort key must have overflowed"
+        );
+
         // Create sorted points array
         let mut points = Array2::<f64>::zeros((n_points, 3));
         let mut original_indices = Array1::<i64>::zeros(n_points);
@@ -109,26 +325,98 @@ impl PointBin3D {
         }
 
         // Store backups for reset functionality
-        let original_first_member = first_member.clone();
-        let original_next_member = next_member.clone();
+        let original_first_member = keep_reset_backups.then(|| first_member.clone());
+        let original_next_member = keep_reset_backups.then(|| next_member.clone());
 
         // Initialize search buffers
-        let found_indices_buffer = Array1::<i64>::from_elem(n_points, -1);
+        let found_indices_buffer = Vec::with_capacity(n_points);
 
         Self {
             original_points,
             points,
             bin_widths,
-            origin,
+            reported_origin: origin.clone(),
+            grid_origin: origin,
+            bin_offset: [0; 3],
             original_indices,
+            bin_offsets,
             bin_shape,
             first_member,
             next_member,
             original_first_member,
             original_next_member,
             found_indices_buffer,
-            found_count: 0,
+            found_confidences_buffer: Vec::new(),
+            found_full_buffer: Vec::new(),
+            found_lod_buffer: Vec::new(),
+            found_pass_buffer: Array1::zeros(n_points),
+            removed_count: 0,
+            point_radii: None,
+            max_point_radius: 0.0,
+        }
+    }
+
+    /// Create a new PointBin3D structure where each point carries an influence radius
+    ///
+    /// # Arguments
+    /// * `original_points` - 2D array of shape (n_points, 3) with point coordinates
+    /// * `bin_widths` - 1D array of shape (3,) with bin widths for x, y, z
+    /// * `point_radii` - 1D array of shape (n_points,) with each point's radius
+    ///
+    /// # Panics
+    /// Panics under the same conditions as `new`, or if point_radii doesn't have length n_points
+    pub fn new_with_radii(original_points: Array2<f64>, bin_widths: Array1<f64>, point_radii: Array1<f64>) -> Self {
+        assert_eq!(
+            point_radii.len(),
+            original_points.nrows(),
+            "Point radii must have one entry per point"
+        );
+
+        let mut point_bin = Self::new(original_points, bin_widths);
+        point_bin.set_point_radii(&point_radii);
+        point_bin
+    }
+
+    /// Store per-point radii given in original point order
+    fn set_point_radii(&mut self, point_radii: &Array1<f64>) {
+        let sorted_radii = self
+            .original_indices
+            .mapv(|orig_idx| point_radii[orig_idx as usize]);
+        self.max_point_radius = sorted_radii.iter().cloned().fold(0.0, f64::max);
+        self.point_radii = Some(sorted_radii);
+    }
+
+    /// Build a separate structure for each distinct label
+    ///
+    /// Groups the rows of `points` by `labels` and indexes each group on its own, e.g.
+    /// one structure per cluster after DBSCAN. Label -1 is treated like any other value,
+    /// so unlabeled points end up together under key -1. Within each structure, original
+    /// indices count that label's rows in input order: original index `k` is the k-th
+    /// row carrying the label.
+    ///
+    /// # Arguments
+    /// * `points` - 2D array of shape (n_points, 3) with point coordinates
+    /// * `labels` - 1D array of shape (n_points,) with each point's label
+    /// * `bin_widths` - 1D array of shape (3,) with bin widths for x, y, z, shared by all
+    ///   the structures
+    ///
+    /// # Returns
+    /// A map from each label present to the structure over its points
+    ///
+    /// # Panics
+    /// Panics if labels doesn't have one entry per point, or under the same conditions as `new`
+    pub fn split_by_label(points: Array2<f64>, labels: Array1<i32>, bin_widths: Array1<f64>) -> HashMap<i32, PointBin3D> {
+        assert_eq!(labels.len(), points.nrows(), "Labels must have one entry per point");
+
+        let mut rows_by_label: HashMap<i32, Vec<usize>> = HashMap::new();
+        for (row, &label) in labels.iter().enumerate() {
+            rows_by_label.entry(label).or_default().push(row);
         }
+
+        rows_by_label
+            .into_iter()
+            .map(|(label, rows)| (label, Self::new(points.select(Axis(0), &rows), bin_widths.clone())))
+            .collect()
     }
 
     /// Perform a radius search around a query point
@@ -145,23 +433,7 @@ impl PointBin3D {
     pub fn radius_search(&mut self, query_point: &ArrayView1<f64>, radius: f64) {
         assert_eq!(query_point.len(), 3, "Query point must have 3 dimensions");
 
-        // Compute bounding box in bin coordinates
-        let min_corner = query_point - radius;
-        let max_corner = query_point + radius;
-
-        let mut min_bin = Array1::<i64>::zeros(3);
-        let mut max_bin = Array1::<i64>::zeros(3);
-
-        for j in 0..3 {
-            min_bin[j] = ((min_corner[j] - self.origin[j]) / self.bin_widths[j]).floor() as i64;
-            max_bin[j] = ((max_corner[j] - self.origin[j]) / self.bin_widths[j]).floor() as i64;
-        }
-
-        // Clamp to valid range
-        for j in 0..3 {
-            min_bin[j] = min_bin[j].max(0);
-            max_bin[j] = max_bin[j].min(self.bin_shape[j] - 1);
-        }
+        let (min_bin, max_bin) = self.bin_range(query_point, radius);
 
         let radius_sq = radius * radius;
 
@@ -169,149 +441,2737 @@ impl PointBin3D {
         for ix in min_bin[0]..=max_bin[0] {
             for iy in min_bin[1]..=max_bin[1] {
                 for iz in min_bin[2]..=max_bin[2] {
-                    let mut prev: i64 = -1;
-                    let mut i = self.first_member[[ix as usize, iy as usize, iz as usize]];
-
-                    // Traverse linked list
-                    while i != -1 {
-                        let next_i = self.next_member[i as usize];
-
-                        // Compute distance squared
-                        let mut dist_sq = 0.0;
-                        for j in 0..3 {
-                            let diff = self.points[[i as usize, j]] - query_point[j];
-                            dist_sq += diff * diff;
-                        }
-
-                        if dist_sq <= radius_sq {
-                            // Point found - remove from linked list
-                            if prev == -1 {
-                                self.first_member[[ix as usize, iy as usize, iz as usize]] = next_i;
-                            } else {
-                                self.next_member[prev as usize] = next_i;
-                            }
-
-                            self.next_member[i as usize] = -2; // Mark as removed
-                            self.found_indices_buffer[self.found_count] = i;
-                            self.found_count += 1;
-                        } else {
-                            prev = i;
-                        }
-                        i = next_i;
-                    }
+                    let bin = [ix as usize, iy as usize, iz as usize];
+                    self.unlink_matching_in_bin(bin, true, |_, point| {
+                        squared_distance(&point, query_point) <= radius_sq
+                    });
                 }
             }
         }
     }
 
-    /// Get the original indices of all found points
+    /// Non-destructive radius search
     ///
-    /// Returns the indices into the original points array that were found
-    /// across all radius searches since the last reset.
+    /// Visits the same bins in the same order as `radius_search` and applies the same
+    /// `<=` test, but leaves the structure untouched and records nothing, so it can be
+    /// called through a shared reference and repeated or overlapping queries are
+    /// independent. Only points not already removed by earlier destructive searches are
+    /// considered.
+    ///
+    /// # Arguments
+    /// * `query_point` - 3D point to search around
+    /// * `radius` - Search radius
     ///
     /// # Returns
-    /// 1D array of original point indices
-    pub fn found_indices(&self) -> Array1<i64> {
-        let mut result = Array1::<i64>::zeros(self.found_count);
-        for i in 0..self.found_count {
-            let sorted_idx = self.found_indices_buffer[i] as usize;
-            result[i] = self.original_indices[sorted_idx];
-        }
-        result
+    /// Original indices of the points within `radius`, in the order `radius_search`
+    /// would find them
+    ///
+    /// # Panics
+    /// Panics if query_point doesn't have exactly 3 elements
+    pub fn radius_query(&self, query_point: &ArrayView1<f64>, radius: f64) -> Array1<i64> {
+        assert_eq!(query_point.len(), 3, "Query point must have 3 dimensions");
+
+        let mut found = Vec::new();
+        let _ = self.visit_within(query_point, radius, |i, _| {
+            found.push(self.original_indices[i]);
+            ControlFlow::Continue(())
+        });
+
+        Array1::from(found)
     }
 
-    /// Reset the structure for a fresh search
+    /// Count the remaining points within both of two spheres
     ///
-    /// Restores all points and clears the found indices buffer.
-    pub fn reset(&mut self) {
-        self.first_member.assign(&self.original_first_member);
-        self.next_member.assign(&self.original_next_member);
-        self.found_count = 0;
+    /// Only the bins in the overlap of the two spheres' bin boxes are visited, and each
+    /// point there is tested against both spheres, so neither match set is built.
+    /// Nothing is removed or recorded.
+    ///
+    /// # Arguments
+    /// * `query_a`, `radius_a` - Centre and radius of the first sphere
+    /// * `query_b`, `radius_b` - Centre and radius of the second sphere
+    ///
+    /// # Returns
+    /// Number of points within `radius_a` of `query_a` and within `radius_b` of `query_b`
+    ///
+    /// # Panics
+    /// Panics if either query doesn't have exactly 3 elements
+    pub fn intersection_count(
+        &self,
+        query_a: &ArrayView1<f64>,
+        radius_a: f64,
+        query_b: &ArrayView1<f64>,
+        radius_b: f64,
+    ) -> usize {
+        assert_eq!(query_a.len(), 3, "Query point must have 3 dimensions");
+        assert_eq!(query_b.len(), 3, "Query point must have 3 dimensions");
+
+        let (min_a, max_a) = self.bin_range(query_a, radius_a);
+        let (min_b, max_b) = self.bin_range(query_b, radius_b);
+        let min_bin = [0, 1, 2].map(|j| min_a[j].max(min_b[j]));
+        let max_bin = [0, 1, 2].map(|j| max_a[j].min(max_b[j]));
+        let (radius_a_sq, radius_b_sq) = (radius_a * radius_a, radius_b * radius_b);
+
+        let mut count = 0;
+        let _ = self.visit_in_bins(min_bin, max_bin, |i| {
+            if self.dist_sq_to(i, query_a) <= radius_a_sq && self.dist_sq_to(i, query_b) <= radius_b_sq {
+                count += 1;
+            }
+            ControlFlow::Continue(())
+        });
+        count
     }
 
-    /// Get the number of points found so far
-    pub fn found_count(&self) -> usize {
-        self.found_count
+    /// Radius search that also inserts the matches into a caller-owned set
+    ///
+    /// Behaves exactly like `radius_search`, and additionally inserts the original
+    /// indices of this search's matches into `acc`. Because `acc` belongs to the caller
+    /// it survives `reset()`, so it can tally matches across several search phases.
+    ///
+    /// # Arguments
+    /// * `query_point` - 3D point to search around
+    /// * `radius` - Search radius
+    /// * `acc` - Set that receives the original indices of the matches
+    ///
+    /// # Panics
+    /// Panics if query_point doesn't have exactly 3 elements
+    pub fn radius_search_accumulate(&mut self, query_point: &ArrayView1<f64>, radius: f64, acc: &mut HashSet<i64>) {
+        let start = self.found_indices_buffer.len();
+        self.radius_search(query_point, radius);
+        acc.extend(
+            self.found_indices_buffer[start..]
+                .iter()
+                .map(|&sorted_idx| self.original_indices[sorted_idx as usize]),
+        );
     }
 
-    /// Get a reference to the original points
-    pub fn original_points(&self) -> &Array2<f64> {
-        &self.original_points
+    /// Radius search that sums a weight over its matches
+    ///
+    /// Behaves exactly like `radius_search`, and additionally calls
+    /// `weight_fn(original_index, dist_sq)` on each of this search's matches and returns
+    /// the sum, e.g. for kernel density estimates. The matches are read straight from the
+    /// found indices, so no index array is allocated.
+    ///
+    /// # Arguments
+    /// * `query_point` - 3D point to search around
+    /// * `radius` - Search radius
+    /// * `weight_fn` - Weight of a match given its original index and squared distance
+    ///
+    /// # Returns
+    /// Sum of the weights, 0 if nothing was found
+    ///
+    /// # Panics
+    /// Panics if query_point doesn't have exactly 3 elements
+    pub fn radius_search_and_accumulate_scalar<F>(&mut self, query_point: &ArrayView1<f64>, radius: f64, weight_fn: F) -> f64
+    where
+        F: Fn(i64, f64) -> f64,
+    {
+        let start = self.found_indices_buffer.len();
+        self.radius_search(query_point, radius);
+        self.found_indices_buffer[start..]
+            .iter()
+            .map(|&sorted_idx| {
+                let sorted_idx = sorted_idx as usize;
+                weight_fn(self.original_indices[sorted_idx], self.dist_sq_to(sorted_idx, query_point))
+            })
+            .sum()
     }
 
-    /// Get the bin shape
-    pub fn bin_shape(&self) -> &Array1<i64> {
-        &self.bin_shape
+    /// Radius search that leaves the matched points in the structure
+    ///
+    /// Matches are appended to the found indices like `radius_search`, but nothing is
+    /// removed, so overlapping keep-mode searches report shared points once per search
+    /// and `found_count()` can exceed the number of points.
+    ///
+    /// # Arguments
+    /// * `query_point` - 3D point to search around
+    /// * `radius` - Search radius
+    ///
+    /// # Panics
+    /// Panics if query_point doesn't have exactly 3 elements
+    pub fn radius_search_keep(&mut self, query_point: &ArrayView1<f64>, radius: f64) {
+        assert_eq!(query_point.len(), 3, "Query point must have 3 dimensions");
+
+        let mut matches = Vec::new();
+        let _ = self.visit_within(query_point, radius, |i, _| {
+            matches.push(i as i64);
+            ControlFlow::Continue(())
+        });
+        self.found_indices_buffer.extend(matches);
     }
 
-    /// Get the origin
-    pub fn origin(&self) -> &Array1<f64> {
-        &self.origin
+    /// Radius search that tags its matches with a pass number instead of removing them
+    ///
+    /// For multi-pass algorithms: points found in one pass stay in the structure and
+    /// are only removed later, by `commit_tagged`, if they still carry that pass's tag.
+    /// A point matched by several passes keeps the tag of the latest one. Pass 0 means
+    /// untagged, so tagging with 0 clears the matches' tags. Nothing is added to the
+    /// found indices until the tags are committed. Tags survive `clear_found` and are
+    /// cleared by `reset`.
+    ///
+    /// # Arguments
+    /// * `query_point` - 3D point to search around
+    /// * `radius` - Search radius
+    /// * `pass` - Tag to give the matches
+    ///
+    /// # Panics
+    /// Panics if query_point doesn't have exactly 3 elements
+    pub fn radius_search_tag(&mut self, query_point: &ArrayView1<f64>, radius: f64, pass: u8) {
+        assert_eq!(query_point.len(), 3, "Query point must have 3 dimensions");
+
+        let mut matches = Vec::new();
+        let _ = self.visit_within(query_point, radius, |i, _| {
+            matches.push(i);
+            ControlFlow::Continue(())
+        });
+        for i in matches {
+            self.found_pass_buffer[i] = pass;
+        }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use ndarray::array;
-    use approx::assert_abs_diff_eq;
+    /// Remove every remaining point tagged with a pass by `radius_search_tag`
+    ///
+    /// The points are removed like a search removes its matches and added to the found
+    /// indices, in bin order, and their tags are cleared. Points with other tags are
+    /// left in place.
+    ///
+    /// # Arguments
+    /// * `pass` - Tag of the points to remove; 0 (untagged) removes nothing
+    pub fn commit_tagged(&mut self, pass: u8) {
+        if pass == 0 {
+            return;
+        }
 
-    #[test]
-    fn test_pointbin_creation() {
-        let points = array![
-            [0.5, 0.5, 0.5],
-            [3.0, 3.0, 3.0],
-            [6.0, 5.0, 5.0],
-        ];
-        let bin_widths = array![5.0, 5.0, 5.0];
+        let tagged: HashSet<usize> = (0..self.points.nrows())
+            .filter(|&i| self.found_pass_buffer[i] == pass && !self.is_removed(i))
+            .collect();
+        let mut bins: Vec<[usize; 3]> = tagged
+            .iter()
+            .map(|&i| self.bin_of(&self.points.row(i)).map(|b| b as usize))
+            .collect();
+        bins.sort_unstable();
+        bins.dedup();
 
-        let point_bin = PointBin3D::new(points, bin_widths);
+        for bin in bins {
+            self.unlink_matching_in_bin(bin, true, |i, _| tagged.contains(&i));
+        }
+        for &i in &tagged {
+            self.found_pass_buffer[i] = 0;
+        }
+    }
 
-        assert_eq!(point_bin.original_points().nrows(), 3);
-        assert_abs_diff_eq!(point_bin.origin()[0], 0.5, epsilon = 1e-10);
+    /// Original indices of the remaining points carrying a `radius_search_tag` pass tag
+    ///
+    /// # Arguments
+    /// * `pass` - Tag to look for
+    ///
+    /// # Returns
+    /// Original indices of the tagged points, ascending
+    pub fn tagged_indices(&self, pass: u8) -> Array1<i64> {
+        let mut tagged: Vec<i64> = (0..self.points.nrows())
+            .filter(|&i| self.found_pass_buffer[i] == pass && !self.is_removed(i))
+            .map(|i| self.original_indices[i])
+            .collect();
+        tagged.sort_unstable();
+        Array1::from(tagged)
     }
 
-    #[test]
-    fn test_radius_search_basic() {
-        let points = array![
-            [0.5, 0.5, 0.5],  // Index 0
-            [3.0, 3.0, 3.0],  // Index 1
-            [6.0, 5.0, 5.0],  // Index 2
-        ];
-        let bin_widths = array![5.0, 5.0, 5.0];
+    /// Non-destructive radius search that reports whether the grid clipped the sphere
+    ///
+    /// The search is clipped when part of the sphere lies outside the grid, so any
+    /// points there could not have been found. Nothing is removed or recorded.
+    ///
+    /// # Arguments
+    /// * `query_point` - 3D point to search around
+    /// * `radius` - Search radius
+    ///
+    /// # Returns
+    /// Original indices of the points within `radius`, and whether the sphere was clipped
+    ///
+    /// # Panics
+    /// Panics if query_point doesn't have exactly 3 elements
+    pub fn radius_search_clipped(&self, query_point: &ArrayView1<f64>, radius: f64) -> (Array1<i64>, bool) {
+        assert_eq!(query_point.len(), 3, "Query point must have 3 dimensions");
 
-        let mut point_bin = PointBin3D::new(points, bin_widths);
+        let clipped = (0..3).any(|j| {
+            let grid_max = self.grid_origin[j] + self.bin_shape[j] as f64 * self.bin_widths[j];
+            query_point[j] - radius < self.grid_origin[j] || query_point[j] + radius > grid_max
+        });
 
-        // Search near point 2
-        let query = array![5.0, 5.0, 5.0];
-        point_bin.radius_search(&query.view(), 1.5);
+        let mut found = Vec::new();
+        let _ = self.visit_within(query_point, radius, |i, _| {
+            found.push(self.original_indices[i]);
+            ControlFlow::Continue(())
+        });
 
-        let results = point_bin.found_indices();
-        assert_eq!(results.len(), 1);
-        assert_eq!(results[0], 2);
+        (Array1::from(found), clipped)
     }
 
-    #[test]
-    fn test_reset() {
-        let points = array![
-            [0.5, 0.5, 0.5],
-            [6.0, 5.0, 5.0],
-        ];
-        let bin_widths = array![5.0, 5.0, 5.0];
-
-        let mut point_bin = PointBin3D::new(points, bin_widths);
+    /// Non-destructive radius search that returns only points not seen before
+    ///
+    /// Returns the matches whose entry in `seen` is still `false` and then marks them,
+    /// so successive calls with the same `seen` yield only the newly discovered frontier,
+    /// e.g. for region growing. The structure itself is not modified.
+    ///
+    /// # Arguments
+    /// * `query_point` - 3D point to search around
+    /// * `radius` - Search radius
+    /// * `seen` - One flag per point, indexed by original index
+    ///
+    /// # Returns
+    /// Original indices of the matches that had not been seen
+    ///
+    /// # Panics
+    /// Panics if query_point doesn't have exactly 3 elements or seen doesn't have length n_points
+    pub fn radius_search_novel(&self, query_point: &ArrayView1<f64>, radius: f64, seen: &mut [bool]) -> Array1<i64> {
+        assert_eq!(query_point.len(), 3, "Query point must have 3 dimensions");
+        assert_eq!(seen.len(), self.points.nrows(), "Seen flags must have one entry per point");
 
-        // First search
-        let query = array![5.0, 5.0, 5.0];
-        point_bin.radius_search(&query.view(), 1.5);
-        assert_eq!(point_bin.found_count(), 1);
+        let mut novel = Vec::new();
+        let _ = self.visit_within(query_point, radius, |i, _| {
+            let orig_idx = self.original_indices[i];
+            if !seen[orig_idx as usize] {
+                seen[orig_idx as usize] = true;
+                novel.push(orig_idx);
+            }
+            ControlFlow::Continue(())
+        });
 
-        // Reset
-        point_bin.reset();
-        assert_eq!(point_bin.found_count(), 0);
+        Array1::from(novel)
+    }
+
+    /// Non-destructive radius search that also returns each match's offset from the query
+    ///
+    /// Gathers the matches and their displacement vectors `point - query` in a single
+    /// traversal, e.g. for computing directional forces. Nothing is removed or recorded.
+    ///
+    /// # Arguments
+    /// * `query_point` - 3D point to search around
+    /// * `radius` - Search radius
+    ///
+    /// # Returns
+    /// Original indices of the points within `radius`, and an (m, 3) array whose row `k`
+    /// is the offset of match `k` from the query
+    ///
+    /// # Panics
+    /// Panics if query_point doesn't have exactly 3 elements
+    pub fn radius_search_offsets(&self, query_point: &ArrayView1<f64>, radius: f64) -> (Array1<i64>, Array2<f64>) {
+        assert_eq!(query_point.len(), 3, "Query point must have 3 dimensions");
+
+        let mut found = Vec::new();
+        let mut offsets = Vec::new();
+        let _ = self.visit_within(query_point, radius, |i, _| {
+            found.push(self.original_indices[i]);
+            offsets.extend((0..3).map(|j| self.points[[i, j]] - query_point[j]));
+            ControlFlow::Continue(())
+        });
+
+        let offsets = Array2::from_shape_vec((found.len(), 3), offsets).expect("three offsets per match");
+        (Array1::from(found), offsets)
+    }
+
+    /// Non-destructive radius search with the query wrapped into the grid
+    ///
+    /// Treats the grid as a tile that repeats in every direction, like texture repeat
+    /// addressing: each coordinate of the query is reduced modulo the grid extent
+    /// (`bin_shape * bin_widths`, starting at the origin) before searching, so a query
+    /// at 1.5 times the extent behaves like one at 0.5 times. Only the query is wrapped;
+    /// distances are ordinary Euclidean distances within the tile, so the sphere does
+    /// not reach across the tile boundary. Nothing is removed or recorded.
+    ///
+    /// # Arguments
+    /// * `query_point` - 3D point to search around, anywhere in space
+    /// * `radius` - Search radius
+    ///
+    /// # Returns
+    /// Original indices of the points within `radius` of the wrapped query
+    ///
+    /// # Panics
+    /// Panics if query_point doesn't have exactly 3 elements
+    pub fn radius_search_wrapped(&self, query_point: &ArrayView1<f64>, radius: f64) -> Array1<i64> {
+        assert_eq!(query_point.len(), 3, "Query point must have 3 dimensions");
+
+        let wrapped = Array1::from_shape_fn(3, |j| {
+            let extent = self.bin_shape[j] as f64 * self.bin_widths[j];
+            self.grid_origin[j] + (query_point[j] - self.grid_origin[j]).rem_euclid(extent)
+        });
+
+        let mut found = Vec::new();
+        let _ = self.visit_within(&wrapped.view(), radius, |i, _| {
+            found.push(self.original_indices[i]);
+            ControlFlow::Continue(())
+        });
+
+        Array1::from(found)
+    }
+
+    /// Radius search that skips bins lying entirely outside the search sphere
+    ///
+    /// Behaves exactly like `radius_search`, but before traversing a bin it checks the
+    /// distance from the query to the nearest face of that bin's x-slab, xy-column and
+    /// finally the bin itself, skipping any that are farther than `radius`. For radii
+    /// spanning many bins this avoids visiting the corners of the bin bounding box.
+    ///
+    /// # Arguments
+    /// * `query_point` - 3D point to search around
+    /// * `radius` - Search radius
+    ///
+    /// # Panics
+    /// Panics if query_point doesn't have exactly 3 elements
+    pub fn radius_search_sphere_prefilter(&mut self, query_point: &ArrayView1<f64>, radius: f64) {
+        assert_eq!(query_point.len(), 3, "Query point must have 3 dimensions");
+
+        let (min_bin, max_bin) = self.bin_range(query_point, radius);
+        let radius_sq = radius * radius;
+
+        for ix in min_bin[0]..=max_bin[0] {
+            let dx_sq = self.axis_gap_sq(0, ix, query_point[0]);
+            if dx_sq > radius_sq {
+                continue;
+            }
+            for iy in min_bin[1]..=max_bin[1] {
+                let dxy_sq = dx_sq + self.axis_gap_sq(1, iy, query_point[1]);
+                if dxy_sq > radius_sq {
+                    continue;
+                }
+                for iz in min_bin[2]..=max_bin[2] {
+                    if dxy_sq + self.axis_gap_sq(2, iz, query_point[2]) > radius_sq {
+                        continue;
+                    }
+                    let bin = [ix as usize, iy as usize, iz as usize];
+                    self.unlink_matching_in_bin(bin, true, |_, point| {
+                        squared_distance(&point, query_point) <= radius_sq
+                    });
+                }
+            }
+        }
+    }
+
+    /// Radius search restricted to a spherical shell
+    ///
+    /// Removes and records the points whose distance `d` from the query satisfies
+    /// `r_inner < d <= r_outer`, leaving points inside the inner sphere in place.
+    ///
+    /// # Arguments
+    /// * `query_point` - 3D point to search around
+    /// * `r_inner` - Inner radius (exclusive)
+    /// * `r_outer` - Outer radius (inclusive)
+    ///
+    /// # Panics
+    /// Panics if query_point doesn't have exactly 3 elements
+    pub fn radius_shell_search(&mut self, query_point: &ArrayView1<f64>, r_inner: f64, r_outer: f64) {
+        assert_eq!(query_point.len(), 3, "Query point must have 3 dimensions");
+
+        let (min_bin, max_bin) = self.bin_range(query_point, r_outer);
+        let inner_sq = r_inner * r_inner;
+        let outer_sq = r_outer * r_outer;
+
+        for ix in min_bin[0]..=max_bin[0] {
+            for iy in min_bin[1]..=max_bin[1] {
+                for iz in min_bin[2]..=max_bin[2] {
+                    let bin = [ix as usize, iy as usize, iz as usize];
+                    self.unlink_matching_in_bin(bin, true, |_, point| {
+                        let dist_sq = squared_distance(&point, query_point);
+                        dist_sq > inner_sq && dist_sq <= outer_sq
+                    });
+                }
+            }
+        }
+    }
+
+    /// Remove the points within `max_radius` shell by shell, from the outside in
+    ///
+    /// Runs `radius_shell_search` on shells of thickness `dr` starting at `max_radius`,
+    /// finishing with an ordinary radius search for the innermost sphere, so the found
+    /// indices list outer points before inner ones (surface peeling order).
+    ///
+    /// # Arguments
+    /// * `query_point` - 3D point to search around
+    /// * `max_radius` - Radius of the outermost shell
+    /// * `dr` - Shell thickness
+    ///
+    /// # Panics
+    /// Panics if query_point doesn't have exactly 3 elements, max_radius is negative or
+    /// not finite, or dr is not positive
+    pub fn radius_search_inward(&mut self, query_point: &ArrayView1<f64>, max_radius: f64, dr: f64) {
+        assert_eq!(query_point.len(), 3, "Query point must have 3 dimensions");
+        assert!(max_radius >= 0.0 && max_radius.is_finite(), "Maximum radius must be finite and non-negative");
+        assert!(dr > 0.0, "Shell thickness must be positive");
+
+        // Radii are computed from max_radius rather than by repeated subtraction, which
+        // stops shrinking once dr is below the rounding step of r_outer
+        let n_shells = ((max_radius / dr).ceil() as usize).max(1);
+        let radius = |k: usize| max_radius - k as f64 * dr;
+        for k in 0..n_shells - 1 {
+            self.radius_shell_search(query_point, radius(k + 1), radius(k));
+        }
+        self.radius_search(query_point, radius(n_shells - 1));
+    }
+
+    /// Radius search measured in a linearly transformed space
+    ///
+    /// Finds and removes the points `p` with `||T (p - q)|| <= radius`, where `T` is
+    /// `transform`. With `T` whitening the coordinates (rotating onto the principal axes
+    /// and dividing by the standard deviation along each), this is an ellipsoidal query
+    /// in the original space that follows the shape of an elongated cloud. Only the bins
+    /// overlapping the ellipsoid's bounding box are visited. Results are accumulated
+    /// like `radius_search`.
+    ///
+    /// # Arguments
+    /// * `query_point` - 3D point to search around
+    /// * `radius` - Search radius in the transformed space
+    /// * `transform` - 3x3 invertible matrix mapping offsets into the search space
+    ///
+    /// # Panics
+    /// Panics if query_point doesn't have exactly 3 elements, or transform is not an
+    /// invertible 3x3 matrix
+    pub fn radius_search_projected(&mut self, query_point: &ArrayView1<f64>, radius: f64, transform: &ArrayView2<f64>) {
+        assert_eq!(query_point.len(), 3, "Query point must have 3 dimensions");
+        assert_eq!(transform.dim(), (3, 3), "Transform must be a 3x3 matrix");
+        let inverse = inverse_3x3(transform).expect("Transform must be invertible");
+
+        // The ellipsoid T^-1 (unit ball) * radius extends |row j of T^-1| * radius along axis j
+        let half_extents = std::array::from_fn(|j| radius * inverse.row(j).dot(&inverse.row(j)).sqrt());
+        let (min_bin, max_bin) = self.bin_range_box(query_point, half_extents);
+
+        let radius_sq = radius * radius;
+        for ix in min_bin[0]..=max_bin[0] {
+            for iy in min_bin[1]..=max_bin[1] {
+                for iz in min_bin[2]..=max_bin[2] {
+                    let bin = [ix as usize, iy as usize, iz as usize];
+                    self.unlink_matching_in_bin(bin, true, |_, point| {
+                        let offset = &point - query_point;
+                        let projected = transform.dot(&offset);
+                        projected.dot(&projected) <= radius_sq
+                    });
+                }
+            }
+        }
+    }
+
+    /// Search for the points inside an ellipsoid given by a metric tensor
+    ///
+    /// Finds and removes the points `p` with `(p - q)ᵀ M (p - q) <= 1`, where `M` is
+    /// `metric`. `M = I / r²` is an ordinary radius search; other positive-definite
+    /// matrices give ellipsoids of any orientation, and a diagonal `M` with a large
+    /// weight on one axis approaches a slab. Only the bins overlapping the ellipsoid's
+    /// axis-aligned bounding box are visited; its half-extent along axis `j` is
+    /// `sqrt((M⁻¹)_jj)`. Results are accumulated like `radius_search`.
+    ///
+    /// # Arguments
+    /// * `query_point` - Centre of the ellipsoid
+    /// * `metric` - 3x3 symmetric positive-definite matrix
+    ///
+    /// # Panics
+    /// Panics if query_point doesn't have exactly 3 elements, or metric is not an
+    /// invertible 3x3 matrix
+    pub fn radius_search_ellipsoid(&mut self, query_point: &ArrayView1<f64>, metric: &ArrayView2<f64>) {
+        assert_eq!(query_point.len(), 3, "Query point must have 3 dimensions");
+        assert_eq!(metric.dim(), (3, 3), "Metric must be a 3x3 matrix");
+        let inverse = inverse_3x3(metric).expect("Metric must be invertible");
+
+        let half_extents = std::array::from_fn(|j| inverse[[j, j]].max(0.0).sqrt());
+        let (min_bin, max_bin) = self.bin_range_box(query_point, half_extents);
+
+        for ix in min_bin[0]..=max_bin[0] {
+            for iy in min_bin[1]..=max_bin[1] {
+                for iz in min_bin[2]..=max_bin[2] {
+                    let bin = [ix as usize, iy as usize, iz as usize];
+                    self.unlink_matching_in_bin(bin, true, |_, point| {
+                        let offset = &point - query_point;
+                        offset.dot(&metric.dot(&offset)) <= 1.0
+                    });
+                }
+            }
+        }
+    }
+
+    /// Remove every point in a list of bins, regardless of distance
+    ///
+    /// The raw bin-enumeration primitive behind the distance-based searches: each listed
+    /// bin's remaining points are removed and added to the found indices. Bins may be
+    /// listed in any order and repeated; bins outside the grid are ignored.
+    ///
+    /// # Arguments
+    /// * `bin_list` - Bin coordinates `[ix, iy, iz]` to empty
+    ///
+    /// # Returns
+    /// Original indices of the points removed by this call
+    pub fn query_bins(&mut self, bin_list: &[[usize; 3]]) -> Array1<i64> {
+        let start = self.found_indices_buffer.len();
+        for &bin in bin_list {
+            if (0..3).all(|j| (bin[j] as i64) < self.bin_shape[j]) {
+                self.unlink_matching_in_bin(bin, true, |_, _| true);
+            }
+        }
+
+        self.found_indices_buffer[start..]
+            .iter()
+            .map(|&sorted_idx| self.original_indices[sorted_idx as usize])
+            .collect()
+    }
+
+    /// Radius search that leaves a slab of coordinates along one axis untouched
+    ///
+    /// Behaves like `radius_search`, except that points whose coordinate along `axis`
+    /// lies in `[exclude_min, exclude_max]` are neither removed nor recorded, e.g. to
+    /// skip the ground plane band of a LiDAR scan.
+    ///
+    /// # Arguments
+    /// * `query_point` - 3D point to search around
+    /// * `radius` - Search radius
+    /// * `axis` - Axis of the excluded slab (0 = x, 1 = y, 2 = z)
+    /// * `exclude_min` - Lower bound of the excluded slab (inclusive)
+    /// * `exclude_max` - Upper bound of the excluded slab (inclusive)
+    ///
+    /// # Panics
+    /// Panics if query_point doesn't have exactly 3 elements or axis is not 0, 1 or 2
+    pub fn radius_search_exclude_axis_range(
+        &mut self,
+        query_point: &ArrayView1<f64>,
+        radius: f64,
+        axis: usize,
+        exclude_min: f64,
+        exclude_max: f64,
+    ) {
+        assert_eq!(query_point.len(), 3, "Query point must have 3 dimensions");
+        assert!(axis < 3, "Axis must be 0, 1 or 2");
+
+        let (min_bin, max_bin) = self.bin_range(query_point, radius);
+        let radius_sq = radius * radius;
+
+        for ix in min_bin[0]..=max_bin[0] {
+            for iy in min_bin[1]..=max_bin[1] {
+                for iz in min_bin[2]..=max_bin[2] {
+                    let bin = [ix as usize, iy as usize, iz as usize];
+                    self.unlink_matching_in_bin(bin, true, |_, point| {
+                        !(exclude_min..=exclude_max).contains(&point[axis])
+                            && squared_distance(&point, query_point) <= radius_sq
+                    });
+                }
+            }
+        }
+    }
+
+    /// Radius search with two radii in a single traversal
+    ///
+    /// Finds the points within `r2` of the query in one pass over the bins and splits
+    /// them into those within `r1` and those in the shell `(r1, r2]`. All of them are
+    /// removed from the structure and added to the found indices.
+    ///
+    /// # Arguments
+    /// * `query_point` - 3D point to search around
+    /// * `r1` - Inner radius
+    /// * `r2` - Outer radius, at least `r1`
+    ///
+    /// # Returns
+    /// `(inner, outer)` original indices of the points within `r1` and in `(r1, r2]`
+    ///
+    /// # Panics
+    /// Panics if query_point doesn't have exactly 3 elements or r2 is smaller than r1
+    pub fn radius_search_two_radii(
+        &mut self,
+        query_point: &ArrayView1<f64>,
+        r1: f64,
+        r2: f64,
+    ) -> (Array1<i64>, Array1<i64>) {
+        assert_eq!(query_point.len(), 3, "Query point must have 3 dimensions");
+        assert!(r2 >= r1, "Outer radius must be at least the inner radius");
+
+        let (min_bin, max_bin) = self.bin_range(query_point, r2);
+        let r1_sq = r1 * r1;
+        let r2_sq = r2 * r2;
+
+        let mut inner = Vec::new();
+        let mut outer = Vec::new();
+        for ix in min_bin[0]..=max_bin[0] {
+            for iy in min_bin[1]..=max_bin[1] {
+                for iz in min_bin[2]..=max_bin[2] {
+                    let bin = [ix as usize, iy as usize, iz as usize];
+                    self.unlink_matching_in_bin(bin, true, |i, point| {
+                        let dist_sq = squared_distance(&point, query_point);
+                        if dist_sq <= r1_sq {
+                            inner.push(i);
+                        } else if dist_sq <= r2_sq {
+                            outer.push(i);
+                        }
+                        dist_sq <= r2_sq
+                    });
+                }
+            }
+        }
+
+        let to_original = |sorted: Vec<usize>| sorted.into_iter().map(|i| self.original_indices[i]).collect();
+        (to_original(inner), to_original(outer))
+    }
+
+    /// Radius search that scores each found point with a Gaussian confidence
+    ///
+    /// Behaves like `radius_search`, and additionally weights each match by
+    /// `exp(-dist² / (2 * sigma²))`. The confidences are also appended to the buffer
+    /// returned by `found_confidences()`.
+    ///
+    /// # Arguments
+    /// * `query_point` - 3D point to search around
+    /// * `radius` - Search radius
+    /// * `sigma` - Standard deviation of the Gaussian confidence model
+    ///
+    /// # Returns
+    /// Original indices of the points found by this search and their confidences
+    ///
+    /// # Panics
+    /// Panics if query_point doesn't have exactly 3 elements
+    pub fn radius_search_confidence(
+        &mut self,
+        query_point: &ArrayView1<f64>,
+        radius: f64,
+        sigma: f64,
+    ) -> (Array1<i64>, Array1<f64>) {
+        assert_eq!(query_point.len(), 3, "Query point must have 3 dimensions");
+
+        let (min_bin, max_bin) = self.bin_range(query_point, radius);
+        let radius_sq = radius * radius;
+        let two_sigma_sq = 2.0 * sigma * sigma;
+
+        let mut found = Vec::new();
+        let mut confidences = Vec::new();
+        for ix in min_bin[0]..=max_bin[0] {
+            for iy in min_bin[1]..=max_bin[1] {
+                for iz in min_bin[2]..=max_bin[2] {
+                    let bin = [ix as usize, iy as usize, iz as usize];
+                    self.unlink_matching_in_bin(bin, true, |i, point| {
+                        let dist_sq = squared_distance(&point, query_point);
+                        let matched = dist_sq <= radius_sq;
+                        if matched {
+                            found.push(i);
+                            confidences.push((-dist_sq / two_sigma_sq).exp());
+                        }
+                        matched
+                    });
+                }
+            }
+        }
+
+        self.found_confidences_buffer.extend_from_slice(&confidences);
+        let indices = found.into_iter().map(|i| self.original_indices[i]).collect();
+        (indices, Array1::from(confidences))
+    }
+
+    /// Radius search with a soft boundary between two radii
+    ///
+    /// Points within `r_hard` get weight 1, points at distance `d` in `(r_hard, r_soft)`
+    /// get the linearly decreasing weight `(r_soft - d) / (r_soft - r_hard)`, and points
+    /// at or beyond `r_soft` get weight 0. Every point with a positive weight is removed
+    /// and added to the found indices, like `radius_search`.
+    ///
+    /// # Arguments
+    /// * `query_point` - 3D point to search around
+    /// * `r_hard` - Radius within which membership is certain
+    /// * `r_soft` - Radius at which membership falls to zero, at least `r_hard`
+    ///
+    /// # Returns
+    /// Original indices of the points found by this search and their weights
+    ///
+    /// # Panics
+    /// Panics if query_point doesn't have exactly 3 elements or r_soft is smaller than r_hard
+    pub fn radius_search_soft(
+        &mut self,
+        query_point: &ArrayView1<f64>,
+        r_hard: f64,
+        r_soft: f64,
+    ) -> (Array1<i64>, Array1<f64>) {
+        assert_eq!(query_point.len(), 3, "Query point must have 3 dimensions");
+        assert!(r_soft >= r_hard, "r_soft must be at least r_hard");
+
+        let (min_bin, max_bin) = self.bin_range(query_point, r_soft);
+        let hard_sq = r_hard * r_hard;
+        let soft_sq = r_soft * r_soft;
+
+        let mut found = Vec::new();
+        let mut weights = Vec::new();
+        for ix in min_bin[0]..=max_bin[0] {
+            for iy in min_bin[1]..=max_bin[1] {
+                for iz in min_bin[2]..=max_bin[2] {
+                    let bin = [ix as usize, iy as usize, iz as usize];
+                    self.unlink_matching_in_bin(bin, true, |i, point| {
+                        let dist_sq = squared_distance(&point, query_point);
+                        let weight = if dist_sq <= hard_sq {
+                            1.0
+                        } else if dist_sq < soft_sq {
+                            (r_soft - dist_sq.sqrt()) / (r_soft - r_hard)
+                        } else {
+                            0.0
+                        };
+                        if weight > 0.0 {
+                            found.push(i);
+                            weights.push(weight);
+                        }
+                        weight > 0.0
+                    });
+                }
+            }
+        }
+
+        let indices = found.into_iter().map(|i| self.original_indices[i]).collect();
+        (indices, Array1::from(weights))
+    }
+
+    /// Find the nearest point within a radius in each azimuth sector around a query
+    ///
+    /// Splits the full turn around the z-axis through the query into `n_sectors` equal
+    /// sectors, sector 0 starting at +x and counting counter-clockwise towards +y, and
+    /// keeps the nearest remaining point within `radius` in each. Points directly above
+    /// or below the query have no azimuth and are skipped. Nothing is removed from the
+    /// structure.
+    ///
+    /// # Arguments
+    /// * `query_point` - 3D point to scan around
+    /// * `radius` - Search radius
+    /// * `n_sectors` - Number of azimuth sectors
+    ///
+    /// # Returns
+    /// 1D array of shape (n_sectors,) with the original index of the nearest point in
+    /// each sector, or -1 for sectors without one
+    ///
+    /// # Panics
+    /// Panics if query_point doesn't have exactly 3 elements or n_sectors is 0
+    pub fn angular_nearest(&self, query_point: &ArrayView1<f64>, radius: f64, n_sectors: usize) -> Array1<i64> {
+        assert_eq!(query_point.len(), 3, "Query point must have 3 dimensions");
+        assert!(n_sectors > 0, "Number of sectors must be positive");
+
+        let sector_width = 2.0 * std::f64::consts::PI / n_sectors as f64;
+        let mut nearest = vec![(-1i64, f64::INFINITY); n_sectors];
+        let _ = self.visit_within(query_point, radius, |i, dist_sq| {
+            let dx = self.points[[i, 0]] - query_point[0];
+            let dy = self.points[[i, 1]] - query_point[1];
+            if dx != 0.0 || dy != 0.0 {
+                let azimuth = dy.atan2(dx).rem_euclid(2.0 * std::f64::consts::PI);
+                let sector = ((azimuth / sector_width) as usize).min(n_sectors - 1);
+                if dist_sq < nearest[sector].1 {
+                    nearest[sector] = (self.original_indices[i], dist_sq);
+                }
+            }
+            ControlFlow::Continue(())
+        });
+
+        nearest.into_iter().map(|(idx, _)| idx).collect()
+    }
+
+    /// Rank the points within a radius by a caller-supplied score
+    ///
+    /// Scores every remaining point within `radius` with `score(original_index,
+    /// distance)` and keeps the `top_n` highest in a bounded buffer, so the whole match
+    /// set is never collected. Points whose score is NaN are skipped, and ties keep the
+    /// bin order. Nothing is removed from the structure.
+    ///
+    /// # Arguments
+    /// * `query_point` - 3D point to search around
+    /// * `radius` - Search radius
+    /// * `score` - Score of a match from its original index and distance to the query
+    /// * `top_n` - Maximum number of matches to return
+    ///
+    /// # Returns
+    /// Original indices of up to `top_n` matches and their scores, highest score first
+    ///
+    /// # Panics
+    /// Panics if query_point doesn't have exactly 3 elements
+    pub fn radius_search_ranked<F>(
+        &self,
+        query_point: &ArrayView1<f64>,
+        radius: f64,
+        score: F,
+        top_n: usize,
+    ) -> (Array1<i64>, Array1<f64>)
+    where
+        F: Fn(i64, f64) -> f64,
+    {
+        assert_eq!(query_point.len(), 3, "Query point must have 3 dimensions");
+
+        let mut best: Vec<(i64, f64)> = Vec::with_capacity(top_n + 1);
+        if top_n > 0 {
+            let _ = self.visit_within(query_point, radius, |i, dist_sq| {
+                let orig_idx = self.original_indices[i];
+                let s = score(orig_idx, dist_sq.sqrt());
+                if !s.is_nan() && (best.len() < top_n || s > best[best.len() - 1].1) {
+                    let pos = best.partition_point(|&(_, b)| b >= s);
+                    best.insert(pos, (orig_idx, s));
+                    best.truncate(top_n);
+                }
+                ControlFlow::Continue(())
+            });
+        }
+
+        let (indices, scores): (Vec<i64>, Vec<f64>) = best.into_iter().unzip();
+        (Array1::from(indices), Array1::from(scores))
+    }
+
+    /// Level-of-detail radius search that decimates distant points
+    ///
+    /// Removes every point within `r_full` of the query, and every `lod_stride`-th point
+    /// (the `lod_stride`-th, `2 * lod_stride`-th, ...) in linked-list order among those in
+    /// the shell `(r_full, r_lod]`; the others in the shell stay in the structure. The two
+    /// sets accumulate separately in `found_full_indices()` and `found_lod_indices()`
+    /// rather than in `found_indices()`.
+    ///
+    /// # Arguments
+    /// * `query_point` - 3D point to search around
+    /// * `r_full` - Radius within which all points are removed
+    /// * `r_lod` - Outer radius of the decimated shell, at least `r_full`
+    /// * `lod_stride` - Keep one in this many shell points, at least 1
+    ///
+    /// # Panics
+    /// Panics if query_point doesn't have exactly 3 elements, r_lod is smaller than
+    /// r_full, or lod_stride is 0
+    pub fn lod_search(&mut self, query_point: &ArrayView1<f64>, r_full: f64, r_lod: f64, lod_stride: usize) {
+        assert_eq!(query_point.len(), 3, "Query point must have 3 dimensions");
+        assert!(r_lod >= r_full, "LOD radius must be at least the full-resolution radius");
+        assert!(lod_stride > 0, "LOD stride must be positive");
+
+        let (min_bin, max_bin) = self.bin_range(query_point, r_lod);
+        let r_full_sq = r_full * r_full;
+        let r_lod_sq = r_lod * r_lod;
+
+        let mut full = Vec::new();
+        let mut lod = Vec::new();
+        let mut shell_seen = 0;
+        for ix in min_bin[0]..=max_bin[0] {
+            for iy in min_bin[1]..=max_bin[1] {
+                for iz in min_bin[2]..=max_bin[2] {
+                    let bin = [ix as usize, iy as usize, iz as usize];
+                    self.unlink_matching_in_bin(bin, false, |i, point| {
+                        let dist_sq = squared_distance(&point, query_point);
+                        if dist_sq <= r_full_sq {
+                            full.push(i as i64);
+                            true
+                        } else if dist_sq <= r_lod_sq {
+                            shell_seen += 1;
+                            let take = shell_seen % lod_stride == 0;
+                            if take {
+                                lod.push(i as i64);
+                            }
+                            take
+                        } else {
+                            false
+                        }
+                    });
+                }
+            }
+        }
+
+        self.found_full_buffer.extend(full);
+        self.found_lod_buffer.extend(lod);
+    }
+
+    /// Squared distance along one axis from a coordinate to the slab of bin `index`
+    fn axis_gap_sq(&self, axis: usize, index: i64, coord: f64) -> f64 {
+        let lower = self.grid_origin[axis] + index as f64 * self.bin_widths[axis];
+        let upper = lower + self.bin_widths[axis];
+        let gap = if coord < lower {
+            lower - coord
+        } else if coord > upper {
+            coord - upper
+        } else {
+            0.0
+        };
+        gap * gap
+    }
+
+    /// Unlink every point of one bin accepted by `matches` and mark it removed
+    ///
+    /// `matches` receives each member's sorted index and coordinates. Removed points
+    /// are appended to the found buffer when `record_found` is set.
+    fn unlink_matching_in_bin<F>(&mut self, bin: [usize; 3], record_found: bool, mut matches: F)
+    where
+        F: FnMut(usize, ArrayView1<f64>) -> bool,
+    {
+        let mut prev: i64 = -1;
+        let mut i = self.first_member[bin];
+
+        // Traverse linked list
+        while i != -1 {
+            let next_i = self.next_member[i as usize];
+
+            if matches(i as usize, self.points.row(i as usize)) {
+                // Point found - remove from linked list
+                if prev == -1 {
+                    self.first_member[bin] = next_i;
+                } else {
+                    self.next_member[prev as usize] = next_i;
+                }
+
+                self.next_member[i as usize] = -2; // Mark as removed
+                self.removed_count += 1;
+                if record_found {
+                    self.found_indices_buffer.push(i);
+                }
+            } else {
+                prev = i;
+            }
+            i = next_i;
+        }
+    }
+
+    /// Apply a homogeneous transform to every point and rebin the structure
+    ///
+    /// Each point `p` becomes `(A · [p, 1])[..3] / (A · [p, 1])[3]`, so rigid and affine
+    /// transforms (bottom row `[0, 0, 0, 1]`) apply directly. Because bin assignments
+    /// change, the structure is rebuilt from scratch: this costs as much as `new`
+    /// (O(n log n)), recomputes the origin and bin shape, restores all removed points,
+    /// and clears found results as `reset()` does. Bin widths, point radii and the
+    /// reset backup setting are kept.
+    ///
+    /// # Arguments
+    /// * `affine` - 4x4 homogeneous transform matrix
+    ///
+    /// # Panics
+    /// Panics if affine is not 4x4
+    pub fn transform_points(&mut self, affine: &ArrayView2<f64>) {
+        assert_eq!(affine.dim(), (4, 4), "Transform must be a 4x4 matrix");
+
+        let n_points = self.original_points.nrows();
+        let mut transformed = Array2::<f64>::zeros((n_points, 3));
+        for i in 0..n_points {
+            let p = self.original_points.row(i);
+            let mut homogeneous = [0.0; 4];
+            for (r, value) in homogeneous.iter_mut().enumerate() {
+                *value = affine[[r, 0]] * p[0] + affine[[r, 1]] * p[1] + affine[[r, 2]] * p[2] + affine[[r, 3]];
+            }
+            for j in 0..3 {
+                transformed[[i, j]] = homogeneous[j] / homogeneous[3];
+            }
+        }
+
+        let mut builder = Self::builder(transformed, self.bin_widths.clone())
+            .keep_reset_backups(self.original_first_member.is_some());
+        if let Some(point_radii) = self.original_order_point_radii() {
+            builder = builder.point_radii(point_radii);
+        }
+        *self = builder.build();
+    }
+
+    /// Move the origin by a whole number of bins without rebinning
+    ///
+    /// The origin reported by `origin()` moves by `shift * bin_widths` and the bins are
+    /// relabelled to match: bin `(i, j, k)` now spans
+    /// `origin() + ([i, j, k] - shift) * bin_widths` along each axis. No point moves
+    /// and no point changes bin, so searches return exactly what they did before, and
+    /// this is an O(1) update of the grid metadata. Only whole-bin shifts are supported,
+    /// keeping the grid on the same lattice; any other change of origin needs a rebin.
+    /// Shifts accumulate and are reported by `grid_metadata()` as `bin_offset`.
+    ///
+    /// # Arguments
+    /// * `shift` - Number of bins to move the origin by along x, y, z
+    pub fn shift_origin_by_bins(&mut self, shift: [i64; 3]) {
+        for (offset, shift) in self.bin_offset.iter_mut().zip(shift) {
+            *offset += shift;
+        }
+        // Computed from the unshifted origin so repeated shifts don't accumulate rounding
+        self.reported_origin = Array1::from_shape_fn(3, |j| self.grid_origin[j] + self.bin_offset[j] as f64 * self.bin_widths[j]);
+    }
+
+    /// Per-point radii in original point order, if the structure has them
+    fn original_order_point_radii(&self) -> Option<Array1<f64>> {
+        self.point_radii.as_ref().map(|sorted_radii| {
+            let mut radii = Array1::<f64>::zeros(sorted_radii.len());
+            for (i, &radius) in sorted_radii.iter().enumerate() {
+                radii[self.original_indices[i] as usize] = radius;
+            }
+            radii
+        })
+    }
+
+    /// Find the points whose influence sphere contains a query point
+    ///
+    /// This is the inverse of a radius search: a point `p` matches when
+    /// `dist(p, query) <= point_radii[p]`. Only bins within the largest point radius
+    /// of the query are visited. The structure is not modified.
+    ///
+    /// # Arguments
+    /// * `query_point` - 3D point to test against every influence sphere
+    ///
+    /// # Returns
+    /// 1D array of original indices of the enclosing points
+    ///
+    /// # Panics
+    /// Panics if the structure was not built with `new_with_radii`
+    pub fn reverse_radius_search(&self, query_point: &ArrayView1<f64>) -> Array1<i64> {
+        assert_eq!(query_point.len(), 3, "Query point must have 3 dimensions");
+        let point_radii = self
+            .point_radii
+            .as_ref()
+            .expect("Reverse radius search requires a structure built with new_with_radii");
+
+        let (min_bin, max_bin) = self.bin_range(query_point, self.max_point_radius);
+        let mut result = Vec::new();
+        let _ = self.visit_in_bins(min_bin, max_bin, |i| {
+            let radius = point_radii[i];
+            if self.dist_sq_to(i, query_point) <= radius * radius {
+                result.push(self.original_indices[i]);
+            }
+            ControlFlow::Continue(())
+        });
+
+        Array1::from(result)
+    }
+
+    /// Find the points of `a` that have no counterpart in `b`
+    ///
+    /// A point of `a` is matched if any point of `b` that is still in the structure lies
+    /// within `tolerance` of it. Useful for finding the points added between two scans.
+    /// Neither structure is modified.
+    ///
+    /// # Arguments
+    /// * `a` - Structure whose remaining points are tested
+    /// * `b` - Structure searched for matches
+    /// * `tolerance` - Maximum distance at which two points are considered the same
+    ///
+    /// # Returns
+    /// 2D array of shape (n_unmatched, 3) with the unmatched points of `a`, in original index order
+    pub fn difference(a: &PointBin3D, b: &PointBin3D, tolerance: f64) -> Array2<f64> {
+        let mut unmatched: Vec<usize> = Vec::new();
+        for i in 0..a.points.nrows() {
+            if a.is_removed(i) {
+                continue;
+            }
+            if !b.any_within(&a.points.row(i), tolerance) {
+                unmatched.push(a.original_indices[i] as usize);
+            }
+        }
+        unmatched.sort_unstable();
+
+        let mut result = Array2::<f64>::zeros((unmatched.len(), 3));
+        for (row, &orig_idx) in unmatched.iter().enumerate() {
+            result.row_mut(row).assign(&a.original_points.row(orig_idx));
+        }
+        result
+    }
+
+    /// Compute the clamped range of bins overlapped by the cube around a query point
+    ///
+    /// Returns inclusive `(min_bin, max_bin)` bin coordinates. The range is empty
+    /// (some `min_bin[j] > max_bin[j]`) when the cube misses the grid entirely.
+    pub(crate) fn bin_range(&self, query_point: &ArrayView1<f64>, radius: f64) -> ([i64; 3], [i64; 3]) {
+        let mut min_bin = [0i64; 3];
+        let mut max_bin = [0i64; 3];
+
+        for j in 0..3 {
+            min_bin[j] = ((query_point[j] - radius - self.grid_origin[j]) / self.bin_widths[j]).floor() as i64;
+            max_bin[j] = ((query_point[j] + radius - self.grid_origin[j]) / self.bin_widths[j]).floor() as i64;
+
+            // Clamp to valid range
+            min_bin[j] = min_bin[j].max(0);
+            max_bin[j] = max_bin[j].min(self.bin_shape[j] - 1);
+        }
+
+        (min_bin, max_bin)
+    }
+
+    /// Get the clamped range of bins overlapping a box centred on a query point
+    fn bin_range_box(&self, query_point: &ArrayView1<f64>, half_extents: [f64; 3]) -> ([i64; 3], [i64; 3]) {
+        let mut min_bin = [0i64; 3];
+        let mut max_bin = [0i64; 3];
+
+        for j in 0..3 {
+            min_bin[j] = ((query_point[j] - half_extents[j] - self.grid_origin[j]) / self.bin_widths[j]).floor() as i64;
+            max_bin[j] = ((query_point[j] + half_extents[j] - self.grid_origin[j]) / self.bin_widths[j]).floor() as i64;
+            min_bin[j] = min_bin[j].max(0);
+            max_bin[j] = max_bin[j].min(self.bin_shape[j] - 1);
+        }
+
+        (min_bin, max_bin)
+    }
+
+    /// Visit every point still in the structure within `radius` of a query point
+    ///
+    /// The visitor receives the sorted index and squared distance of each match and can
+    /// stop the traversal early by returning `ControlFlow::Break`.
+    pub(crate) fn visit_within<F>(&self, query_point: &ArrayView1<f64>, radius: f64, mut visit: F) -> ControlFlow<()>
+    where
+        F: FnMut(usize, f64) -> ControlFlow<()>,
+    {
+        let (min_bin, max_bin) = self.bin_range(query_point, radius);
+        let radius_sq = radius * radius;
+
+        self.visit_in_bins(min_bin, max_bin, |i| {
+            let dist_sq = self.dist_sq_to(i, query_point);
+            if dist_sq <= radius_sq {
+                visit(i, dist_sq)?;
+            }
+            ControlFlow::Continue(())
+        })
+    }
+
+    /// Visit every point still in the structure whose bin lies in an inclusive bin range
+    ///
+    /// The visitor receives sorted indices and can stop early by returning `ControlFlow::Break`.
+    pub(crate) fn visit_in_bins<F>(&self, min_bin: [i64; 3], max_bin: [i64; 3], mut visit: F) -> ControlFlow<()>
+    where
+        F: FnMut(usize) -> ControlFlow<()>,
+    {
+        for ix in min_bin[0]..=max_bin[0] {
+            for iy in min_bin[1]..=max_bin[1] {
+                for iz in min_bin[2]..=max_bin[2] {
+                    let mut i = self.first_member[[ix as usize, iy as usize, iz as usize]];
+                    while i != -1 {
+                        visit(i as usize)?;
+                        i = self.next_member[i as usize];
+                    }
+                }
+            }
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    /// Check whether any point still in the structure lies within `radius` of a query point
+    pub(crate) fn any_within(&self, query_point: &ArrayView1<f64>, radius: f64) -> bool {
+        self.visit_within(query_point, radius, |_, _| ControlFlow::Break(()))
+            .is_break()
+    }
+
+    /// Original indices of the points not removed by a search, ascending
+    pub(crate) fn remaining_original_indices(&self) -> Vec<usize> {
+        let mut remaining: Vec<usize> = (0..self.points.nrows())
+            .filter(|&i| !self.is_removed(i))
+            .map(|i| self.original_indices[i] as usize)
+            .collect();
+        remaining.sort_unstable();
+        remaining
+    }
+
+    /// Squared distance between the point at a sorted index and a query point
+    fn dist_sq_to(&self, sorted_idx: usize, query_point: &ArrayView1<f64>) -> f64 {
+        squared_distance(&self.points.row(sorted_idx), query_point)
+    }
+
+    /// Whether the point at a sorted index has been removed by a search
+    fn is_removed(&self, sorted_idx: usize) -> bool {
+        self.next_member[sorted_idx] == -2
+    }
+
+    /// Get the original indices of all found points
+    ///
+    /// Returns the indices into the original points array that were found
+    /// across all radius searches since the last reset or `clear_found`.
+    ///
+    /// # Returns
+    /// 1D array of original point indices
+    pub fn found_indices(&self) -> Array1<i64> {
+        self.found_indices_buffer
+            .iter()
+            .map(|&sorted_idx| self.original_indices[sorted_idx as usize])
+            .collect()
+    }
+
+    /// Get the coordinates of all found points
+    ///
+    /// # Returns
+    /// 2D array of shape (n_found, 3), in the same order as `found_indices`
+    pub fn found_points(&self) -> Array2<f64> {
+        let mut found = Array2::<f64>::zeros((self.found_indices_buffer.len(), 3));
+        for (mut row, &sorted_idx) in found.rows_mut().into_iter().zip(&self.found_indices_buffer) {
+            row.assign(&self.points.row(sorted_idx as usize));
+        }
+        found
+    }
+
+    /// Get the coordinates of all found points as separate x, y and z columns
+    ///
+    /// # Returns
+    /// `(x, y, z)` 1D arrays, in the same order as `found_indices`
+    pub fn found_points_columns(&self) -> (Array1<f64>, Array1<f64>, Array1<f64>) {
+        let column = |j: usize| {
+            self.found_indices_buffer
+                .iter()
+                .map(|&sorted_idx| self.points[[sorted_idx as usize, j]])
+                .collect()
+        };
+        (column(0), column(1), column(2))
+    }
+
+    /// Get the found original indices sorted ascending with duplicates removed
+    ///
+    /// Destructive searches never report a point twice, but keep-mode searches
+    /// (`radius_search_keep`) can, e.g. when their spheres overlap.
+    pub fn found_indices_sorted_unique(&self) -> Array1<i64> {
+        let mut indices = self.found_indices().to_vec();
+        indices.sort_unstable();
+        indices.dedup();
+        Array1::from(indices)
+    }
+
+    /// Get the occupancy of every bin as a boolean voxel grid
+    ///
+    /// # Returns
+    /// 3D array of shape (bin_shape[0], bin_shape[1], bin_shape[2]) that is `true` where
+    /// at least one point not yet removed by a search lies in the bin
+    pub fn to_occupancy_grid(&self) -> Array3<bool> {
+        self.first_member.mapv(|head| head != -1)
+    }
+
+    /// Get the number of points in every bin as a voxel grid
+    ///
+    /// Like `to_occupancy_grid`, this reflects the current state: points removed by
+    /// searches are not counted.
+    ///
+    /// # Returns
+    /// 3D array of shape (bin_shape[0], bin_shape[1], bin_shape[2]) with per-bin counts
+    pub fn to_count_grid(&self) -> Array3<u32> {
+        Array3::from_shape_fn(self.first_member.dim(), |bin| {
+            let mut count = 0;
+            let mut i = self.first_member[bin];
+            while i != -1 {
+                count += 1;
+                i = self.next_member[i as usize];
+            }
+            count
+        })
+    }
+
+    /// Find the bins in a box that contain no points
+    ///
+    /// Enumerates every bin overlapping the axis-aligned box between `min_corner` and
+    /// `max_corner` and reports those without a remaining member, i.e. holes in the
+    /// coverage. Parts of the box outside the grid are ignored.
+    ///
+    /// # Arguments
+    /// * `min_corner` - Lower corner of the box
+    /// * `max_corner` - Upper corner of the box
+    ///
+    /// # Returns
+    /// 2D array of shape (n_empty, 3) with the bin coordinates of the empty bins, in
+    /// row-major order
+    ///
+    /// # Panics
+    /// Panics if either corner doesn't have exactly 3 elements
+    pub fn empty_bins_in_range(&self, min_corner: &ArrayView1<f64>, max_corner: &ArrayView1<f64>) -> Array2<i64> {
+        assert_eq!(min_corner.len(), 3, "Min corner must have 3 dimensions");
+        assert_eq!(max_corner.len(), 3, "Max corner must have 3 dimensions");
+
+        let mut min_bin = [0i64; 3];
+        let mut max_bin = [0i64; 3];
+        for j in 0..3 {
+            min_bin[j] = (((min_corner[j] - self.grid_origin[j]) / self.bin_widths[j]).floor() as i64).max(0);
+            max_bin[j] = (((max_corner[j] - self.grid_origin[j]) / self.bin_widths[j]).floor() as i64).min(self.bin_shape[j] - 1);
+        }
+
+        let mut empty = Vec::new();
+        for ix in min_bin[0]..=max_bin[0] {
+            for iy in min_bin[1]..=max_bin[1] {
+                for iz in min_bin[2]..=max_bin[2] {
+                    if self.first_member[[ix as usize, iy as usize, iz as usize]] == -1 {
+                        empty.extend_from_slice(&[ix, iy, iz]);
+                    }
+                }
+            }
+        }
+
+        Array2::from_shape_vec((empty.len() / 3, 3), empty).expect("three coordinates per bin")
+    }
+
+    /// Remove every point in a box without reporting it as found
+    ///
+    /// Points with all coordinates between `min_corner` and `max_corner` (inclusive) are
+    /// removed like a search removes its matches, so later searches skip them and
+    /// `remaining_count()` drops, but they are not added to the found indices. Use this
+    /// to mask out a region while keeping the found results for actual matches. `reset()`
+    /// restores the masked points along with everything else.
+    ///
+    /// # Arguments
+    /// * `min_corner` - Lower corner of the box
+    /// * `max_corner` - Upper corner of the box
+    ///
+    /// # Panics
+    /// Panics if either corner doesn't have exactly 3 elements
+    pub fn remove_region(&mut self, min_corner: &ArrayView1<f64>, max_corner: &ArrayView1<f64>) {
+        assert_eq!(min_corner.len(), 3, "Min corner must have 3 dimensions");
+        assert_eq!(max_corner.len(), 3, "Max corner must have 3 dimensions");
+
+        let mut min_bin = [0i64; 3];
+        let mut max_bin = [0i64; 3];
+        for j in 0..3 {
+            min_bin[j] = (((min_corner[j] - self.grid_origin[j]) / self.bin_widths[j]).floor() as i64).max(0);
+            max_bin[j] = (((max_corner[j] - self.grid_origin[j]) / self.bin_widths[j]).floor() as i64).min(self.bin_shape[j] - 1);
+        }
+
+        for ix in min_bin[0]..=max_bin[0] {
+            for iy in min_bin[1]..=max_bin[1] {
+                for iz in min_bin[2]..=max_bin[2] {
+                    let bin = [ix as usize, iy as usize, iz as usize];
+                    self.unlink_matching_in_bin(bin, false, |_, point| {
+                        (0..3).all(|j| min_corner[j] <= point[j] && point[j] <= max_corner[j])
+                    });
+                }
+            }
+        }
+    }
+
+    /// Get the axis-aligned bounding box of the found points
+    ///
+    /// # Returns
+    /// `(min_corner, max_corner)` of the found points' coordinates, or `None` if
+    /// nothing has been found since the last reset or `clear_found`
+    pub fn found_bounds(&self) -> Option<(Array1<f64>, Array1<f64>)> {
+        if self.found_indices_buffer.is_empty() {
+            return None;
+        }
+
+        let mut min_corner = Array1::from_elem(3, f64::INFINITY);
+        let mut max_corner = Array1::from_elem(3, f64::NEG_INFINITY);
+        for &sorted_idx in &self.found_indices_buffer {
+            for j in 0..3 {
+                let value = self.points[[sorted_idx as usize, j]];
+                min_corner[j] = min_corner[j].min(value);
+                max_corner[j] = max_corner[j].max(value);
+            }
+        }
+
+        Some((min_corner, max_corner))
+    }
+
+    /// Get the confidences of all points found by `radius_search_confidence`
+    ///
+    /// Accumulates across searches since the last reset or `clear_found`. Entries line
+    /// up with `found_indices()` only if no other kind of search was run in between.
+    pub fn found_confidences(&self) -> Array1<f64> {
+        Array1::from(self.found_confidences_buffer.clone())
+    }
+
+    /// Get the original indices of points removed at full resolution by `lod_search`
+    pub fn found_full_indices(&self) -> Array1<i64> {
+        self.found_full_buffer
+            .iter()
+            .map(|&sorted_idx| self.original_indices[sorted_idx as usize])
+            .collect()
+    }
+
+    /// Get the original indices of points removed from the decimated shell by `lod_search`
+    pub fn found_lod_indices(&self) -> Array1<i64> {
+        self.found_lod_buffer
+            .iter()
+            .map(|&sorted_idx| self.original_indices[sorted_idx as usize])
+            .collect()
+    }
+
+    /// Merge the found indices of several shards into one global index list
+    ///
+    /// Each shard numbers its points locally from 0. Global indices are defined by
+    /// concatenating the shards' original points in the order given, so a point with
+    /// local index `i` in shard `s` has global index `i + sum(n_points of shards before s)`.
+    ///
+    /// # Arguments
+    /// * `shards` - Shards in the order their points appear in the global point list
+    ///
+    /// # Returns
+    /// 1D array of global point indices, shard by shard in search order
+    pub fn merge_found(shards: &[&PointBin3D]) -> Array1<i64> {
+        let total: usize = shards.iter().map(|shard| shard.found_count()).sum();
+        let mut result = Vec::with_capacity(total);
+
+        let mut offset: i64 = 0;
+        for shard in shards {
+            result.extend(shard.found_indices().iter().map(|&idx| idx + offset));
+            offset += shard.original_points.nrows() as i64;
+        }
+
+        Array1::from(result)
+    }
+
+    /// Reset the structure for a fresh search
+    ///
+    /// Restores all points and clears the found indices buffer.
+    ///
+    /// # Panics
+    /// Panics if the structure was built with `keep_reset_backups(false)`
+    pub fn reset(&mut self) {
+        let (Some(first_member), Some(next_member)) = (&self.original_first_member, &self.original_next_member) else {
+            panic!("reset() is unavailable: this PointBin3D was built with keep_reset_backups(false)");
+        };
+        self.first_member.assign(first_member);
+        self.next_member.assign(next_member);
+        self.found_indices_buffer.clear();
+        self.found_confidences_buffer.clear();
+        self.found_full_buffer.clear();
+        self.found_lod_buffer.clear();
+        self.found_pass_buffer.fill(0);
+        self.removed_count = 0;
+    }
+
+    /// Clear the found indices buffer without restoring any points
+    ///
+    /// Points removed by earlier searches stay removed, so later searches only report
+    /// new matches. After this call `found_count()` no longer equals the number of
+    /// removed points; use `remaining_count()` to track how many are left.
+    pub fn clear_found(&mut self) {
+        self.found_indices_buffer.clear();
+        self.found_confidences_buffer.clear();
+        self.found_full_buffer.clear();
+        self.found_lod_buffer.clear();
+    }
+
+    /// Consume the structure and iterate its points in bin-sorted order
+    ///
+    /// Yields `(original_index, [x, y, z])` for every point, including points removed
+    /// by searches, in the cache-friendly order used internally.
+    pub fn into_sorted_iter(self) -> impl Iterator<Item = (i64, [f64; 3])> {
+        let Self { points, original_indices, .. } = self;
+        (0..points.nrows()).map(move |i| {
+            (original_indices[i], [points[[i, 0]], points[[i, 1]], points[[i, 2]]])
+        })
+    }
+
+    /// Get the number of points found since the last reset or `clear_found`
+    pub fn found_count(&self) -> usize {
+        self.found_indices_buffer.len()
+    }
+
+    /// Get the number of points not yet removed by a search
+    pub fn remaining_count(&self) -> usize {
+        self.points.nrows() - self.removed_count
+    }
+
+    /// Map the original index of every remaining point to a contiguous index
+    ///
+    /// Remaining points are numbered `0..remaining_count()` in increasing order of their
+    /// original index, e.g. to use them as row indices of a matrix built after removals.
+    pub fn compact_index_map(&self) -> HashMap<i64, usize> {
+        let mut remaining: Vec<i64> = (0..self.points.nrows())
+            .filter(|&i| !self.is_removed(i))
+            .map(|i| self.original_indices[i])
+            .collect();
+        remaining.sort_unstable();
+
+        remaining
+            .into_iter()
+            .enumerate()
+            .map(|(compact, orig_idx)| (orig_idx, compact))
+            .collect()
+    }
+
+    /// Snapshot of the remaining points as a compact array
+    ///
+    /// Unlike `compact_index_map`, which keeps original order, rows come in bin-sorted
+    /// order, the order the structure stores them in. The structure is not modified.
+    ///
+    /// # Returns
+    /// 2D array of shape (remaining_count(), 3) with the coordinates of the points not
+    /// yet removed by a search, and the original index of each row
+    pub fn live_points(&self) -> (Array2<f64>, Array1<i64>) {
+        let live: Vec<usize> = (0..self.points.nrows()).filter(|&i| !self.is_removed(i)).collect();
+        let original_indices = live.iter().map(|&i| self.original_indices[i]).collect();
+        (self.points.select(Axis(0), &live), original_indices)
+    }
+
+    /// Get a reference to the original points
+    pub fn original_points(&self) -> &Array2<f64> {
+        &self.original_points
+    }
+
+    /// Get the bin shape
+    pub fn bin_shape(&self) -> &Array1<i64> {
+        &self.bin_shape
+    }
+
+    /// Get the origin, including any `shift_origin_by_bins` shift
+    pub fn origin(&self) -> &Array1<f64> {
+        &self.reported_origin
+    }
+
+    /// Get the bin widths
+    pub fn bin_widths(&self) -> &Array1<f64> {
+        &self.bin_widths
+    }
+}
+
+/// Deterministic test points spread over `[0, extent)`, stepping each axis by a different stride
+#[cfg(test)]
+fn scattered_points(n_points: usize, extent: [f64; 3]) -> Array2<f64> {
+    let strides = [1.234, 2.345, 3.456];
+    Array2::from_shape_fn((n_points, 3), |(i, j)| (i as f64 * strides[j]) % extent[j])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::{array, Axis, ShapeBuilder};
+    use approx::assert_abs_diff_eq;
+
+    #[test]
+    fn test_pointbin_creation() {
+        let points = array![
+            [0.5, 0.5, 0.5],
+            [3.0, 3.0, 3.0],
+            [6.0, 5.0, 5.0],
+        ];
+        let bin_widths = array![5.0, 5.0, 5.0];
+
+        let point_bin = PointBin3D::new(points, bin_widths);
+
+        assert_eq!(point_bin.original_points().nrows(), 3);
+        assert_abs_diff_eq!(point_bin.origin()[0], 0.5, epsilon = 1e-10);
+    }
+
+    #[test]
+    fn test_radius_search_basic() {
+        let points = array![
+            [0.5, 0.5, 0.5],  // Index 0
+            [3.0, 3.0, 3.0],  // Index 1
+            [6.0, 5.0, 5.0],  // Index 2
+        ];
+        let bin_widths = array![5.0, 5.0, 5.0];
+
+        let mut point_bin = PointBin3D::new(points, bin_widths);
+
+        // Search near point 2
+        let query = array![5.0, 5.0, 5.0];
+        point_bin.radius_search(&query.view(), 1.5);
+
+        let results = point_bin.found_indices();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0], 2);
+    }
+
+    #[test]
+    fn test_reset() {
+        let points = array![
+            [0.5, 0.5, 0.5],
+            [6.0, 5.0, 5.0],
+        ];
+        let bin_widths = array![5.0, 5.0, 5.0];
+
+        let mut point_bin = PointBin3D::new(points, bin_widths);
+
+        // First search
+        let query = array![5.0, 5.0, 5.0];
+        point_bin.radius_search(&query.view(), 1.5);
+        assert_eq!(point_bin.found_count(), 1);
+
+        // Reset
+        point_bin.reset();
+        assert_eq!(point_bin.found_count(), 0);
 
         // Search again - should find the same point
         point_bin.radius_search(&query.view(), 1.5);
         assert_eq!(point_bin.found_count(), 1);
     }
+
+    #[test]
+    fn test_merge_found() {
+        // Global points 0..2 live in shard A, 3..4 in shard B
+        let shard_a_points = array![
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [8.0, 8.0, 8.0],
+        ];
+        let shard_b_points = array![
+            [9.0, 9.0, 9.0],
+            [10.0, 0.0, 0.0],
+        ];
+        let bin_widths = array![2.0, 2.0, 2.0];
+
+        let mut shard_a = PointBin3D::new(shard_a_points, bin_widths.clone());
+        let mut shard_b = PointBin3D::new(shard_b_points, bin_widths);
+
+        let query = array![8.5, 8.5, 8.5];
+        shard_a.radius_search(&query.view(), 1.0);
+        shard_b.radius_search(&query.view(), 1.0);
+
+        let merged = PointBin3D::merge_found(&[&shard_a, &shard_b]);
+        let mut sorted_merged = merged.to_vec();
+        sorted_merged.sort();
+        assert_eq!(sorted_merged, vec![2, 3]);
+    }
+
+    #[test]
+    fn test_difference() {
+        let scan_a = array![
+            [0.0, 0.0, 0.0],
+            [1.0, 1.0, 1.0],
+            [4.0, 4.0, 4.0],  // New in scan A
+            [2.0, 2.0, 2.0],
+        ];
+        let scan_b = array![
+            [0.01, 0.0, 0.0],
+            [1.0, 1.01, 1.0],
+            [2.0, 2.0, 1.99],
+        ];
+        let bin_widths = array![1.0, 1.0, 1.0];
+
+        let a = PointBin3D::new(scan_a, bin_widths.clone());
+        let b = PointBin3D::new(scan_b, bin_widths);
+
+        let new_points = PointBin3D::difference(&a, &b, 0.05);
+        assert_eq!(new_points.nrows(), 1);
+        assert_eq!(new_points.row(0).to_vec(), vec![4.0, 4.0, 4.0]);
+
+        let removed_points = PointBin3D::difference(&b, &a, 0.05);
+        assert_eq!(removed_points.nrows(), 0);
+    }
+
+    #[test]
+    fn test_into_sorted_iter() {
+        let points = array![
+            [9.0, 9.0, 9.0],
+            [0.5, 0.5, 0.5],
+            [4.0, 0.0, 8.0],
+            [0.0, 0.0, 0.0],
+        ];
+        let bin_widths = array![2.0, 2.0, 2.0];
+
+        let mut point_bin = PointBin3D::new(points.clone(), bin_widths);
+        // Removed points are still yielded
+        let query = array![0.0, 0.0, 0.0];
+        point_bin.radius_search(&query.view(), 1.0);
+
+        let items: Vec<(i64, [f64; 3])> = point_bin.into_sorted_iter().collect();
+
+        let mut indices: Vec<i64> = items.iter().map(|&(idx, _)| idx).collect();
+        indices.sort();
+        assert_eq!(indices, vec![0, 1, 2, 3]);
+
+        for (idx, coords) in items {
+            assert_eq!(coords.to_vec(), points.row(idx as usize).to_vec());
+        }
+    }
+
+    #[test]
+    fn test_reverse_radius_search() {
+        let points = array![
+            [0.0, 0.0, 0.0],  // Radius 0.5: too small to reach the query at distance 1
+            [3.0, 0.0, 0.0],  // Radius 2.5: reaches the query at distance 2
+            [1.0, 1.0, 0.0],  // Radius 1.5: reaches the query at distance 1
+            [9.0, 9.0, 9.0],  // Radius 20.0: reaches across the whole grid
+        ];
+        let radii = array![0.5, 2.5, 1.5, 20.0];
+        let bin_widths = array![1.0, 1.0, 1.0];
+
+        let point_bin = PointBin3D::new_with_radii(points, bin_widths, radii);
+
+        let query = array![1.0, 0.0, 0.0];
+        let mut results = point_bin.reverse_radius_search(&query.view()).to_vec();
+        results.sort();
+        assert_eq!(results, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_transform_points_translation() {
+        let points = array![
+            [0.0, 0.0, 0.0],
+            [0.5, 0.5, 0.0],
+            [5.0, 5.0, 5.0],
+        ];
+        let radii = array![1.0, 2.0, 3.0];
+        let bin_widths = array![1.0, 1.0, 1.0];
+        let mut point_bin = PointBin3D::new_with_radii(points, bin_widths, radii);
+
+        let translation = array![
+            [1.0, 0.0, 0.0, 10.0],
+            [0.0, 1.0, 0.0, -3.0],
+            [0.0, 0.0, 1.0, 2.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ];
+        point_bin.transform_points(&translation.view());
+
+        assert_abs_diff_eq!(point_bin.origin()[0], 10.0, epsilon = 1e-10);
+        assert_abs_diff_eq!(point_bin.origin()[1], -3.0, epsilon = 1e-10);
+
+        // A query at the translated location finds the points that were near the origin
+        let query = array![10.0, -3.0, 2.0];
+        point_bin.radius_search(&query.view(), 1.0);
+        let mut results = point_bin.found_indices().to_vec();
+        results.sort();
+        assert_eq!(results, vec![0, 1]);
+
+        // Radii follow their points through the rebuild
+        let mut enclosing = point_bin.reverse_radius_search(&array![14.0, 2.0, 7.0].view()).to_vec();
+        enclosing.sort();
+        assert_eq!(enclosing, vec![2]);
+    }
+
+    #[test]
+    fn test_sphere_prefilter_matches_radius_search() {
+        let points = scattered_points(500, [20.0; 3]);
+        let bin_widths = array![1.0, 1.0, 1.0];
+
+        let mut plain = PointBin3D::new(points.clone(), bin_widths.clone());
+        let mut prefiltered = PointBin3D::new(points, bin_widths);
+
+        for (query, radius) in [(array![10.0, 10.0, 10.0], 6.5), (array![0.0, 19.0, 3.0], 4.0)] {
+            plain.radius_search(&query.view(), radius);
+            prefiltered.radius_search_sphere_prefilter(&query.view(), radius);
+        }
+
+        let mut expected = plain.found_indices().to_vec();
+        let mut actual = prefiltered.found_indices().to_vec();
+        expected.sort();
+        actual.sort();
+        assert!(!expected.is_empty());
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_clear_found() {
+        let points = array![
+            [0.0, 0.0, 0.0],
+            [0.5, 0.0, 0.0],
+            [5.0, 5.0, 5.0],
+            [5.5, 5.0, 5.0],
+            [9.0, 9.0, 9.0],
+        ];
+        let bin_widths = array![1.0, 1.0, 1.0];
+        let mut point_bin = PointBin3D::new(points, bin_widths);
+
+        point_bin.radius_search(&array![0.0, 0.0, 0.0].view(), 1.0);
+        assert_eq!(point_bin.found_count(), 2);
+
+        point_bin.clear_found();
+        assert_eq!(point_bin.found_count(), 0);
+        assert_eq!(point_bin.remaining_count(), 3);
+
+        // The cleared points stay removed, so only the new matches are reported
+        point_bin.radius_search(&array![0.0, 0.0, 0.0].view(), 1.0);
+        point_bin.radius_search(&array![5.0, 5.0, 5.0].view(), 1.0);
+        let mut found = point_bin.found_indices().to_vec();
+        found.sort();
+        assert_eq!(found, vec![2, 3]);
+        assert_eq!(point_bin.remaining_count(), 1);
+
+        point_bin.reset();
+        assert_eq!(point_bin.remaining_count(), 5);
+    }
+
+    #[test]
+    fn test_compact_index_map() {
+        let points = array![
+            [0.0, 0.0, 0.0],
+            [3.0, 3.0, 3.0],
+            [0.5, 0.0, 0.0],
+            [6.0, 6.0, 6.0],
+            [9.0, 9.0, 9.0],
+        ];
+        let bin_widths = array![1.0, 1.0, 1.0];
+        let mut point_bin = PointBin3D::new(points, bin_widths);
+
+        point_bin.radius_search(&array![0.0, 0.0, 0.0].view(), 1.0);
+        point_bin.radius_search(&array![6.0, 6.0, 6.0].view(), 1.0);
+
+        let map = point_bin.compact_index_map();
+        assert_eq!(map.len(), point_bin.remaining_count());
+        assert_eq!(map[&1], 0);
+        assert_eq!(map[&4], 1);
+        assert!(!map.contains_key(&0));
+    }
+
+    #[test]
+    fn test_radius_search_two_radii() {
+        let points = array![
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 2.5, 0.0],
+            [3.0, 0.0, 0.0],
+            [0.0, 0.0, 5.0],
+        ];
+        let bin_widths = array![1.0, 1.0, 1.0];
+        let mut point_bin = PointBin3D::new(points, bin_widths);
+
+        let (inner, outer) = point_bin.radius_search_two_radii(&array![0.0, 0.0, 0.0].view(), 1.0, 3.0);
+        let mut inner = inner.to_vec();
+        let mut outer = outer.to_vec();
+        inner.sort();
+        outer.sort();
+        assert_eq!(inner, vec![0, 1]);
+        assert_eq!(outer, vec![2, 3]);
+
+        // Both shells were removed
+        assert_eq!(point_bin.found_count(), 4);
+        assert_eq!(point_bin.remaining_count(), 1);
+    }
+
+    #[test]
+    fn test_radius_search_keep_exceeds_n_points() {
+        let points = array![
+            [0.0, 0.0, 0.0],
+            [0.5, 0.5, 0.5],
+            [1.0, 1.0, 1.0],
+        ];
+        let bin_widths = array![1.0, 1.0, 1.0];
+        let mut point_bin = PointBin3D::new(points, bin_widths);
+
+        for _ in 0..10 {
+            point_bin.radius_search_keep(&array![0.5, 0.5, 0.5].view(), 2.0);
+        }
+        assert_eq!(point_bin.found_count(), 30);
+        assert_eq!(point_bin.found_indices().len(), 30);
+        assert_eq!(point_bin.remaining_count(), 3);
+
+        // A destructive search afterwards still finds every point once more
+        point_bin.radius_search(&array![0.5, 0.5, 0.5].view(), 2.0);
+        assert_eq!(point_bin.found_count(), 33);
+    }
+
+    #[test]
+    fn test_lod_search() {
+        // Two points near the query and six on a line further out
+        let points = array![
+            [0.0, 0.0, 0.0],
+            [0.5, 0.0, 0.0],
+            [2.0, 0.0, 0.0],
+            [2.5, 0.0, 0.0],
+            [3.0, 0.0, 0.0],
+            [3.5, 0.0, 0.0],
+            [4.0, 0.0, 0.0],
+            [4.5, 0.0, 0.0],
+        ];
+        let bin_widths = array![10.0, 10.0, 10.0];
+        let mut point_bin = PointBin3D::new(points, bin_widths);
+
+        point_bin.lod_search(&array![0.0, 0.0, 0.0].view(), 1.0, 5.0, 3);
+
+        let mut full = point_bin.found_full_indices().to_vec();
+        full.sort();
+        assert_eq!(full, vec![0, 1]);
+        assert_eq!(point_bin.found_lod_indices().len(), 2);
+        assert_eq!(point_bin.found_count(), 0);
+        assert_eq!(point_bin.remaining_count(), 4);
+
+        point_bin.reset();
+        assert_eq!(point_bin.found_full_indices().len(), 0);
+        assert_eq!(point_bin.found_lod_indices().len(), 0);
+    }
+
+    #[test]
+    fn test_found_indices_sorted_unique() {
+        let points = array![
+            [3.0, 0.0, 0.0],
+            [0.0, 0.0, 0.0],
+            [1.5, 0.0, 0.0],
+            [9.0, 9.0, 9.0],
+        ];
+        let bin_widths = array![1.0, 1.0, 1.0];
+        let mut point_bin = PointBin3D::new(points, bin_widths);
+
+        // Point 2 lies in both spheres, so it is reported twice
+        point_bin.radius_search_keep(&array![0.0, 0.0, 0.0].view(), 1.6);
+        point_bin.radius_search_keep(&array![3.0, 0.0, 0.0].view(), 1.6);
+        assert_eq!(point_bin.found_count(), 4);
+
+        assert_eq!(point_bin.found_indices_sorted_unique(), array![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_radius_search_confidence() {
+        let points = array![
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 2.0, 0.0],
+            [5.0, 5.0, 5.0],
+        ];
+        let bin_widths = array![1.0, 1.0, 1.0];
+        let mut point_bin = PointBin3D::new(points, bin_widths);
+
+        let (indices, confidences) = point_bin.radius_search_confidence(&array![0.0, 0.0, 0.0].view(), 2.0, 1.0);
+        assert_eq!(indices.len(), 3);
+        for (&idx, &confidence) in indices.iter().zip(confidences.iter()) {
+            let dist_sq = [0.0, 1.0, 4.0][idx as usize];
+            assert_abs_diff_eq!(confidence, f64::exp(-dist_sq / 2.0), epsilon = 1e-12);
+        }
+        assert_eq!(point_bin.found_confidences(), confidences);
+        assert_eq!(point_bin.found_indices(), indices);
+
+        point_bin.reset();
+        assert_eq!(point_bin.found_confidences().len(), 0);
+    }
+
+    #[test]
+    fn test_found_bounds() {
+        let points = array![
+            [0.0, 0.0, 0.0],
+            [1.0, -0.5, 0.25],
+            [-0.5, 0.75, 0.0],
+            [5.0, 5.0, 5.0],
+        ];
+        let bin_widths = array![1.0, 1.0, 1.0];
+        let mut point_bin = PointBin3D::new(points, bin_widths);
+        assert_eq!(point_bin.found_bounds(), None);
+
+        point_bin.radius_search(&array![0.0, 0.0, 0.0].view(), 1.5);
+        let (min_corner, max_corner) = point_bin.found_bounds().unwrap();
+        assert_eq!(min_corner, array![-0.5, -0.5, 0.0]);
+        assert_eq!(max_corner, array![1.0, 0.75, 0.25]);
+    }
+
+    #[test]
+    fn test_to_occupancy_grid() {
+        let points = array![
+            [0.5, 0.5, 0.5],
+            [2.5, 0.5, 1.5],
+            [2.5, 1.5, 0.5],
+        ];
+        let bin_widths = array![1.0, 1.0, 1.0];
+        let mut point_bin = PointBin3D::new(points, bin_widths);
+
+        let grid = point_bin.to_occupancy_grid();
+        assert_eq!(grid.dim(), (3, 2, 2));
+        assert_eq!(grid.iter().filter(|&&occupied| occupied).count(), 3);
+        assert!(grid[[0, 0, 0]] && grid[[2, 0, 1]] && grid[[2, 1, 0]]);
+
+        point_bin.radius_search(&array![0.5, 0.5, 0.5].view(), 0.1);
+        assert!(!point_bin.to_occupancy_grid()[[0, 0, 0]]);
+    }
+
+    #[test]
+    fn test_radius_search_clipped() {
+        let points = array![
+            [0.0, 0.0, 0.0],
+            [2.0, 2.0, 2.0],
+            [3.9, 3.9, 3.9],
+        ];
+        let bin_widths = array![1.0, 1.0, 1.0];
+        let point_bin = PointBin3D::new(points, bin_widths);
+
+        // The grid spans [0, 4) on each axis
+        let (found, clipped) = point_bin.radius_search_clipped(&array![0.2, 0.5, 0.5].view(), 1.0);
+        assert_eq!(found, array![0]);
+        assert!(clipped);
+
+        let (found, clipped) = point_bin.radius_search_clipped(&array![2.0, 2.0, 2.0].view(), 1.0);
+        assert_eq!(found, array![1]);
+        assert!(!clipped);
+        assert_eq!(point_bin.found_count(), 0);
+    }
+
+    #[test]
+    fn test_to_count_grid() {
+        let points = array![
+            [0.5, 0.5, 0.5],
+            [0.6, 0.6, 0.6],
+            [0.7, 0.7, 0.7],
+            [1.5, 1.5, 1.5],
+        ];
+        let bin_widths = array![1.0, 1.0, 1.0];
+        let mut point_bin = PointBin3D::new(points, bin_widths);
+
+        let counts = point_bin.to_count_grid();
+        assert_eq!(counts[[0, 0, 0]], 3);
+        assert_eq!(counts[[1, 1, 1]], 1);
+        assert_eq!(counts.sum(), 4);
+
+        point_bin.radius_search(&array![0.5, 0.5, 0.5].view(), 0.2);
+        let counts = point_bin.to_count_grid();
+        assert_eq!(counts[[0, 0, 0]], 1);
+        assert_eq!(counts.sum() as usize, point_bin.remaining_count());
+    }
+
+    #[test]
+    fn test_radius_search_accumulate_across_resets() {
+        let points = array![
+            [0.0, 0.0, 0.0],
+            [0.5, 0.0, 0.0],
+            [5.0, 5.0, 5.0],
+            [9.0, 9.0, 9.0],
+        ];
+        let bin_widths = array![1.0, 1.0, 1.0];
+        let mut point_bin = PointBin3D::new(points, bin_widths);
+        let mut acc = HashSet::new();
+
+        // Phase 1
+        point_bin.radius_search_accumulate(&array![0.0, 0.0, 0.0].view(), 1.0, &mut acc);
+        point_bin.reset();
+
+        // Phase 2 finds point 0 again along with point 2
+        point_bin.radius_search_accumulate(&array![0.0, 0.0, 0.0].view(), 0.1, &mut acc);
+        point_bin.radius_search_accumulate(&array![5.0, 5.0, 5.0].view(), 0.1, &mut acc);
+
+        assert_eq!(point_bin.found_count(), 2);
+        assert_eq!(acc, HashSet::from([0, 1, 2]));
+    }
+
+    #[test]
+    fn test_radius_search_inward_order() {
+        let points = array![
+            [0.0, 0.0, 0.0],
+            [1.5, 0.0, 0.0],
+            [0.0, 2.5, 0.0],
+            [0.0, 0.0, 0.5],
+            [9.0, 9.0, 9.0],
+        ];
+        let bin_widths = array![1.0, 1.0, 1.0];
+        let mut point_bin = PointBin3D::new(points, bin_widths);
+
+        point_bin.radius_shell_search(&array![0.0, 0.0, 0.0].view(), 1.0, 2.0);
+        assert_eq!(point_bin.found_indices(), array![1]);
+
+        point_bin.reset();
+        point_bin.radius_search_inward(&array![0.0, 0.0, 0.0].view(), 3.0, 1.0);
+        let found = point_bin.found_indices();
+        assert_eq!(found.slice(ndarray::s![..2]), array![2, 1]);
+        let mut core = found.slice(ndarray::s![2..]).to_vec();
+        core.sort();
+        assert_eq!(core, vec![0, 3]);
+    }
+
+    #[test]
+    fn test_radius_search_inward_radii() {
+        let points = array![[0.0, 0.0, 0.0], [0.15, 0.0, 0.0], [0.25, 0.0, 0.0], [0.35, 0.0, 0.0]];
+        let mut point_bin = PointBin3D::new(points, array![1.0, 1.0, 1.0]);
+
+        // 0.3 / 0.1 rounds just below 3, which must still give three shells
+        point_bin.radius_search_inward(&array![0.0, 0.0, 0.0].view(), 0.3, 0.1);
+        assert_eq!(point_bin.found_indices(), array![2, 1, 0]);
+
+        point_bin.reset();
+        point_bin.radius_search_inward(&array![0.0, 0.0, 0.0].view(), 0.0, 1.0);
+        assert_eq!(point_bin.found_indices(), array![0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Maximum radius must be finite and non-negative")]
+    fn test_radius_search_inward_nan_radius() {
+        let mut point_bin = PointBin3D::new(array![[0.0, 0.0, 0.0]], array![1.0, 1.0, 1.0]);
+        point_bin.radius_search_inward(&array![0.0, 0.0, 0.0].view(), f64::NAN, 1.0);
+    }
+
+    #[test]
+    fn test_empty_bins_in_range() {
+        // 3x3 layer of bins with the centre one left empty
+        let mut rows = Vec::new();
+        for ix in 0..3 {
+            for iy in 0..3 {
+                if (ix, iy) != (1, 1) {
+                    rows.extend_from_slice(&[ix as f64 + 0.5, iy as f64 + 0.5, 0.5]);
+                }
+            }
+        }
+        let points = Array2::from_shape_vec((8, 3), rows).unwrap();
+        let bin_widths = array![1.0, 1.0, 1.0];
+        let mut point_bin = PointBin3D::new(points, bin_widths);
+
+        // Box extending well past the grid on every side
+        let holes = point_bin.empty_bins_in_range(&array![-5.0, -5.0, -5.0].view(), &array![10.0, 10.0, 10.0].view());
+        assert_eq!(holes, array![[1, 1, 0]]);
+
+        // Box not touching the hole
+        let holes = point_bin.empty_bins_in_range(&array![-1.0, -1.0, 0.0].view(), &array![0.5, 2.5, 0.5].view());
+        assert_eq!(holes.nrows(), 0);
+
+        // Box entirely outside the grid
+        let holes = point_bin.empty_bins_in_range(&array![5.0, 5.0, 5.0].view(), &array![6.0, 6.0, 6.0].view());
+        assert_eq!(holes.nrows(), 0);
+
+        // Removed points leave new holes behind
+        point_bin.radius_search(&array![0.5, 0.5, 0.5].view(), 0.1);
+        let holes = point_bin.empty_bins_in_range(&array![0.0, 0.0, 0.0].view(), &array![1.5, 1.5, 0.5].view());
+        assert_eq!(holes, array![[0, 0, 0], [1, 1, 0]]);
+    }
+
+    #[test]
+    fn test_found_points_columns() {
+        let points = array![
+            [0.0, 0.1, 0.2],
+            [0.5, 0.6, 0.7],
+            [5.0, 5.0, 5.0],
+            [-0.3, 0.2, -0.1],
+        ];
+        let bin_widths = array![1.0, 1.0, 1.0];
+        let mut point_bin = PointBin3D::new(points.clone(), bin_widths);
+
+        point_bin.radius_search(&array![0.0, 0.0, 0.0].view(), 1.5);
+        let found = point_bin.found_points();
+        assert_eq!(found.nrows(), 3);
+        for (row, &idx) in found.rows().into_iter().zip(point_bin.found_indices().iter()) {
+            assert_eq!(row, points.row(idx as usize));
+        }
+
+        let (x, y, z) = point_bin.found_points_columns();
+        assert_eq!(ndarray::stack![Axis(1), x, y, z], found);
+
+        point_bin.clear_found();
+        let (x, _, _) = point_bin.found_points_columns();
+        assert!(x.is_empty());
+    }
+
+    #[test]
+    fn test_radius_search_projected() {
+        // Points along x and along y, 3 units from the origin
+        let points = array![
+            [3.0, 0.0, 0.0],
+            [-3.0, 0.0, 0.0],
+            [0.0, 3.0, 0.0],
+            [0.0, 0.0, 0.5],
+            [20.0, 0.0, 0.0],
+        ];
+        let bin_widths = array![1.0, 1.0, 1.0];
+        let mut point_bin = PointBin3D::new(points, bin_widths);
+
+        // Squash x by 4: the unit ball becomes an ellipsoid reaching 4 units along x
+        let transform = array![[0.25, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+        point_bin.radius_search_projected(&array![0.0, 0.0, 0.0].view(), 1.0, &transform.view());
+
+        let mut found = point_bin.found_indices().to_vec();
+        found.sort();
+        assert_eq!(found, vec![0, 1, 3]);
+        assert_eq!(point_bin.remaining_count(), 2);
+
+        // The identity transform reduces to an ordinary radius search
+        point_bin.reset();
+        point_bin.radius_search_projected(&array![0.0, 0.0, 0.0].view(), 3.0, &Array2::eye(3).view());
+        let mut found = point_bin.found_indices().to_vec();
+        found.sort();
+        assert_eq!(found, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_radius_search_ellipsoid() {
+        let points = array![
+            [3.0, 0.0, 0.0],
+            [0.0, 1.5, 0.0],
+            [0.0, 0.5, 0.0],
+            [2.0, 2.0, 0.0],
+            [0.0, 0.0, 0.9],
+        ];
+        let bin_widths = array![1.0, 1.0, 1.0];
+        let mut point_bin = PointBin3D::new(points.clone(), bin_widths.clone());
+
+        // Semi-axes 4 along x, 1 along y and z
+        let metric = array![[1.0 / 16.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+        point_bin.radius_search_ellipsoid(&array![0.0, 0.0, 0.0].view(), &metric.view());
+        let mut found = point_bin.found_indices().to_vec();
+        found.sort();
+        assert_eq!(found, vec![0, 2, 4]);
+
+        // A rotated ellipsoid stretched along the diagonal x = y reaches (2, 2, 0)
+        let (a, b) = (1.0 / 9.0, 4.0);
+        let metric = array![
+            [(a + b) / 2.0, (a - b) / 2.0, 0.0],
+            [(a - b) / 2.0, (a + b) / 2.0, 0.0],
+            [0.0, 0.0, 1.0],
+        ];
+        point_bin.reset();
+        point_bin.radius_search_ellipsoid(&array![0.0, 0.0, 0.0].view(), &metric.view());
+        let mut found = point_bin.found_indices().to_vec();
+        found.sort();
+        assert_eq!(found, vec![2, 3, 4]);
+
+        // M = I / r^2 matches radius_search
+        let mut reference = PointBin3D::new(points, bin_widths);
+        reference.radius_search(&array![0.0, 0.0, 0.0].view(), 1.6);
+        point_bin.reset();
+        let metric = Array2::<f64>::eye(3) / (1.6 * 1.6);
+        point_bin.radius_search_ellipsoid(&array![0.0, 0.0, 0.0].view(), &metric.view());
+        assert_eq!(point_bin.found_indices_sorted_unique(), reference.found_indices_sorted_unique());
+    }
+
+    #[test]
+    fn test_radius_search_wrapped() {
+        // Grid spans [0, 4) on every axis
+        let points = array![
+            [0.0, 0.0, 0.0],
+            [1.0, 2.0, 2.0],
+            [1.2, 2.1, 1.9],
+            [3.5, 3.5, 3.5],
+        ];
+        let bin_widths = array![1.0, 1.0, 1.0];
+        let point_bin = PointBin3D::new(points, bin_widths);
+
+        let sorted = |indices: Array1<i64>| {
+            let mut indices = indices.to_vec();
+            indices.sort();
+            indices
+        };
+        let (in_range, _) = point_bin.radius_search_clipped(&array![1.0, 2.0, 2.0].view(), 0.5);
+        let in_range = sorted(in_range);
+        assert_eq!(in_range, vec![1, 2]);
+
+        for query in [array![5.0, 2.0, 2.0], array![1.0, -2.0, 10.0], array![-7.0, 6.0, -2.0]] {
+            assert_eq!(sorted(point_bin.radius_search_wrapped(&query.view(), 0.5)), in_range);
+        }
+        assert_eq!(point_bin.remaining_count(), 4);
+    }
+
+    #[test]
+    fn test_new_with_bins_matches_new() {
+        let mut points = Array2::<f64>::zeros((200, 3));
+        for i in 0..200 {
+            let fi = i as f64;
+            points[[i, 0]] = (fi * 0.731) % 7.0 - 2.0;
+            points[[i, 1]] = (fi * 1.913) % 5.0;
+            points[[i, 2]] = (fi * 2.417) % 3.0 + 10.0;
+        }
+        let bin_widths = array![0.5, 1.0, 0.75];
+
+        let origin = crate::min_along_axis0(&points.view());
+        let mut bin_indices = Array2::<i64>::zeros((200, 3));
+        for ((i, j), bin) in bin_indices.indexed_iter_mut() {
+            *bin = ((points[[i, j]] - origin[j]) / bin_widths[j]).floor() as i64;
+        }
+
+        let mut expected = PointBin3D::new(points.clone(), bin_widths.clone());
+        let mut point_bin = PointBin3D::new_with_bins(points, bin_widths, origin, bin_indices);
+        assert_eq!(point_bin.bin_shape(), expected.bin_shape());
+        assert_eq!(point_bin.to_count_grid(), expected.to_count_grid());
+
+        for query in [array![0.0, 2.0, 11.0], array![3.0, 4.0, 12.5]] {
+            point_bin.radius_search(&query.view(), 1.2);
+            expected.radius_search(&query.view(), 1.2);
+        }
+        assert_eq!(point_bin.found_indices(), expected.found_indices());
+    }
+
+    #[test]
+    fn test_query_bins() {
+        let points = array![
+            [0.5, 0.5, 0.5],
+            [0.2, 0.9, 0.1],
+            [1.5, 0.5, 0.5],
+            [2.5, 2.5, 2.5],
+            [0.5, 1.5, 0.5],
+        ];
+        let bin_widths = array![1.0, 1.0, 1.0];
+        let mut point_bin = PointBin3D::new(points, bin_widths);
+
+        let mut removed = point_bin.query_bins(&[[2, 2, 2], [0, 0, 0], [9, 0, 0]]).to_vec();
+        removed.sort();
+        assert_eq!(removed, vec![0, 1, 3]);
+        assert_eq!(point_bin.remaining_count(), 2);
+
+        // Already-emptied bins contribute nothing on a second pass
+        let removed = point_bin.query_bins(&[[0, 0, 0], [1, 0, 0]]);
+        assert_eq!(removed, array![2]);
+        assert_eq!(point_bin.found_count(), 4);
+    }
+
+    #[test]
+    fn test_shift_origin_by_bins() {
+        let points = array![
+            [0.0, 0.0, 0.0],
+            [0.4, 0.3, 0.2],
+            [1.5, 1.5, 1.5],
+            [3.0, 2.0, 1.0],
+        ];
+        let bin_widths = array![0.5, 1.0, 2.0];
+        let mut point_bin = PointBin3D::new(points.clone(), bin_widths);
+        point_bin.radius_search(&array![3.0, 2.0, 1.0].view(), 0.1);
+
+        let queries = [array![0.5, 0.5, 0.5], array![0.3, 0.0, 0.2], array![1.5, 1.5, 1.5]];
+        let before: Vec<_> = queries.iter().map(|q| point_bin.radius_search_clipped(&q.view(), 2.0)).collect();
+        let grid_before = point_bin.to_count_grid();
+
+        point_bin.shift_origin_by_bins([4, -3, 2]);
+        point_bin.shift_origin_by_bins([0, 0, -1]);
+        assert_eq!(point_bin.origin(), &array![2.0, -3.0, 2.0]);
+        assert_eq!(point_bin.grid_metadata().bin_offset, [4, -3, 1]);
+
+        // Nothing moved: same points, same bins, same results
+        assert_eq!(point_bin.original_points(), &points);
+        assert_eq!(point_bin.to_count_grid(), grid_before);
+        assert_eq!(point_bin.remaining_count(), 3);
+        let after: Vec<_> = queries.iter().map(|q| point_bin.radius_search_clipped(&q.view(), 2.0)).collect();
+        assert_eq!(after, before);
+    }
+
+    #[test]
+    fn test_radius_search_exclude_axis_range() {
+        let points = array![
+            [0.0, 0.0, -1.0],
+            [0.0, 0.0, -0.1],
+            [0.0, 0.0, 0.0],
+            [0.0, 0.0, 0.1],
+            [0.0, 0.0, 1.0],
+            [0.5, 0.0, 0.05],
+        ];
+        let bin_widths = array![1.0, 1.0, 1.0];
+        let mut point_bin = PointBin3D::new(points, bin_widths);
+
+        point_bin.radius_search_exclude_axis_range(&array![0.0, 0.0, 0.0].view(), 1.5, 2, -0.1, 0.1);
+        let mut found = point_bin.found_indices().to_vec();
+        found.sort();
+        assert_eq!(found, vec![0, 4]);
+        assert_eq!(point_bin.remaining_count(), 4);
+    }
+
+    #[test]
+    fn test_radius_search_offsets() {
+        let points = array![
+            [0.0, 0.0, 0.0],
+            [1.0, 0.5, -0.5],
+            [-0.7, 0.2, 0.3],
+            [5.0, 5.0, 5.0],
+        ];
+        let bin_widths = array![1.0, 1.0, 1.0];
+        let point_bin = PointBin3D::new(points.clone(), bin_widths);
+        let query = array![0.2, 0.1, 0.0];
+
+        let (found, offsets) = point_bin.radius_search_offsets(&query.view(), 1.5);
+        assert_eq!(found.len(), 3);
+        assert_eq!(offsets.nrows(), 3);
+        for (&idx, offset) in found.iter().zip(offsets.rows()) {
+            let reconstructed = &offset + &query;
+            for j in 0..3 {
+                assert!((reconstructed[j] - points[[idx as usize, j]]).abs() < 1e-12);
+            }
+        }
+        assert_eq!(point_bin.remaining_count(), 4);
+    }
+
+    #[test]
+    fn test_new_with_fortran_order_points() {
+        let mut points = Array2::<f64>::zeros((100, 3));
+        for i in 0..100 {
+            let fi = i as f64;
+            points[[i, 0]] = (fi * 1.37) % 6.0;
+            points[[i, 1]] = (fi * 2.71) % 4.0 - 1.0;
+            points[[i, 2]] = (fi * 0.59) % 3.0;
+        }
+        let mut fortran = Array2::<f64>::zeros((100, 3).f());
+        fortran.assign(&points);
+        assert!(!fortran.is_standard_layout());
+
+        let bin_widths = array![0.5, 0.5, 0.5];
+        let mut c_order = PointBin3D::new(points.clone(), bin_widths.clone());
+        let mut f_order = PointBin3D::new(fortran, bin_widths);
+        assert_eq!(f_order.origin(), c_order.origin());
+        assert_eq!(f_order.bin_shape(), c_order.bin_shape());
+        assert_eq!(f_order.original_points(), &points);
+
+        for query in [array![1.0, 0.0, 1.0], array![4.0, 2.0, 2.5]] {
+            c_order.radius_search(&query.view(), 0.8);
+            f_order.radius_search(&query.view(), 0.8);
+        }
+        assert_eq!(f_order.found_indices(), c_order.found_indices());
+    }
+
+    #[test]
+    fn test_radius_search_soft() {
+        let points = array![
+            [0.5, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.5, 0.0, 0.0],
+            [2.0, 0.0, 0.0],
+            [2.5, 0.0, 0.0],
+        ];
+        let bin_widths = array![1.0, 1.0, 1.0];
+        let mut point_bin = PointBin3D::new(points, bin_widths);
+
+        let (found, weights) = point_bin.radius_search_soft(&array![0.0, 0.0, 0.0].view(), 1.0, 2.0);
+        let mut pairs: Vec<(i64, f64)> = found.iter().cloned().zip(weights.iter().cloned()).collect();
+        pairs.sort_by_key(|&(i, _)| i);
+        assert_eq!(pairs, vec![(0, 1.0), (1, 1.0), (2, 0.5)]);
+
+        // Points at or beyond r_soft stay in the structure
+        assert_eq!(point_bin.remaining_count(), 2);
+        assert_eq!(point_bin.found_count(), 3);
+    }
+
+    #[test]
+    fn test_split_by_label() {
+        let points = array![
+            [0.0, 0.0, 0.0],
+            [5.0, 5.0, 5.0],
+            [0.5, 0.5, 0.5],
+            [9.0, 9.0, 9.0],
+            [5.5, 5.0, 5.0],
+        ];
+        let labels = array![0, 1, 0, -1, 1];
+        let bin_widths = array![1.0, 1.0, 1.0];
+
+        let split = PointBin3D::split_by_label(points, labels, bin_widths);
+        let mut keys: Vec<i32> = split.keys().copied().collect();
+        keys.sort();
+        assert_eq!(keys, vec![-1, 0, 1]);
+
+        assert_eq!(split[&0].original_points(), &array![[0.0, 0.0, 0.0], [0.5, 0.5, 0.5]]);
+        assert_eq!(split[&1].original_points(), &array![[5.0, 5.0, 5.0], [5.5, 5.0, 5.0]]);
+        assert_eq!(split[&-1].original_points(), &array![[9.0, 9.0, 9.0]]);
+        assert_eq!(split[&1].origin(), &array![5.0, 5.0, 5.0]);
+    }
+
+    #[test]
+    fn test_remove_region() {
+        let points = array![
+            [0.5, 0.5, 0.5],
+            [1.5, 1.5, 1.5],
+            [2.0, 2.0, 2.0],
+            [2.5, 1.5, 0.5],
+            [3.5, 3.5, 3.5],
+        ];
+        let bin_widths = array![1.0, 1.0, 1.0];
+        let mut point_bin = PointBin3D::new(points, bin_widths);
+
+        point_bin.radius_search(&array![0.5, 0.5, 0.5].view(), 0.1);
+        assert_eq!(point_bin.found_indices(), array![0]);
+
+        point_bin.remove_region(&array![1.0, 1.0, 1.0].view(), &array![2.0, 2.0, 2.0].view());
+        assert_eq!(point_bin.remaining_count(), 2);
+        assert_eq!(point_bin.found_count(), 1);
+        assert_eq!(point_bin.found_indices(), array![0]);
+
+        // Masked points no longer match, the rest still do
+        point_bin.radius_search(&array![2.0, 2.0, 2.0].view(), 10.0);
+        let mut found = point_bin.found_indices().to_vec();
+        found.sort();
+        assert_eq!(found, vec![0, 3, 4]);
+    }
+
+    #[test]
+    fn test_angular_nearest() {
+        let points = array![
+            [1.0, 0.1, 0.0],   // sector 0 (+x)
+            [2.0, 0.2, 0.5],   // sector 0, farther
+            [-0.1, 1.5, 0.0],  // sector 1 (+y)
+            [0.1, 0.8, 0.3],   // sector 0 near 90 degrees, the nearest in it
+            [-1.0, -1.0, 0.0], // sector 2
+            [0.0, 0.0, 0.5],   // straight above: no azimuth
+            [0.5, -3.0, 0.0],  // sector 3, beyond the radius
+        ];
+        let bin_widths = array![1.0, 1.0, 1.0];
+        let point_bin = PointBin3D::new(points, bin_widths);
+
+        let nearest = point_bin.angular_nearest(&array![0.0, 0.0, 0.0].view(), 2.5, 4);
+        assert_eq!(nearest, array![3, 2, 4, -1]);
+        assert_eq!(point_bin.found_count(), 0);
+    }
+
+    #[test]
+    fn test_radius_search_ranked() {
+        let points = array![
+            [0.1, 0.0, 0.0],
+            [0.4, 0.0, 0.0],
+            [0.0, 0.7, 0.0],
+            [0.0, 0.0, 0.9],
+            [3.0, 0.0, 0.0],
+        ];
+        let bin_widths = array![1.0, 1.0, 1.0];
+        let point_bin = PointBin3D::new(points, bin_widths);
+        let query = array![0.0, 0.0, 0.0];
+
+        let (nearest_first, scores) = point_bin.radius_search_ranked(&query.view(), 1.0, |_, d| -d, 3);
+        assert_eq!(nearest_first, array![0, 1, 2]);
+        assert!((scores[0] + 0.1).abs() < 1e-12);
+
+        // Inverting the score flips the ranking
+        let (farthest_first, scores) = point_bin.radius_search_ranked(&query.view(), 1.0, |_, d| d, 3);
+        assert_eq!(farthest_first, array![3, 2, 1]);
+        assert!((scores[0] - 0.9).abs() < 1e-12);
+
+        // The score can use the index too; NaN scores are dropped
+        let (found, _) = point_bin.radius_search_ranked(
+            &query.view(),
+            1.0,
+            |i, d| if i == 3 { f64::NAN } else { i as f64 + d },
+            10,
+        );
+        assert_eq!(found, array![2, 1, 0]);
+        assert_eq!(point_bin.found_count(), 0);
+    }
+
+    #[test]
+    fn test_radius_search_novel() {
+        let points = array![
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [2.0, 0.0, 0.0],
+            [3.0, 0.0, 0.0],
+            [4.0, 0.0, 0.0],
+        ];
+        let bin_widths = array![1.0, 1.0, 1.0];
+        let point_bin = PointBin3D::new(points, bin_widths);
+        let mut seen = vec![false; 5];
+
+        let mut first = point_bin.radius_search_novel(&array![1.0, 0.0, 0.0].view(), 1.0, &mut seen).to_vec();
+        first.sort();
+        assert_eq!(first, vec![0, 1, 2]);
+
+        // Overlapping search only reports points 3 and 4
+        let mut second = point_bin.radius_search_novel(&array![3.0, 0.0, 0.0].view(), 1.0, &mut seen).to_vec();
+        second.sort();
+        assert_eq!(second, vec![3, 4]);
+
+        assert!(point_bin.radius_search_novel(&array![2.0, 0.0, 0.0].view(), 5.0, &mut seen).is_empty());
+        assert_eq!(point_bin.remaining_count(), 5);
+    }
+
+    #[test]
+    fn test_radius_query_matches_radius_search() {
+        let mut points = scattered_points(200, [8.0; 3]);
+        points.row_mut(0).assign(&array![2.0, 2.0, 2.0]);
+        points.row_mut(1).assign(&array![3.5, 2.0, 2.0]);
+        let mut point_bin = PointBin3D::new(points, array![1.0, 1.0, 1.0]);
+
+        let queries = [[2.0, 2.0, 2.0], [2.5, 2.5, 2.5], [-1.0, 4.0, 9.0], [7.9, 0.0, 3.3]];
+        let results: Vec<Array1<i64>> = queries
+            .iter()
+            .map(|q| point_bin.radius_query(&ArrayView1::from(q), 1.5))
+            .collect();
+
+        // Overlapping queries are independent and match a fresh destructive search
+        assert!(results[0].iter().any(|i| results[1].iter().any(|j| i == j)));
+        for (q, result) in queries.iter().zip(&results) {
+            assert_eq!(point_bin.found_count(), 0);
+            point_bin.radius_search(&ArrayView1::from(q), 1.5);
+            assert_eq!(&point_bin.found_indices(), result);
+            point_bin.reset();
+        }
+
+        // A point exactly on the sphere is included
+        assert!(results[0].iter().any(|&i| i == 1));
+        assert!(point_bin.radius_query(&array![2.0, 2.0, 2.0].view(), -1.0).is_empty());
+    }
+
+    #[test]
+    fn test_live_points() {
+        let points = scattered_points(150, [5.0; 3]);
+        let mut point_bin = PointBin3D::new(points.clone(), array![1.0, 1.0, 1.0]);
+        point_bin.radius_search(&array![1.0, 1.0, 1.0].view(), 1.5);
+        point_bin.radius_search(&array![4.0, 2.0, 3.0].view(), 1.0);
+        assert!(point_bin.found_count() > 0);
+
+        let (live, original_indices) = point_bin.live_points();
+        assert_eq!(live.nrows(), point_bin.remaining_count());
+        assert_eq!(original_indices.len(), point_bin.remaining_count());
+        for (row, &orig_idx) in live.rows().into_iter().zip(&original_indices) {
+            assert_eq!(row, points.row(orig_idx as usize));
+        }
+
+        // Exactly the points not found, in bin order
+        let found: HashSet<i64> = point_bin.found_indices().into_iter().collect();
+        let mut sorted = original_indices.to_vec();
+        sorted.sort();
+        let expected: Vec<i64> = (0..150).filter(|i| !found.contains(i)).collect();
+        assert_eq!(sorted, expected);
+        let bins: Vec<[i64; 3]> = live.rows().into_iter().map(|row| point_bin.bin_of(&row)).collect();
+        assert!(bins.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn test_radius_search_and_accumulate_scalar() {
+        let points = array![
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 2.0, 0.0],
+            [5.0, 5.0, 5.0],
+        ];
+        let weights = array![1.0, 10.0, 100.0, 1000.0];
+        let mut point_bin = PointBin3D::new(points, array![1.0, 1.0, 1.0]);
+
+        let total = point_bin.radius_search_and_accumulate_scalar(&array![0.0, 0.0, 0.0].view(), 2.0, |i, dist_sq| {
+            weights[i as usize] * dist_sq
+        });
+        assert_eq!(total, 10.0 + 400.0);
+        assert_eq!(point_bin.found_indices_sorted_unique(), array![0, 1, 2]);
+
+        // Removed points don't count again
+        let total = point_bin.radius_search_and_accumulate_scalar(&array![0.0, 0.0, 0.0].view(), 20.0, |i, _| {
+            weights[i as usize]
+        });
+        assert_eq!(total, 1000.0);
+    }
+
+    #[test]
+    fn test_intersection_count() {
+        let points = scattered_points(500, [10.0; 3]);
+        let mut point_bin = PointBin3D::new(points.clone(), array![1.0, 1.0, 1.0]);
+        point_bin.radius_search(&array![4.5, 5.0, 5.0].view(), 0.8);
+
+        let (a, b) = (array![4.0, 5.0, 5.0], array![6.0, 5.5, 4.5]);
+        let found = point_bin.found_indices();
+        let expected = (0..500)
+            .filter(|&i| !found.iter().any(|&f| f == i as i64))
+            .filter(|&i| squared_distance(&points.row(i), &a.view()) <= 2.5 * 2.5)
+            .filter(|&i| squared_distance(&points.row(i), &b.view()) <= 1.5 * 1.5)
+            .count();
+        assert!(expected > 0);
+        assert_eq!(point_bin.intersection_count(&a.view(), 2.5, &b.view(), 1.5), expected);
+        assert_eq!(point_bin.intersection_count(&b.view(), 1.5, &a.view(), 2.5), expected);
+
+        // Disjoint spheres share nothing
+        assert_eq!(point_bin.intersection_count(&a.view(), 1.0, &b.view(), 0.9), 0);
+    }
+
+    #[test]
+    fn test_radius_search_tag_and_commit() {
+        let points = array![
+            [0.0, 0.0, 0.0],
+            [0.5, 0.0, 0.0],
+            [1.5, 0.0, 0.0],
+            [2.5, 0.0, 0.0],
+            [5.0, 5.0, 5.0],
+        ];
+        let mut point_bin = PointBin3D::new(points, array![1.0, 1.0, 1.0]);
+
+        // Pass 1 finds points without removing them
+        point_bin.radius_search_tag(&array![0.0, 0.0, 0.0].view(), 1.6, 1);
+        assert_eq!(point_bin.tagged_indices(1), array![0, 1, 2]);
+        assert_eq!(point_bin.remaining_count(), 5);
+        assert_eq!(point_bin.found_count(), 0);
+
+        // Pass 2 retags the points it also matches
+        point_bin.radius_search_tag(&array![2.0, 0.0, 0.0].view(), 0.6, 2);
+        assert_eq!(point_bin.tagged_indices(1), array![0, 1]);
+        assert_eq!(point_bin.tagged_indices(2), array![2, 3]);
+
+        point_bin.commit_tagged(1);
+        assert_eq!(point_bin.found_indices_sorted_unique(), array![0, 1]);
+        assert_eq!(point_bin.remaining_count(), 3);
+        assert!(point_bin.tagged_indices(1).is_empty());
+        assert_eq!(point_bin.tagged_indices(2), array![2, 3]);
+
+        // Committing again or committing untagged points removes nothing
+        point_bin.commit_tagged(1);
+        point_bin.commit_tagged(0);
+        assert_eq!(point_bin.remaining_count(), 3);
+
+        point_bin.reset();
+        assert!(point_bin.tagged_indices(2).is_empty());
+        assert_eq!(point_bin.tagged_indices(0).len(), 5);
+    }
+
+    #[test]
+    fn test_tags_survive_clear_found() {
+        let points = array![[0.0, 0.0, 0.0], [0.5, 0.0, 0.0], [2.5, 0.0, 0.0]];
+        let mut point_bin = PointBin3D::new(points, array![1.0, 1.0, 1.0]);
+
+        point_bin.radius_search_tag(&array![0.0, 0.0, 0.0].view(), 0.6, 1);
+        point_bin.radius_search(&array![2.5, 0.0, 0.0].view(), 0.1);
+        point_bin.clear_found();
+        assert_eq!(point_bin.tagged_indices(1), array![0, 1]);
+
+        point_bin.commit_tagged(1);
+        assert_eq!(point_bin.found_indices_sorted_unique(), array![0, 1]);
+        assert_eq!(point_bin.remaining_count(), 0);
+    }
+
+    #[test]
+    fn test_try_new_rejects_invalid_input() {
+        let points = array![[0.0, 0.0, 0.0], [1.0, 2.0, 3.0]];
+        let widths = array![1.0, 1.0, 1.0];
+
+        assert_eq!(
+            PointBin3D::try_new(array![[0.0, 0.0], [1.0, 1.0]], widths.clone()).err(),
+            Some(BucketError::WrongPointDims { ncols: 2 })
+        );
+        assert_eq!(
+            PointBin3D::try_new(points.clone(), array![1.0, 1.0]).err(),
+            Some(BucketError::WrongBinWidthsLen { len: 2 })
+        );
+        assert_eq!(
+            PointBin3D::try_new(points.clone(), array![1.0, 0.0, -1.0]).err(),
+            Some(BucketError::NonPositiveBinWidth { axis: 1, width: 0.0 })
+        );
+        assert!(matches!(
+            PointBin3D::try_new(points.clone(), array![1.0, 1.0, f64::NAN]),
+            Err(BucketError::NonPositiveBinWidth { axis: 2, .. })
+        ));
+        assert_eq!(
+            PointBin3D::try_new(Array2::zeros((0, 3)), widths.clone()).err(),
+            Some(BucketError::EmptyPoints)
+        );
+
+        let point_bin = PointBin3D::try_new(points, widths).unwrap();
+        assert_eq!(point_bin.bin_shape(), &array![2, 3, 4]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Bin widths must be positive")]
+    fn test_new_panics_on_invalid_input() {
+        PointBin3D::new(array![[0.0, 0.0, 0.0]], array![1.0, -1.0, 1.0]);
+    }
 }
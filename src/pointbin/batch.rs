@@ -0,0 +1,121 @@
+//! Non-destructive radius searches for many queries at once
+
+use std::ops::ControlFlow;
+
+use ndarray::{Array1, ArrayView1, ArrayView2};
+
+use super::PointBin3D;
+
+impl PointBin3D {
+    /// Find the remaining points within `radius` of each of many query points
+    ///
+    /// Nothing is removed, so every query sees the same points and a point can match
+    /// several queries. The result is in compressed sparse row (CSR) form: the matches of
+    /// query `q` are `indices[offsets[q]..offsets[q + 1]]`, in bin order.
+    ///
+    /// # Arguments
+    /// * `queries` - 2D array of shape (m, 3) with one query point per row
+    /// * `radius` - Search radius
+    ///
+    /// # Returns
+    /// `(offsets, indices)`: `m + 1` row offsets and the concatenated original indices
+    ///
+    /// # Panics
+    /// Panics if queries don't have exactly 3 columns
+    pub fn radius_search_batch(&self, queries: &ArrayView2<f64>, radius: f64) -> (Array1<i64>, Array1<i64>) {
+        assert_eq!(queries.ncols(), 3, "Queries must have 3 dimensions");
+        self.batch_csr(queries.nrows(), radius, |q| [queries[[q, 0]], queries[[q, 1]], queries[[q, 2]]])
+    }
+
+    /// `radius_search_batch` with the query coordinates given as separate columns
+    ///
+    /// Saves interleaving columnar query data into an (m, 3) array first. Results are
+    /// identical to `radius_search_batch` on the same queries.
+    ///
+    /// # Arguments
+    /// * `qx`, `qy`, `qz` - Query coordinates along x, y, z, one entry per query
+    /// * `radius` - Search radius
+    ///
+    /// # Returns
+    /// `(offsets, indices)` in the same CSR form as `radius_search_batch`
+    ///
+    /// # Panics
+    /// Panics if the three columns don't have the same length
+    pub fn radius_search_batch_columns(
+        &self,
+        qx: &ArrayView1<f64>,
+        qy: &ArrayView1<f64>,
+        qz: &ArrayView1<f64>,
+        radius: f64,
+    ) -> (Array1<i64>, Array1<i64>) {
+        assert!(
+            qx.len() == qy.len() && qx.len() == qz.len(),
+            "Query columns must have equal lengths"
+        );
+        self.batch_csr(qx.len(), radius, |q| [qx[q], qy[q], qz[q]])
+    }
+
+    /// Run `n_queries` radius queries, fetching each query's coordinates from `query`
+    fn batch_csr<F>(&self, n_queries: usize, radius: f64, query: F) -> (Array1<i64>, Array1<i64>)
+    where
+        F: Fn(usize) -> [f64; 3],
+    {
+        let mut offsets = Vec::with_capacity(n_queries + 1);
+        let mut indices = Vec::new();
+        offsets.push(0);
+
+        for q in 0..n_queries {
+            let query_point = query(q);
+            let _ = self.visit_within(&ArrayView1::from(&query_point), radius, |i, _| {
+                indices.push(self.original_indices[i]);
+                ControlFlow::Continue(())
+            });
+            offsets.push(indices.len() as i64);
+        }
+
+        (Array1::from(offsets), Array1::from(indices))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pointbin::scattered_points;
+    use ndarray::{array, s, Array2};
+
+    #[test]
+    fn test_radius_search_batch() {
+        let points = array![
+            [0.0, 0.0, 0.0],
+            [0.5, 0.0, 0.0],
+            [3.0, 3.0, 3.0],
+        ];
+        let point_bin = PointBin3D::new(points, array![1.0, 1.0, 1.0]);
+        let queries = array![[0.0, 0.0, 0.0], [10.0, 10.0, 10.0], [3.0, 3.0, 2.5]];
+
+        let (offsets, indices) = point_bin.radius_search_batch(&queries.view(), 1.0);
+        assert_eq!(offsets, array![0, 2, 2, 3]);
+        let mut first: Vec<i64> = indices.slice(s![0..2]).to_vec();
+        first.sort();
+        assert_eq!(first, vec![0, 1]);
+        assert_eq!(indices[2], 2);
+        assert_eq!(point_bin.found_count(), 0);
+    }
+
+    #[test]
+    fn test_radius_search_batch_columns_matches_rows() {
+        let points = scattered_points(200, [10.0; 3]);
+        let point_bin = PointBin3D::new(points, array![1.0, 1.0, 1.0]);
+
+        let queries = Array2::from_shape_fn((25, 3), |(q, j)| ((q * 7 + j * 3) % 11) as f64);
+        let rows = point_bin.radius_search_batch(&queries.view(), 1.5);
+        let columns = point_bin.radius_search_batch_columns(
+            &queries.column(0),
+            &queries.column(1),
+            &queries.column(2),
+            1.5,
+        );
+        assert_eq!(rows, columns);
+        assert!(rows.1.len() > 25);
+    }
+}
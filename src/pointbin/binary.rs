@@ -0,0 +1,360 @@
+//! Compact binary snapshots of a PointBin3D for checkpointing
+
+use std::fmt;
+
+use ndarray::{Array1, Array2};
+
+use super::PointBin3D;
+
+/// Leading bytes of every snapshot
+const MAGIC: &[u8; 4] = b"BKSB";
+/// Format version written by `to_bytes`
+const VERSION: u8 = 1;
+/// Header flag: per-point radii follow the found indices
+const FLAG_POINT_RADII: u32 = 1;
+/// Header flag: the structure keeps the backups `reset` restores from
+const FLAG_RESET_BACKUPS: u32 = 2;
+/// Header flag: the `shift_origin_by_bins` offset follows the per-point radii
+const FLAG_BIN_OFFSET: u32 = 4;
+
+/// Error returned when a binary snapshot cannot be loaded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeserializeError {
+    /// The data doesn't start with the snapshot magic number
+    BadMagic,
+    /// The snapshot was written by an unknown format version
+    UnsupportedVersion(u8),
+    /// The data ends before the snapshot does
+    Truncated { expected: usize, actual: usize },
+    /// There are bytes left over after the snapshot
+    TrailingBytes(usize),
+    /// A field holds a value inconsistent with the rest of the snapshot
+    InvalidField { field: &'static str, message: &'static str },
+}
+
+impl fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeserializeError::BadMagic => write!(f, "Not a PointBin3D snapshot"),
+            DeserializeError::UnsupportedVersion(version) => write!(f, "Unsupported snapshot version {}", version),
+            DeserializeError::Truncated { expected, actual } => {
+                write!(f, "Snapshot truncated: expected {} bytes, got {}", expected, actual)
+            }
+            DeserializeError::TrailingBytes(n) => write!(f, "{} unexpected bytes after snapshot", n),
+            DeserializeError::InvalidField { field, message } => write!(f, "Invalid field '{}': {}", field, message),
+        }
+    }
+}
+
+impl std::error::Error for DeserializeError {}
+
+impl PointBin3D {
+    /// Serialize the structure and its search state to a compact binary snapshot
+    ///
+    /// The layout is the magic `BKSB`, a version byte, then little-endian `u32` counts
+    /// `n_points`, `n_removed`, `n_found` and a flags word, followed by the raw
+    /// little-endian arrays: `bin_widths`, `origin` (`f64`), `bin_shape` (`i64`), the
+    /// original points row by row (`f64`), the original indices of removed points and of
+    /// found points in found order (`i64`), the per-point radii in original order
+    /// (`f64`) if the structure has them, and the `bin_offset` of `grid_metadata()`
+    /// (`i64`) if the origin was shifted. Buffers filled by specialised searches, such
+    /// as `found_confidences()`, are not included.
+    ///
+    /// # Panics
+    /// Panics if there are more than `u32::MAX` points
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let n_points = self.points.nrows();
+        let removed: Vec<i64> = (0..n_points)
+            .filter(|&i| self.is_removed(i))
+            .map(|i| self.original_indices[i])
+            .collect();
+        let found = self.found_indices();
+        let point_radii = self.original_order_point_radii();
+
+        let mut flags = 0;
+        if point_radii.is_some() {
+            flags |= FLAG_POINT_RADII;
+        }
+        if self.original_first_member.is_some() {
+            flags |= FLAG_RESET_BACKUPS;
+        }
+        if self.bin_offset != [0; 3] {
+            flags |= FLAG_BIN_OFFSET;
+        }
+
+        let count = |n: usize| u32::try_from(n).expect("snapshot counts must fit in u32");
+        let mut out = Vec::with_capacity(9 + 16 + 8 * (9 + 4 * n_points + removed.len() + found.len()));
+        out.extend_from_slice(MAGIC);
+        out.push(VERSION);
+        for value in [count(n_points), count(removed.len()), count(found.len()), flags] {
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+        for &value in self.bin_widths.iter().chain(&self.grid_origin) {
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+        for &value in &self.bin_shape {
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+        for &value in self.original_points.iter() {
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+        for &value in removed.iter().chain(&found) {
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+        if let Some(point_radii) = point_radii {
+            for &value in &point_radii {
+                out.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        if flags & FLAG_BIN_OFFSET != 0 {
+            for value in self.bin_offset {
+                out.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        out
+    }
+
+    /// Rebuild a structure from the output of `to_bytes`
+    ///
+    /// The points are binned on the stored grid, cut back to the bins the points fall in,
+    /// and the removed points, found indices, point radii, reset backup setting and
+    /// origin shift are restored. `reset()` brings back every
+    /// point as usual.
+    ///
+    /// # Arguments
+    /// * `bytes` - Snapshot produced by `to_bytes`
+    ///
+    /// # Returns
+    /// The restored structure, or a `DeserializeError` if the bytes are not a valid snapshot
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DeserializeError> {
+        let mut reader = Reader { bytes, pos: 0 };
+        if reader.take(4)? != MAGIC {
+            return Err(DeserializeError::BadMagic);
+        }
+        let version = reader.take(1)?[0];
+        if version != VERSION {
+            return Err(DeserializeError::UnsupportedVersion(version));
+        }
+
+        let n_points = reader.u32()? as usize;
+        let n_removed = reader.u32()? as usize;
+        let n_found = reader.u32()? as usize;
+        let flags = reader.u32()?;
+        let invalid = |field, message| DeserializeError::InvalidField { field, message };
+        if n_points == 0 {
+            return Err(invalid("n_points", "snapshot holds no points"));
+        }
+        if n_removed > n_points {
+            return Err(invalid("n_removed", "more removed points than points"));
+        }
+
+        let bin_widths = Array1::from(reader.f64s(3)?);
+        let origin = Array1::from(reader.f64s(3)?);
+        let bin_shape = Array1::from(reader.i64s(3)?);
+        if !bin_widths.iter().all(|&w| w > 0.0 && w.is_finite()) {
+            return Err(invalid("bin_widths", "bin widths must be positive"));
+        }
+        if !bin_shape.iter().all(|&n| n > 0) {
+            return Err(invalid("bin_shape", "bin counts must be positive"));
+        }
+
+        let original_points = Array2::from_shape_vec((n_points, 3), reader.f64s(3 * n_points)?)
+            .expect("three coordinates per point");
+        let removed = reader.i64s(n_removed)?;
+        let found = reader.i64s(n_found)?;
+        let point_radii = if flags & FLAG_POINT_RADII != 0 {
+            Some(Array1::from(reader.f64s(n_points)?))
+        } else {
+            None
+        };
+        let bin_offset = if flags & FLAG_BIN_OFFSET != 0 {
+            let offset = reader.i64s(3)?;
+            Some([offset[0], offset[1], offset[2]])
+        } else {
+            None
+        };
+        if reader.pos != bytes.len() {
+            return Err(DeserializeError::TrailingBytes(bytes.len() - reader.pos));
+        }
+
+        let in_range = |idx: &i64| (0..n_points as i64).contains(idx);
+        if !removed.iter().all(in_range) {
+            return Err(invalid("removed", "index out of range"));
+        }
+        if !found.iter().all(in_range) {
+            return Err(invalid("found", "index out of range"));
+        }
+
+        let (bin_indices, bin_shape) = Self::bins_on_stored_grid(&original_points, &bin_widths, &origin, &bin_shape)
+            .ok_or(invalid("points", "point outside the stored grid"))?;
+        let keep_reset_backups = flags & FLAG_RESET_BACKUPS != 0;
+        let mut point_bin = Self::index_from_bins(original_points, bin_widths, origin, bin_indices, bin_shape, keep_reset_backups);
+        if let Some(point_radii) = &point_radii {
+            point_bin.set_point_radii(point_radii);
+        }
+        if let Some(bin_offset) = bin_offset {
+            point_bin.shift_origin_by_bins(bin_offset);
+        }
+
+        if n_removed > 0 {
+            let mut is_removed = vec![false; n_points];
+            for &idx in &removed {
+                is_removed[idx as usize] = true;
+            }
+            let original_indices = point_bin.original_indices.clone();
+            let (nx, ny, nz) = point_bin.first_member.dim();
+            for ix in 0..nx {
+                for iy in 0..ny {
+                    for iz in 0..nz {
+                        point_bin.unlink_matching_in_bin([ix, iy, iz], false, |i, _| {
+                            is_removed[original_indices[i] as usize]
+                        });
+                    }
+                }
+            }
+            if point_bin.removed_count != n_removed {
+                return Err(invalid("removed", "duplicate index"));
+            }
+        }
+
+        let mut sorted_of_original = vec![0i64; n_points];
+        for (sorted_idx, &orig_idx) in point_bin.original_indices.iter().enumerate() {
+            sorted_of_original[orig_idx as usize] = sorted_idx as i64;
+        }
+        point_bin
+            .found_indices_buffer
+            .extend(found.iter().map(|&orig_idx| sorted_of_original[orig_idx as usize]));
+
+        Ok(point_bin)
+    }
+}
+
+/// Cursor over the snapshot bytes
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], DeserializeError> {
+        let end = self.pos.checked_add(n).filter(|&end| end <= self.bytes.len());
+        let Some(end) = end else {
+            return Err(DeserializeError::Truncated { expected: self.pos.saturating_add(n), actual: self.bytes.len() });
+        };
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn u32(&mut self) -> Result<u32, DeserializeError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().expect("four bytes")))
+    }
+
+    fn f64s(&mut self, n: usize) -> Result<Vec<f64>, DeserializeError> {
+        let bytes = self.take(n.saturating_mul(8))?;
+        Ok(bytes.chunks_exact(8).map(|b| f64::from_le_bytes(b.try_into().expect("eight bytes"))).collect())
+    }
+
+    fn i64s(&mut self, n: usize) -> Result<Vec<i64>, DeserializeError> {
+        let bytes = self.take(n.saturating_mul(8))?;
+        Ok(bytes.chunks_exact(8).map(|b| i64::from_le_bytes(b.try_into().expect("eight bytes"))).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    /// Small deterministic generator so the round trip covers many layouts
+    fn lcg(state: &mut u64) -> f64 {
+        *state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (*state >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    #[test]
+    fn test_bytes_round_trip_property() {
+        for seed in 0..20u64 {
+            let mut state = seed;
+            let n_points = 1 + (lcg(&mut state) * 200.0) as usize;
+            let points = Array2::from_shape_fn((n_points, 3), |_| lcg(&mut state) * 10.0 - 3.0);
+            let bin_widths = Array1::from_shape_fn(3, |_| 0.3 + lcg(&mut state) * 2.0);
+
+            let mut builder = PointBin3D::builder(points, bin_widths).keep_reset_backups(seed % 3 != 0);
+            if seed % 2 == 0 {
+                builder = builder.point_radii(Array1::from_shape_fn(n_points, |_| lcg(&mut state)));
+            }
+            let mut point_bin = builder.build();
+            if seed % 4 == 1 {
+                point_bin.shift_origin_by_bins([seed as i64, -1, 0]);
+            }
+            for _ in 0..3 {
+                let query = Array1::from_shape_fn(3, |_| lcg(&mut state) * 10.0 - 3.0);
+                point_bin.radius_search(&query.view(), lcg(&mut state) * 3.0);
+            }
+
+            let bytes = point_bin.to_bytes();
+            let mut restored = PointBin3D::from_bytes(&bytes).unwrap();
+            assert_eq!(restored.to_bytes(), bytes);
+            assert_eq!(restored.original_points(), point_bin.original_points());
+            assert_eq!(restored.grid_metadata(), point_bin.grid_metadata());
+            assert_eq!(restored.found_indices(), point_bin.found_indices());
+            assert_eq!(restored.remaining_count(), point_bin.remaining_count());
+
+            // Later searches see the same remaining points
+            let query = Array1::from_shape_fn(3, |_| lcg(&mut state) * 10.0 - 3.0);
+            point_bin.clear_found();
+            restored.clear_found();
+            point_bin.radius_search(&query.view(), 4.0);
+            restored.radius_search(&query.view(), 4.0);
+            assert_eq!(restored.found_indices_sorted_unique(), point_bin.found_indices_sorted_unique());
+            if seed % 2 == 0 {
+                assert_eq!(restored.reverse_radius_search(&query.view()), point_bin.reverse_radius_search(&query.view()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_bytes_errors() {
+        let mut point_bin = PointBin3D::new(array![[0.0, 0.0, 0.0], [1.0, 1.0, 1.0]], array![1.0, 1.0, 1.0]);
+        point_bin.radius_search(&array![0.0, 0.0, 0.0].view(), 0.5);
+        let bytes = point_bin.to_bytes();
+
+        assert_eq!(PointBin3D::from_bytes(b"nope").err(), Some(DeserializeError::BadMagic));
+
+        let mut wrong_version = bytes.clone();
+        wrong_version[4] = 9;
+        assert_eq!(PointBin3D::from_bytes(&wrong_version).err(), Some(DeserializeError::UnsupportedVersion(9)));
+
+        assert!(matches!(
+            PointBin3D::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(DeserializeError::Truncated { .. })
+        ));
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(PointBin3D::from_bytes(&trailing).err(), Some(DeserializeError::TrailingBytes(1)));
+
+        // bin_shape follows the 21-byte header, bin widths and origin
+        let with_shape = |shape: [i64; 3]| {
+            let mut bytes = bytes.clone();
+            for (j, n) in shape.iter().enumerate() {
+                bytes[69 + 8 * j..77 + 8 * j].copy_from_slice(&n.to_le_bytes());
+            }
+            PointBin3D::from_bytes(&bytes)
+        };
+        let huge = with_shape([1_000_000_000, 1_000_000, 1_000]).unwrap();
+        assert_eq!(huge.grid_metadata().bin_shape, array![2, 2, 2]);
+        assert!(matches!(with_shape([1, 2, 2]), Err(DeserializeError::InvalidField { field: "points", .. })));
+        assert!(matches!(with_shape([0, 2, 2]), Err(DeserializeError::InvalidField { field: "bin_shape", .. })));
+
+        // The found index is the last 8 bytes
+        let mut bad_index = bytes;
+        let n = bad_index.len();
+        bad_index[n - 8..].copy_from_slice(&7i64.to_le_bytes());
+        assert!(matches!(
+            PointBin3D::from_bytes(&bad_index),
+            Err(DeserializeError::InvalidField { field: "found", .. })
+        ));
+    }
+}
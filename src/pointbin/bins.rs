@@ -0,0 +1,400 @@
+//! Per-bin and nearest-first iteration over the remaining points
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashSet};
+
+use ndarray::{s, ArrayView1, ArrayView2};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use super::PointBin3D;
+
+/// Iterator over the occupied bins of a PointBin3D, nearest to a query first
+///
+/// Created by `PointBin3D::iter_bins_by_distance`.
+pub struct BinsByDistanceIter<'a> {
+    point_bin: &'a PointBin3D,
+    query: [f64; 3],
+    heap: BinaryHeap<Nearest<[usize; 3]>>,
+    queued: HashSet<[usize; 3]>,
+}
+
+/// Stream of the remaining points within a radius, nearest first
+///
+/// Created by `PointBin3D::neighbor_stream`.
+pub struct NeighborStream<'a> {
+    bins: BinsByDistanceIter<'a>,
+    /// Next bin to expand, already taken from `bins`
+    next_bin: Option<([usize; 3], f64)>,
+    points: BinaryHeap<Nearest<usize>>,
+    radius_sq: f64,
+}
+
+/// Heap entry ordered so the `BinaryHeap` pops the smallest distance first
+struct Nearest<T> {
+    dist_sq: f64,
+    item: T,
+}
+
+impl<T: Ord> PartialEq for Nearest<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T: Ord> Eq for Nearest<T> {}
+
+impl<T: Ord> PartialOrd for Nearest<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Ord> Ord for Nearest<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.dist_sq.total_cmp(&self.dist_sq).then_with(|| other.item.cmp(&self.item))
+    }
+}
+
+impl<'a> BinsByDistanceIter<'a> {
+    fn push(&mut self, bin: [usize; 3]) {
+        if self.queued.insert(bin) {
+            let query = ArrayView1::from(&self.query);
+            let dist_sq = (0..3)
+                .map(|j| self.point_bin.axis_gap_sq(j, bin[j] as i64, query[j]))
+                .sum();
+            self.heap.push(Nearest { dist_sq, item: bin });
+        }
+    }
+
+    /// Next occupied bin with its squared distance from the query
+    fn next_sq(&mut self) -> Option<([usize; 3], f64)> {
+        while let Some(Nearest { dist_sq, item: bin }) = self.heap.pop() {
+            // Face neighbours are never closer than the bin itself, so pushing them
+            // here keeps the pops in ascending order
+            for j in 0..3 {
+                if bin[j] > 0 {
+                    let mut neighbour = bin;
+                    neighbour[j] -= 1;
+                    self.push(neighbour);
+                }
+                if (bin[j] as i64) < self.point_bin.bin_shape[j] - 1 {
+                    let mut neighbour = bin;
+                    neighbour[j] += 1;
+                    self.push(neighbour);
+                }
+            }
+
+            if self.point_bin.first_member[bin] != -1 {
+                return Some((bin, dist_sq));
+            }
+        }
+        None
+    }
+}
+
+impl<'a> Iterator for BinsByDistanceIter<'a> {
+    type Item = ([usize; 3], f64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_sq().map(|(bin, dist_sq)| (bin, dist_sq.sqrt()))
+    }
+}
+
+impl<'a> Iterator for NeighborStream<'a> {
+    type Item = (i64, f64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            // A point can be yielded once no unexpanded bin could hold a closer one
+            let expand = match (self.points.peek(), self.next_bin) {
+                (_, None) => false,
+                (None, Some(_)) => true,
+                (Some(point), Some((_, bin_dist_sq))) => bin_dist_sq < point.dist_sq,
+            };
+            if !expand {
+                let Nearest { dist_sq, item } = self.points.pop()?;
+                return Some((self.bins.point_bin.original_indices[item], dist_sq.sqrt()));
+            }
+
+            let (bin, _) = self.next_bin.take().expect("expand implies a pending bin");
+            let point_bin = self.bins.point_bin;
+            let query = ArrayView1::from(&self.bins.query);
+            let mut i = point_bin.first_member[bin];
+            while i != -1 {
+                let dist_sq = point_bin.dist_sq_to(i as usize, &query);
+                if dist_sq <= self.radius_sq {
+                    self.points.push(Nearest { dist_sq, item: i as usize });
+                }
+                i = point_bin.next_member[i as usize];
+            }
+            self.next_bin = self.bins.next_sq().filter(|&(_, d)| d <= self.radius_sq);
+        }
+    }
+}
+
+impl PointBin3D {
+    /// Iterate over the occupied bins in order of distance from a query point
+    ///
+    /// Yields `(bin, distance)` pairs, where `distance` is from the query to the
+    /// nearest point of the bin's box, in ascending order. The iterator starts at the
+    /// query's bin (clamped to the grid) and keeps a `BinaryHeap` frontier: each
+    /// `next()` pops the closest bin and pushes its unvisited face neighbours. Bins with
+    /// no remaining points are expanded through but not yielded. This is the building
+    /// block for best-first nearest-neighbour searches, which can stop once the next
+    /// distance exceeds their current k-th best.
+    ///
+    /// # Arguments
+    /// * `query_point` - 3D point to order the bins by
+    ///
+    /// # Panics
+    /// Panics if query_point doesn't have exactly 3 elements
+    pub fn iter_bins_by_distance<'a>(&'a self, query_point: &ArrayView1<f64>) -> BinsByDistanceIter<'a> {
+        assert_eq!(query_point.len(), 3, "Query point must have 3 dimensions");
+
+        let mut iter = BinsByDistanceIter {
+            point_bin: self,
+            query: [query_point[0], query_point[1], query_point[2]],
+            heap: BinaryHeap::new(),
+            queued: HashSet::new(),
+        };
+        if !self.first_member.is_empty() {
+            iter.push(self.bin_of(query_point).map(|b| b as usize));
+        }
+        iter
+    }
+
+    /// Stream the remaining points within `radius` of a query, nearest first
+    ///
+    /// Yields `(original_index, distance)` pairs in ascending distance order without
+    /// removing anything. Work is done lazily: bins are expanded outward from the query
+    /// through `iter_bins_by_distance`, and a point is only yielded once every bin that
+    /// could hold a closer point has been expanded, so stopping early skips the bins
+    /// further out. Draining the stream finds the same points as `radius_search`.
+    ///
+    /// # Arguments
+    /// * `query_point` - 3D point to search around
+    /// * `radius` - Search radius
+    ///
+    /// # Panics
+    /// Panics if query_point doesn't have exactly 3 elements
+    pub fn neighbor_stream<'a>(&'a self, query_point: &ArrayView1<f64>, radius: f64) -> NeighborStream<'a> {
+        let radius_sq = radius * radius;
+        let mut bins = self.iter_bins_by_distance(query_point);
+        let next_bin = if radius >= 0.0 {
+            bins.next_sq().filter(|&(_, d)| d <= radius_sq)
+        } else {
+            None
+        };
+        NeighborStream { bins, next_bin, points: BinaryHeap::new(), radius_sq }
+    }
+
+    /// Iterate over the bins that were occupied at construction, with their points
+    ///
+    /// Points are sorted by bin, so each bin's points form one contiguous block of the
+    /// sorted points. Yields `(bin_key, points, original_indices)` per occupied bin in
+    /// raveled row-major order, where `bin_key` is `(ix * ny + iy) * nz + iz` and the
+    /// two views cover the bin's block. The blocks are fixed when the structure is built:
+    /// points removed by searches are still included, and points moved by
+    /// `radius_search_and_swap` stay in the block of their original bin. Use
+    /// `for_each_bin` to visit only the remaining points.
+    pub fn occupied_bins_iter(&self) -> impl Iterator<Item = (u64, ArrayView2<'_, f64>, ArrayView1<'_, i64>)> + '_ {
+        self.bin_offsets.iter().map(move |&(key, start, len)| {
+            (
+                key,
+                self.points.slice(s![start..start + len, ..]),
+                self.original_indices.slice(s![start..start + len]),
+            )
+        })
+    }
+
+    /// Call a function on the remaining points of every occupied bin
+    ///
+    /// `f` receives `(ix, iy, iz, sorted_indices)`, where `sorted_indices` lists the
+    /// bin's remaining points as ascending indices into the bin-sorted points. Bins are
+    /// visited in row-major order and empty bins are skipped.
+    ///
+    /// # Arguments
+    /// * `f` - Function to call on each occupied bin
+    pub fn for_each_bin<F>(&self, mut f: F)
+    where
+        F: FnMut(usize, usize, usize, &[i64]),
+    {
+        let mut members = Vec::new();
+        for ((ix, iy, iz), &head) in self.first_member.indexed_iter() {
+            if head == -1 {
+                continue;
+            }
+            self.collect_bin_members(head, &mut members);
+            f(ix, iy, iz, &members);
+        }
+    }
+
+    /// Parallel version of `for_each_bin`
+    ///
+    /// Bins are processed concurrently, so `f` is called in no particular order.
+    ///
+    /// # Arguments
+    /// * `f` - Function to call on each occupied bin
+    #[cfg(feature = "rayon")]
+    pub fn par_for_each_bin<F>(&self, f: F)
+    where
+        F: Fn(usize, usize, usize, &[i64]) + Sync + Send,
+    {
+        let (_, ny, nz) = self.first_member.dim();
+        let heads = self.first_member.as_slice().expect("bin heads are contiguous");
+
+        heads.par_iter().enumerate().for_each_init(Vec::new, |members, (flat, &head)| {
+            if head == -1 {
+                return;
+            }
+            self.collect_bin_members(head, members);
+            f(flat / (ny * nz), (flat / nz) % ny, flat % nz, members);
+        });
+    }
+
+    /// Gather the sorted indices of a bin's linked list into `members`, ascending
+    fn collect_bin_members(&self, head: i64, members: &mut Vec<i64>) {
+        members.clear();
+        let mut i = head;
+        while i != -1 {
+            members.push(i);
+            i = self.next_member[i as usize];
+        }
+        // Lists are built by prepending, so they run from the highest index down
+        members.reverse();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pointbin::scattered_points;
+    use ndarray::array;
+
+    #[test]
+    fn test_for_each_bin() {
+        let points = array![
+            [0.1, 0.1, 0.1],
+            [0.5, 0.5, 0.5],
+            [1.5, 0.5, 0.5],
+            [0.2, 0.9, 0.3],
+            [1.5, 1.5, 1.5],
+        ];
+        let bin_widths = array![1.0, 1.0, 1.0];
+        let mut point_bin = PointBin3D::new(points, bin_widths);
+        point_bin.radius_search(&array![1.5, 1.5, 1.5].view(), 0.1);
+
+        let mut visited = Vec::new();
+        point_bin.for_each_bin(|ix, iy, iz, members| {
+            let mut originals: Vec<i64> = members.iter().map(|&i| point_bin.original_indices[i as usize]).collect();
+            assert!(members.windows(2).all(|w| w[0] < w[1]));
+            originals.sort();
+            visited.push(([ix, iy, iz], originals));
+        });
+        assert_eq!(visited, vec![([0, 0, 0], vec![0, 1, 3]), ([1, 0, 0], vec![2])]);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_for_each_bin_matches_serial() {
+        use std::sync::Mutex;
+
+        let points = scattered_points(300, [5.0; 3]);
+        let point_bin = PointBin3D::new(points, array![1.0, 1.0, 1.0]);
+
+        let mut serial = Vec::new();
+        point_bin.for_each_bin(|ix, iy, iz, members| serial.push(([ix, iy, iz], members.to_vec())));
+
+        let parallel = Mutex::new(Vec::new());
+        point_bin.par_for_each_bin(|ix, iy, iz, members| parallel.lock().unwrap().push(([ix, iy, iz], members.to_vec())));
+        let mut parallel = parallel.into_inner().unwrap();
+        parallel.sort();
+
+        assert_eq!(parallel, serial);
+    }
+
+    #[test]
+    fn test_occupied_bins_iter_partitions_points() {
+        let points = scattered_points(150, [4.0, 3.0, 5.0]);
+        let point_bin = PointBin3D::new(points.clone(), array![1.0, 1.0, 1.0]);
+
+        let mut next_start = 0;
+        let mut last_key = None;
+        for (key, bin_points, original_indices) in point_bin.occupied_bins_iter() {
+            assert!(last_key < Some(key));
+            last_key = Some(key);
+            assert!(bin_points.nrows() > 0);
+
+            // Each block starts where the previous one ended
+            assert_eq!(point_bin.points.slice(s![next_start..next_start + bin_points.nrows(), ..]), bin_points);
+            next_start += bin_points.nrows();
+
+            for (row, &orig_idx) in bin_points.rows().into_iter().zip(original_indices) {
+                assert_eq!(row, points.row(orig_idx as usize));
+                let bin = point_bin.bin_of(&row);
+                assert_eq!(crate::utils::ravel_bin_index(bin, point_bin.bin_shape()) as u64, key);
+            }
+        }
+        assert_eq!(next_start, 150);
+    }
+
+    #[test]
+    fn test_iter_bins_by_distance() {
+        let points = scattered_points(120, [6.0, 4.0, 5.0]);
+        let mut point_bin = PointBin3D::new(points, array![1.0, 1.0, 1.0]);
+        point_bin.radius_search(&array![5.5, 3.5, 4.5].view(), 1.0);
+
+        let query = array![2.3, -1.0, 2.6];
+        let visited: Vec<([usize; 3], f64)> = point_bin.iter_bins_by_distance(&query.view()).collect();
+        assert!(visited.windows(2).all(|w| w[0].1 <= w[1].1));
+        assert!((visited[0].1 - 1.0).abs() < 1e-12);
+
+        // Exactly the occupied bins, each once
+        let mut occupied = Vec::new();
+        point_bin.for_each_bin(|ix, iy, iz, _| occupied.push([ix, iy, iz]));
+        let mut bins: Vec<[usize; 3]> = visited.iter().map(|&(bin, _)| bin).collect();
+        bins.sort();
+        assert_eq!(bins, occupied);
+
+        // Every point of a bin is at least the yielded distance away
+        for &(bin, distance) in &visited {
+            let mut i = point_bin.first_member[bin];
+            while i != -1 {
+                let d = crate::utils::squared_distance(&point_bin.points.row(i as usize), &query.view()).sqrt();
+                assert!(d >= distance - 1e-12);
+                i = point_bin.next_member[i as usize];
+            }
+        }
+    }
+
+    #[test]
+    fn test_neighbor_stream() {
+        let points = scattered_points(300, [6.0; 3]);
+        let mut point_bin = PointBin3D::new(points.clone(), array![1.0, 1.0, 1.0]);
+        point_bin.radius_search(&array![1.0, 1.0, 1.0].view(), 0.8);
+        let query = array![2.2, 2.9, 3.1];
+
+        let streamed: Vec<(i64, f64)> = point_bin.neighbor_stream(&query.view(), 2.1).collect();
+        assert!(streamed.windows(2).all(|w| w[0].1 <= w[1].1));
+        for &(orig_idx, distance) in &streamed {
+            let d = crate::utils::squared_distance(&points.row(orig_idx as usize), &query.view()).sqrt();
+            assert!((d - distance).abs() < 1e-12);
+        }
+
+        // Same points as a non-destructive search, and nothing was removed
+        let (_, expected) = point_bin.radius_search_batch(&query.view().insert_axis(ndarray::Axis(0)), 2.1);
+        let mut expected = expected.to_vec();
+        expected.sort();
+        let mut indices: Vec<i64> = streamed.iter().map(|&(i, _)| i).collect();
+        indices.sort();
+        assert!(indices.len() > 10);
+        assert_eq!(indices, expected);
+        assert_eq!(point_bin.remaining_count(), 300 - point_bin.found_count());
+
+        // Taking a prefix gives the nearest points
+        let nearest: Vec<(i64, f64)> = point_bin.neighbor_stream(&query.view(), 2.1).take(5).collect();
+        assert_eq!(nearest, streamed[..5].to_vec());
+        assert_eq!(point_bin.neighbor_stream(&query.view(), -1.0).count(), 0);
+    }
+}
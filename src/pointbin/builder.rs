@@ -0,0 +1,204 @@
+//! Builder for PointBin3D construction options
+
+use ndarray::{Array1, Array2, Axis};
+
+use super::{BucketError, PointBin3D};
+
+/// Configures optional settings before binning the points
+///
+/// Created with `PointBin3D::builder`. Building with default settings is equivalent
+/// to `PointBin3D::new`.
+pub struct PointBin3DBuilder {
+    original_points: Array2<f64>,
+    bin_widths: Array1<f64>,
+    point_radii: Option<Array1<f64>>,
+    keep_reset_backups: bool,
+    fixed_grid: Option<(Array1<f64>, [usize; 3])>,
+}
+
+impl PointBin3D {
+    /// Start building a PointBin3D with non-default options
+    ///
+    /// # Arguments
+    /// * `original_points` - 2D array of shape (n_points, 3) with point coordinates
+    /// * `bin_widths` - 1D array of shape (3,) with bin widths for x, y, z
+    pub fn builder(original_points: Array2<f64>, bin_widths: Array1<f64>) -> PointBin3DBuilder {
+        PointBin3DBuilder {
+            original_points,
+            bin_widths,
+            point_radii: None,
+            keep_reset_backups: true,
+            fixed_grid: None,
+        }
+    }
+}
+
+impl PointBin3DBuilder {
+    /// Give each point an influence radius, as with `PointBin3D::new_with_radii`
+    pub fn point_radii(mut self, point_radii: Array1<f64>) -> Self {
+        self.point_radii = Some(point_radii);
+        self
+    }
+
+    /// Whether to keep the copies of the linked lists that `reset()` restores from (default true)
+    ///
+    /// The backups are as large as the linked lists themselves. One-shot destructive
+    /// workflows that never call `reset()` can disable them to save that memory;
+    /// `reset()` then panics.
+    pub fn keep_reset_backups(mut self, keep: bool) -> Self {
+        self.keep_reset_backups = keep;
+        self
+    }
+
+    /// Use a fixed grid instead of fitting one to the points
+    ///
+    /// The grid starts at `origin` and has `bin_shape` bins of the configured widths
+    /// along each axis. Points outside it are dropped: they are left out of the
+    /// structure, so `original_points()` and all original indices refer to the input
+    /// with those rows removed. Use `build_reporting_dropped` to find out which were
+    /// dropped.
+    ///
+    /// # Arguments
+    /// * `origin` - 1D array of shape (3,) with the minimum corner of the grid
+    /// * `bin_shape` - Number of bins along x, y, z
+    pub fn fixed_grid(mut self, origin: Array1<f64>, bin_shape: [usize; 3]) -> Self {
+        self.fixed_grid = Some((origin, bin_shape));
+        self
+    }
+
+    /// Bin the points and build the structure
+    ///
+    /// # Panics
+    /// Panics under the same conditions as `PointBin3D::new_with_radii`
+    pub fn build(self) -> PointBin3D {
+        self.build_reporting_dropped().0
+    }
+
+    /// Bin the points and build the structure, reporting points outside a fixed grid
+    ///
+    /// # Returns
+    /// The structure and the indices into the input points of the rows dropped for
+    /// lying outside the `fixed_grid`, ascending (always empty without a fixed grid)
+    ///
+    /// # Panics
+    /// Panics under the same conditions as `PointBin3D::new_with_radii`, or if the fixed
+    /// grid origin doesn't have length 3
+    pub fn build_reporting_dropped(self) -> (PointBin3D, Array1<i64>) {
+        if let Err(err) = BucketError::check(&self.original_points, &self.bin_widths) {
+            panic!("{}", err);
+        }
+        if let Some(point_radii) = &self.point_radii {
+            assert_eq!(
+                point_radii.len(),
+                self.original_points.nrows(),
+                "Point radii must have one entry per point"
+            );
+        }
+
+        let Some((origin, bin_shape)) = self.fixed_grid else {
+            let mut point_bin = PointBin3D::build_index(self.original_points, self.bin_widths, self.keep_reset_backups);
+            if let Some(point_radii) = &self.point_radii {
+                point_bin.set_point_radii(point_radii);
+            }
+            return (point_bin, Array1::from(Vec::new()));
+        };
+
+        assert_eq!(origin.len(), 3, "Origin must have 3 dimensions");
+
+        let mut kept = Vec::new();
+        let mut dropped = Vec::new();
+        let mut kept_bins = Vec::new();
+        for (i, point) in self.original_points.rows().into_iter().enumerate() {
+            let bin: [i64; 3] = std::array::from_fn(|j| ((point[j] - origin[j]) / self.bin_widths[j]).floor() as i64);
+            if (0..3).all(|j| (0..bin_shape[j] as i64).contains(&bin[j])) {
+                kept.push(i);
+                kept_bins.extend_from_slice(&bin);
+            } else {
+                dropped.push(i as i64);
+            }
+        }
+
+        let points = self.original_points.select(Axis(0), &kept);
+        let bin_indices = Array2::from_shape_vec((kept.len(), 3), kept_bins).expect("three bin coordinates per point");
+        let bin_shape = Array1::from_iter(bin_shape.iter().map(|&n| n as i64));
+        let mut point_bin = PointBin3D::index_from_bins(
+            points,
+            self.bin_widths,
+            origin,
+            bin_indices,
+            bin_shape,
+            self.keep_reset_backups,
+        );
+        if let Some(point_radii) = &self.point_radii {
+            point_bin.set_point_radii(&point_radii.select(Axis(0), &kept));
+        }
+
+        (point_bin, Array1::from(dropped))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    #[test]
+    #[should_panic(expected = "keep_reset_backups(false)")]
+    fn test_builder_without_reset_backups() {
+        let points = array![
+            [0.0, 0.0, 0.0],
+            [0.5, 0.5, 0.5],
+            [5.0, 5.0, 5.0],
+        ];
+        let bin_widths = array![1.0, 1.0, 1.0];
+        let mut point_bin = PointBin3D::builder(points, bin_widths)
+            .keep_reset_backups(false)
+            .build();
+        assert!(point_bin.original_first_member.is_none());
+
+        point_bin.radius_search(&array![0.0, 0.0, 0.0].view(), 1.0);
+        let mut found = point_bin.found_indices().to_vec();
+        found.sort();
+        assert_eq!(found, vec![0, 1]);
+
+        point_bin.reset();
+    }
+
+    #[test]
+    fn test_fixed_grid_reports_dropped_points() {
+        let points = array![
+            [0.5, 0.5, 0.5],
+            [-0.5, 0.5, 0.5],
+            [1.5, 1.5, 1.5],
+            [2.5, 0.5, 0.5],
+            [1.0, 1.99, 0.0],
+            [0.5, 0.5, 7.0],
+        ];
+        let bin_widths = array![1.0, 1.0, 1.0];
+
+        // 2x2x2 grid covering [0, 2) on every axis
+        let (mut point_bin, dropped) = PointBin3D::builder(points.clone(), bin_widths)
+            .fixed_grid(array![0.0, 0.0, 0.0], [2, 2, 2])
+            .point_radii(array![1.0, 2.0, 3.0, 4.0, 5.0, 6.0])
+            .build_reporting_dropped();
+        assert_eq!(dropped, array![1, 3, 5]);
+
+        assert_eq!(point_bin.bin_shape(), &array![2, 2, 2]);
+        assert_eq!(point_bin.origin(), &array![0.0, 0.0, 0.0]);
+        assert_eq!(point_bin.original_points(), &points.select(Axis(0), &[0, 2, 4]));
+        assert_eq!(point_bin.original_order_point_radii(), Some(array![1.0, 3.0, 5.0]));
+
+        point_bin.radius_search(&array![1.0, 1.0, 1.0].view(), 10.0);
+        assert_eq!(point_bin.found_count(), 3);
+
+        // Without a fixed grid nothing is dropped
+        let (_, dropped) = PointBin3D::builder(points, array![1.0, 1.0, 1.0]).build_reporting_dropped();
+        assert!(dropped.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Bin widths must be positive")]
+    fn test_builder_rejects_bad_bin_widths() {
+        PointBin3D::builder(array![[0.0, 0.0, 0.0]], array![1.0, 0.0, 1.0]).build();
+    }
+}
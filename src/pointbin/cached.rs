@@ -0,0 +1,135 @@
+//! Read-only radius searches that memoize candidate points per query bin
+
+use std::num::NonZeroUsize;
+use std::ops::ControlFlow;
+
+use lru::LruCache;
+use ndarray::{Array1, ArrayView1};
+
+use super::PointBin3D;
+
+/// Cache key: the query's bin and the search radius rounded up to whole bins per axis
+type CandidateKey = ([i64; 3], [i64; 3]);
+
+/// Read-only radius searches over a PointBin3D with an LRU cache of candidate points
+///
+/// Every query falling in the same bin with a radius spanning the same number of bins
+/// touches the same bins, so the points in those bins are gathered once and reused;
+/// later queries only run the per-point distance test. This only pays off when
+/// queries cluster in a few bins: scattered queries fill the cache with lists that are
+/// never reused. The cache borrows the structure immutably, so destructive searches
+/// cannot run while it is alive and its candidate lists never go stale.
+///
+/// Created with `PointBin3D::cached_queries`.
+pub struct CachedQueries<'a> {
+    point_bin: &'a PointBin3D,
+    candidates: LruCache<CandidateKey, Vec<usize>>,
+    hits: usize,
+    misses: usize,
+}
+
+impl PointBin3D {
+    /// Start a session of cached read-only radius searches
+    ///
+    /// # Arguments
+    /// * `capacity` - Maximum number of candidate lists kept in the cache
+    pub fn cached_queries(&self, capacity: NonZeroUsize) -> CachedQueries<'_> {
+        CachedQueries {
+            point_bin: self,
+            candidates: LruCache::new(capacity),
+            hits: 0,
+            misses: 0,
+        }
+    }
+}
+
+impl CachedQueries<'_> {
+    /// Find the points within `radius` of a query point without removing them
+    ///
+    /// # Arguments
+    /// * `query_point` - 3D point to search around
+    /// * `radius` - Search radius
+    ///
+    /// # Returns
+    /// Original indices of the points within `radius`, in bin order
+    ///
+    /// # Panics
+    /// Panics if query_point doesn't have exactly 3 elements
+    pub fn radius_search(&mut self, query_point: &ArrayView1<f64>, radius: f64) -> Array1<i64> {
+        assert_eq!(query_point.len(), 3, "Query point must have 3 dimensions");
+        let point_bin = self.point_bin;
+
+        let home = point_bin.bin_of(query_point);
+        let reach: [i64; 3] = std::array::from_fn(|j| (radius / point_bin.bin_widths[j]).ceil() as i64);
+
+        if self.candidates.contains(&(home, reach)) {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+            // Any query in the home bin touches only bins within `reach` of it
+            let min_bin = std::array::from_fn(|j| (home[j] - reach[j]).max(0));
+            let max_bin = std::array::from_fn(|j| (home[j] + reach[j]).min(point_bin.bin_shape[j] - 1));
+            let mut candidates = Vec::new();
+            let _ = point_bin.visit_in_bins(min_bin, max_bin, |i| {
+                candidates.push(i);
+                ControlFlow::Continue(())
+            });
+            self.candidates.put((home, reach), candidates);
+        }
+
+        let radius_sq = radius * radius;
+        self.candidates
+            .get(&(home, reach))
+            .expect("candidates were just cached")
+            .iter()
+            .filter(|&&i| point_bin.dist_sq_to(i, query_point) <= radius_sq)
+            .map(|&i| point_bin.original_indices[i])
+            .collect()
+    }
+
+    /// Number of searches answered from a cached candidate list
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Number of searches that had to gather their candidates from the bins
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pointbin::scattered_points;
+    use ndarray::array;
+
+    #[test]
+    fn test_cached_queries_match_uncached() {
+        let points = scattered_points(500, [10.0; 3]);
+        let point_bin = PointBin3D::new(points, array![1.0, 1.0, 1.0]);
+        let mut cached = point_bin.cached_queries(NonZeroUsize::new(4).unwrap());
+
+        // Queries clustered in bin (4, 4, 4), then one elsewhere
+        let queries = [
+            array![4.1, 4.2, 4.3],
+            array![4.9, 4.1, 4.5],
+            array![4.5, 4.5, 4.5],
+            array![8.5, 1.5, 2.5],
+            array![4.2, 4.8, 4.0],
+        ];
+        for query in &queries {
+            let mut expected = point_bin.radius_search_clipped(&query.view(), 1.3).0.to_vec();
+            let mut found = cached.radius_search(&query.view(), 1.3).to_vec();
+            expected.sort();
+            found.sort();
+            assert_eq!(found, expected);
+        }
+        assert_eq!(cached.misses(), 2);
+        assert_eq!(cached.hits(), 3);
+
+        // A radius spanning a different number of bins gets its own entry
+        cached.radius_search(&queries[0].view(), 2.5);
+        assert_eq!(cached.misses(), 3);
+    }
+}
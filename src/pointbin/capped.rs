@@ -0,0 +1,133 @@
+//! Radius searches with a bound on the number of results
+
+use ndarray::ArrayView1;
+
+use super::PointBin3D;
+use crate::utils::squared_distance;
+
+/// Outcome of a radius search that may stop before finding every match
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CappedSearchResult {
+    /// Every point within the radius was found; holds how many
+    Complete(usize),
+    /// The cap was reached with matches left in the structure; holds how many were found
+    Truncated(usize),
+}
+
+impl PointBin3D {
+    /// Radius search that stops after `max_count` matches
+    ///
+    /// Behaves like `radius_search`, but once `max_count` points have been found the
+    /// search stops. The remaining matches stay in the structure, so repeating the call
+    /// picks up where it left off. Which matches are returned first follows the bin order
+    /// and is not by distance.
+    ///
+    /// # Arguments
+    /// * `query_point` - 3D point to search around
+    /// * `radius` - Search radius
+    /// * `max_count` - Maximum number of points to find
+    ///
+    /// # Returns
+    /// `Complete` if every match was found, or `Truncated` if the cap left some behind
+    ///
+    /// # Panics
+    /// Panics if query_point doesn't have exactly 3 elements
+    pub fn radius_search_with_max_count(
+        &mut self,
+        query_point: &ArrayView1<f64>,
+        radius: f64,
+        max_count: usize,
+    ) -> CappedSearchResult {
+        assert_eq!(query_point.len(), 3, "Query point must have 3 dimensions");
+
+        let (min_bin, max_bin) = self.bin_range(query_point, radius);
+        let radius_sq = radius * radius;
+
+        let mut n_found = 0;
+        'bins: for ix in min_bin[0]..=max_bin[0] {
+            for iy in min_bin[1]..=max_bin[1] {
+                for iz in min_bin[2]..=max_bin[2] {
+                    if n_found == max_count {
+                        break 'bins;
+                    }
+                    let bin = [ix as usize, iy as usize, iz as usize];
+                    self.unlink_matching_in_bin(bin, true, |_, point| {
+                        if n_found < max_count && squared_distance(&point, query_point) <= radius_sq {
+                            n_found += 1;
+                            true
+                        } else {
+                            false
+                        }
+                    });
+                }
+            }
+        }
+
+        if n_found == max_count && self.any_within(query_point, radius) {
+            CappedSearchResult::Truncated(n_found)
+        } else {
+            CappedSearchResult::Complete(n_found)
+        }
+    }
+
+    /// Radius search that records at most `max_bytes` worth of found indices
+    ///
+    /// Caps the number of results at `max_bytes / size_of::<i64>()`, the size of an
+    /// entry in the found indices, and otherwise behaves like
+    /// `radius_search_with_max_count`.
+    ///
+    /// # Arguments
+    /// * `query_point` - 3D point to search around
+    /// * `radius` - Search radius
+    /// * `max_bytes` - Memory budget for this search's results
+    ///
+    /// # Panics
+    /// Panics if query_point doesn't have exactly 3 elements
+    pub fn radius_search_capped(&mut self, query_point: &ArrayView1<f64>, radius: f64, max_bytes: usize) -> CappedSearchResult {
+        let max_results = max_bytes / std::mem::size_of::<i64>();
+        self.radius_search_with_max_count(query_point, radius, max_results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::{array, Array2};
+
+    #[test]
+    fn test_radius_search_with_max_count() {
+        let mut points = Array2::<f64>::zeros((10, 3));
+        for i in 0..10 {
+            points[[i, 0]] = i as f64 * 0.3;
+        }
+        let mut point_bin = PointBin3D::new(points, array![1.0, 1.0, 1.0]);
+        let query = array![0.0, 0.0, 0.0];
+
+        assert_eq!(point_bin.radius_search_with_max_count(&query.view(), 1.0, 2), CappedSearchResult::Truncated(2));
+        assert_eq!(point_bin.found_count(), 2);
+
+        // Two more within the radius are left for the next call
+        assert_eq!(point_bin.radius_search_with_max_count(&query.view(), 1.0, 5), CappedSearchResult::Complete(2));
+        let mut found = point_bin.found_indices().to_vec();
+        found.sort();
+        assert_eq!(found, vec![0, 1, 2, 3]);
+
+        // Exactly reaching the cap with nothing left over is complete
+        point_bin.reset();
+        assert_eq!(point_bin.radius_search_with_max_count(&query.view(), 1.0, 4), CappedSearchResult::Complete(4));
+    }
+
+    #[test]
+    fn test_radius_search_capped() {
+        let mut points = Array2::<f64>::zeros((10, 3));
+        for i in 0..10 {
+            points[[i, 0]] = i as f64 * 0.3;
+        }
+        let mut point_bin = PointBin3D::new(points, array![1.0, 1.0, 1.0]);
+
+        // 31 bytes hold three i64 indices
+        let result = point_bin.radius_search_capped(&array![0.0, 0.0, 0.0].view(), 10.0, 31);
+        assert_eq!(result, CappedSearchResult::Truncated(3));
+        assert_eq!(point_bin.remaining_count(), 7);
+    }
+}
@@ -0,0 +1,352 @@
+//! Diagnostics for tuning bin widths and estimating query cost
+
+use std::collections::BTreeMap;
+use std::ops::ControlFlow;
+
+use ndarray::Array1;
+
+use super::PointBin3D;
+
+/// Summary of simulated query cost over a set of probe locations
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryCostProfile {
+    /// Number of probe queries run
+    pub samples: usize,
+    /// Mean number of bins visited per query
+    pub mean_bins_visited: f64,
+    /// Median number of bins visited per query
+    pub median_bins_visited: f64,
+    /// Largest number of bins visited by a single query
+    pub max_bins_visited: usize,
+    /// Mean number of points distance-tested per query
+    pub mean_points_tested: f64,
+    /// Median number of points distance-tested per query
+    pub median_points_tested: f64,
+    /// Largest number of points distance-tested by a single query
+    pub max_points_tested: usize,
+}
+
+/// Occupancy statistics over every bin of the grid
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinStats {
+    /// Number of bins in the grid
+    pub total_bins: usize,
+    /// Number of bins holding at least one remaining point
+    pub occupied_bins: usize,
+    /// Number of bins holding no remaining points
+    pub empty_bins: usize,
+    /// Most points in a single bin
+    pub max_occupancy: usize,
+    /// Fewest points in an occupied bin (0 if every bin is empty)
+    pub min_occupancy_nonempty: usize,
+    /// Mean points per bin, over all bins including empty ones
+    pub mean_occupancy: f64,
+    /// Standard deviation of the points per bin, over all bins including empty ones
+    pub std_occupancy: f64,
+    /// `(occupancy, number of bins)` for every occupancy that occurs, ascending
+    pub histogram: Vec<(usize, usize)>,
+}
+
+impl PointBin3D {
+    /// Estimate the cost of radius queries by probing the grid
+    ///
+    /// Runs read-only probes at `samples` pseudo-random locations spread over the grid
+    /// extent and records how many bins each visits and how many points it distance-tests.
+    /// The locations are deterministic, so repeated calls give identical profiles.
+    ///
+    /// # Arguments
+    /// * `radius` - Query radius to profile
+    /// * `samples` - Number of probe locations
+    ///
+    /// # Returns
+    /// Mean, median and maximum bins visited and points tested per query
+    pub fn profile_query_cost(&self, radius: f64, samples: usize) -> QueryCostProfile {
+        let mut rng_state: u64 = 0x5eed_b1c3_7a11_0c47;
+        let mut bins_visited = Vec::with_capacity(samples);
+        let mut points_tested = Vec::with_capacity(samples);
+
+        let mut query = Array1::<f64>::zeros(3);
+        for _ in 0..samples {
+            for j in 0..3 {
+                let extent = self.bin_shape[j] as f64 * self.bin_widths[j];
+                query[j] = self.grid_origin[j] + unit_interval(&mut rng_state) * extent;
+            }
+
+            let (min_bin, max_bin) = self.bin_range(&query.view(), radius);
+            let n_bins: usize = (0..3)
+                .map(|j| (max_bin[j] - min_bin[j] + 1).max(0) as usize)
+                .product();
+
+            let mut n_tested = 0;
+            let _ = self.visit_in_bins(min_bin, max_bin, |_| {
+                n_tested += 1;
+                ControlFlow::Continue(())
+            });
+
+            bins_visited.push(n_bins);
+            points_tested.push(n_tested);
+        }
+
+        let (mean_bins_visited, median_bins_visited, max_bins_visited) = summarize(&mut bins_visited);
+        let (mean_points_tested, median_points_tested, max_points_tested) = summarize(&mut points_tested);
+
+        QueryCostProfile {
+            samples,
+            mean_bins_visited,
+            median_bins_visited,
+            max_bins_visited,
+            mean_points_tested,
+            median_points_tested,
+            max_points_tested,
+        }
+    }
+
+    /// Summarize how the remaining points are spread over the bins
+    ///
+    /// Walks every bin's linked list once, so the cost is O(n_points + n_bins).
+    pub fn bin_stats(&self) -> BinStats {
+        let mut histogram: BTreeMap<usize, usize> = BTreeMap::new();
+        for &head in self.first_member.iter() {
+            let mut occupancy = 0;
+            let mut i = head;
+            while i != -1 {
+                occupancy += 1;
+                i = self.next_member[i as usize];
+            }
+            *histogram.entry(occupancy).or_insert(0) += 1;
+        }
+
+        let total_bins = self.first_member.len();
+        let empty_bins = histogram.get(&0).copied().unwrap_or(0);
+        let n_points: usize = histogram.iter().map(|(occupancy, bins)| occupancy * bins).sum();
+        let mean_occupancy = if total_bins > 0 { n_points as f64 / total_bins as f64 } else { 0.0 };
+        let variance = if total_bins > 0 {
+            histogram
+                .iter()
+                .map(|(&occupancy, &bins)| bins as f64 * (occupancy as f64 - mean_occupancy).powi(2))
+                .sum::<f64>()
+                / total_bins as f64
+        } else {
+            0.0
+        };
+
+        BinStats {
+            total_bins,
+            occupied_bins: total_bins - empty_bins,
+            empty_bins,
+            max_occupancy: histogram.keys().next_back().copied().unwrap_or(0),
+            min_occupancy_nonempty: histogram.keys().copied().find(|&occupancy| occupancy > 0).unwrap_or(0),
+            mean_occupancy,
+            std_occupancy: variance.sqrt(),
+            histogram: histogram.into_iter().collect(),
+        }
+    }
+
+    /// Number of bins holding at least one remaining point
+    pub fn occupied_bin_count(&self) -> usize {
+        self.first_member.iter().filter(|&&head| head != -1).count()
+    }
+
+    /// Fraction of the grid's bins that hold at least one remaining point
+    ///
+    /// The dense grid costs memory for every bin, occupied or not, so a very low value
+    /// (say 0.001) means most of that memory is spent on empty bins and larger bin
+    /// widths would suit the data better.
+    ///
+    /// # Returns
+    /// `occupied_bin_count()` divided by the number of bins, or 0 for an empty grid
+    pub fn grid_utilization(&self) -> f64 {
+        let total_bins = self.first_member.len();
+        if total_bins == 0 {
+            return 0.0;
+        }
+        self.occupied_bin_count() as f64 / total_bins as f64
+    }
+
+    /// Coordinate variance of the points in each non-empty bin
+    ///
+    /// For every bin holding at least one remaining point, computes the population
+    /// variance of its points' x, y and z coordinates. A variance close to
+    /// `bin_width² / 12` means the points spread evenly across the bin; much smaller
+    /// values mean they cluster, which suggests the bin widths are larger than needed.
+    ///
+    /// # Returns
+    /// `(bin, [var_x, var_y, var_z])` for each non-empty bin, in row-major bin order
+    pub fn bin_variances(&self) -> Vec<([usize; 3], [f64; 3])> {
+        let mut variances = Vec::new();
+        for (bin, &head) in self.first_member.indexed_iter() {
+            if head == -1 {
+                continue;
+            }
+
+            // Welford's update, which stays accurate far from the origin where the
+            // sum of squares would cancel
+            let mut n = 0.0;
+            let mut mean = [0.0; 3];
+            let mut m2 = [0.0; 3];
+            let mut i = head;
+            while i != -1 {
+                n += 1.0;
+                for j in 0..3 {
+                    let x = self.points[[i as usize, j]];
+                    let delta = x - mean[j];
+                    mean[j] += delta / n;
+                    m2[j] += delta * (x - mean[j]);
+                }
+                i = self.next_member[i as usize];
+            }
+
+            let variance = std::array::from_fn(|j| m2[j] / n);
+            variances.push(([bin.0, bin.1, bin.2], variance));
+        }
+        variances
+    }
+}
+
+/// Draw a uniform value in [0, 1) from a SplitMix64 generator state
+fn unit_interval(state: &mut u64) -> f64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^= z >> 31;
+    (z >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Mean, median and maximum of a list of counts (all zero when empty)
+fn summarize(values: &mut [usize]) -> (f64, f64, usize) {
+    if values.is_empty() {
+        return (0.0, 0.0, 0);
+    }
+
+    values.sort_unstable();
+    let n = values.len();
+    let mean = values.iter().sum::<usize>() as f64 / n as f64;
+    let median = if n % 2 == 1 {
+        values[n / 2] as f64
+    } else {
+        (values[n / 2 - 1] + values[n / 2]) as f64 / 2.0
+    };
+
+    (mean, median, values[n - 1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::Array2;
+
+    #[test]
+    fn test_profile_query_cost_uniform() {
+        // 10x10x10 lattice with one point per unit bin
+        let mut points = Array2::<f64>::zeros((1000, 3));
+        for i in 0..1000 {
+            points[[i, 0]] = (i % 10) as f64 + 0.5;
+            points[[i, 1]] = ((i / 10) % 10) as f64 + 0.5;
+            points[[i, 2]] = (i / 100) as f64 + 0.5;
+        }
+        let bin_widths = ndarray::array![1.0, 1.0, 1.0];
+        let point_bin = PointBin3D::new(points, bin_widths);
+
+        let profile = point_bin.profile_query_cost(1.0, 200);
+        assert_eq!(profile.samples, 200);
+
+        // An interior query of radius 1 spans at most 3 bins per axis, and with one
+        // point per bin every visited bin contributes exactly one tested point
+        assert!(profile.max_bins_visited <= 27);
+        assert!(profile.mean_points_tested > 8.0 && profile.mean_points_tested <= 27.0);
+        assert_eq!(profile.mean_points_tested, profile.mean_bins_visited);
+        assert_eq!(profile, point_bin.profile_query_cost(1.0, 200));
+    }
+
+    #[test]
+    fn test_bin_stats() {
+        // 2x1x1 grid: three points in one bin, one in the other
+        let points = ndarray::array![
+            [0.1, 0.1, 0.1],
+            [0.2, 0.2, 0.2],
+            [0.3, 0.3, 0.3],
+            [1.5, 0.5, 0.5],
+        ];
+        let bin_widths = ndarray::array![1.0, 1.0, 1.0];
+        let mut point_bin = PointBin3D::new(points, bin_widths);
+
+        let stats = point_bin.bin_stats();
+        assert_eq!(stats.total_bins, 2);
+        assert_eq!(stats.occupied_bins, 2);
+        assert_eq!(stats.empty_bins, 0);
+        assert_eq!(stats.max_occupancy, 3);
+        assert_eq!(stats.min_occupancy_nonempty, 1);
+        assert_eq!(stats.mean_occupancy, 2.0);
+        assert_eq!(stats.std_occupancy, 1.0);
+        assert_eq!(stats.histogram, vec![(1, 1), (3, 1)]);
+
+        point_bin.radius_search(&ndarray::array![1.5, 0.5, 0.5].view(), 0.1);
+        let stats = point_bin.bin_stats();
+        assert_eq!(stats.empty_bins, 1);
+        assert_eq!(stats.min_occupancy_nonempty, 3);
+        assert_eq!(stats.histogram, vec![(0, 1), (3, 1)]);
+    }
+
+    #[test]
+    fn test_grid_utilization() {
+        // Opposite corners of a 4x2x5 grid, plus a second point in the first corner bin
+        let points = ndarray::array![
+            [0.0, 0.0, 0.0],
+            [0.5, 0.5, 0.5],
+            [3.5, 1.5, 4.5],
+        ];
+        let mut point_bin = PointBin3D::new(points, ndarray::array![1.0, 1.0, 1.0]);
+        assert_eq!(point_bin.bin_shape(), &ndarray::array![4, 2, 5]);
+        assert_eq!(point_bin.occupied_bin_count(), 2);
+        assert!((point_bin.grid_utilization() - 2.0 / 40.0).abs() < 1e-12);
+
+        point_bin.radius_search(&ndarray::array![3.5, 1.5, 4.5].view(), 0.1);
+        assert!((point_bin.grid_utilization() - 1.0 / 40.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_bin_variances() {
+        let points = ndarray::array![
+            [0.0, 0.0, 0.0],
+            [0.8, 0.0, 0.0],
+            [1.5, 1.2, 0.5],
+            [1.5, 1.4, 0.7],
+            [1.5, 1.6, 0.9],
+        ];
+        let bin_widths = ndarray::array![1.0, 1.0, 1.0];
+        let mut point_bin = PointBin3D::new(points, bin_widths);
+
+        let variances = point_bin.bin_variances();
+        assert_eq!(variances.len(), 2);
+
+        let (bin, variance) = variances[0];
+        assert_eq!(bin, [0, 0, 0]);
+        assert!((variance[0] - 0.16).abs() < 1e-12);
+        assert_eq!(variance[1], 0.0);
+
+        let (bin, variance) = variances[1];
+        assert_eq!(bin, [1, 1, 0]);
+        assert_eq!(variance[0], 0.0);
+        assert!((variance[1] - 0.08 / 3.0).abs() < 1e-12);
+        assert!((variance[2] - 0.08 / 3.0).abs() < 1e-12);
+
+        // Emptied bins are skipped
+        point_bin.radius_search(&ndarray::array![0.4, 0.0, 0.0].view(), 0.5);
+        assert_eq!(point_bin.bin_variances().len(), 1);
+    }
+
+    #[test]
+    fn test_bin_variances_far_from_origin() {
+        // The sum of squares of these coordinates cancels completely in f64
+        let points = ndarray::array![
+            [1e9 + 0.1, 0.0, 0.0],
+            [1e9 + 0.3, 0.0, 0.0],
+            [1e9 + 0.5, 0.0, 0.0],
+        ];
+        let point_bin = PointBin3D::new(points, ndarray::array![1.0, 1.0, 1.0]);
+
+        let (_, variance) = point_bin.bin_variances()[0];
+        assert!((variance[0] - 0.08 / 3.0).abs() < 1e-6);
+    }
+}
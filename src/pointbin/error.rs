@@ -0,0 +1,56 @@
+//! Errors for invalid constructor input
+
+use std::fmt;
+
+use ndarray::{Array1, Array2};
+
+/// Error returned by `PointBin3D::try_new` for input it cannot index
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BucketError {
+    /// The points don't have exactly 3 columns
+    WrongPointDims { ncols: usize },
+    /// The bin widths don't have exactly 3 elements
+    WrongBinWidthsLen { len: usize },
+    /// A bin width is zero, negative or NaN
+    NonPositiveBinWidth { axis: usize, width: f64 },
+    /// There are no points, so there is no grid to build
+    EmptyPoints,
+}
+
+impl BucketError {
+    /// Check constructor input, returning the first problem found
+    pub(crate) fn check(original_points: &Array2<f64>, bin_widths: &Array1<f64>) -> Result<(), BucketError> {
+        if original_points.ncols() != 3 {
+            return Err(BucketError::WrongPointDims { ncols: original_points.ncols() });
+        }
+        if bin_widths.len() != 3 {
+            return Err(BucketError::WrongBinWidthsLen { len: bin_widths.len() });
+        }
+        if let Some((axis, &width)) = bin_widths.iter().enumerate().find(|&(_, &w)| w <= 0.0 || w.is_nan()) {
+            return Err(BucketError::NonPositiveBinWidth { axis, width });
+        }
+        if original_points.nrows() == 0 {
+            return Err(BucketError::EmptyPoints);
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for BucketError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BucketError::WrongPointDims { ncols } => {
+                write!(f, "Points must have exactly 3 columns (x, y, z), got {}", ncols)
+            }
+            BucketError::WrongBinWidthsLen { len } => {
+                write!(f, "Bin widths must have exactly 3 elements, got {}", len)
+            }
+            BucketError::NonPositiveBinWidth { axis, width } => {
+                write!(f, "Bin widths must be positive, got {} along axis {}", width, axis)
+            }
+            BucketError::EmptyPoints => write!(f, "Points must contain at least one point"),
+        }
+    }
+}
+
+impl std::error::Error for BucketError {}
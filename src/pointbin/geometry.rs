@@ -0,0 +1,127 @@
+//! Geometric reconstruction helpers built on top of PointBin3D
+
+use std::collections::HashSet;
+use std::ops::ControlFlow;
+
+use super::PointBin3D;
+
+impl PointBin3D {
+    /// Greedily connect nearby points into triangles
+    ///
+    /// For each point still in the structure, finds its two nearest neighbors within
+    /// `connect_radius` and emits the triangle `[i, j, k]` if the three points are not
+    /// collinear. Triangles are reported once even when several of their corners produce them.
+    ///
+    /// # Arguments
+    /// * `connect_radius` - Maximum distance from a point to its triangle neighbors
+    ///
+    /// # Returns
+    /// Triangles as triples of original point indices, ordered by their first corner
+    pub fn connect_to_mesh(&self, connect_radius: f64) -> Vec<[usize; 3]> {
+        let mut live: Vec<usize> = (0..self.points.nrows())
+            .filter(|&i| !self.is_removed(i))
+            .collect();
+        live.sort_unstable_by_key(|&i| self.original_indices[i]);
+
+        let mut seen: HashSet<[usize; 3]> = HashSet::new();
+        let mut triangles = Vec::new();
+
+        for i in live {
+            // Two nearest neighbors as (dist_sq, sorted index)
+            let mut nearest: [Option<(f64, usize)>; 2] = [None, None];
+            let _ = self.visit_within(&self.points.row(i), connect_radius, |j, dist_sq| {
+                if j != i {
+                    let closer_than = |slot: Option<(f64, usize)>| match slot {
+                        Some((d, _)) => dist_sq < d,
+                        None => true,
+                    };
+                    if closer_than(nearest[0]) {
+                        nearest[1] = nearest[0];
+                        nearest[0] = Some((dist_sq, j));
+                    } else if closer_than(nearest[1]) {
+                        nearest[1] = Some((dist_sq, j));
+                    }
+                }
+                ControlFlow::Continue(())
+            });
+
+            let (Some((_, j)), Some((_, k))) = (nearest[0], nearest[1]) else {
+                continue;
+            };
+            if self.is_degenerate_triangle(i, j, k) {
+                continue;
+            }
+
+            let triangle = [
+                self.original_indices[i] as usize,
+                self.original_indices[j] as usize,
+                self.original_indices[k] as usize,
+            ];
+            let mut key = triangle;
+            key.sort_unstable();
+            if seen.insert(key) {
+                triangles.push(triangle);
+            }
+        }
+
+        triangles
+    }
+
+    /// Whether three points (by sorted index) are collinear to within rounding error
+    fn is_degenerate_triangle(&self, i: usize, j: usize, k: usize) -> bool {
+        let mut a = [0.0; 3];
+        let mut b = [0.0; 3];
+        for d in 0..3 {
+            a[d] = self.points[[j, d]] - self.points[[i, d]];
+            b[d] = self.points[[k, d]] - self.points[[i, d]];
+        }
+        let cross = [
+            a[1] * b[2] - a[2] * b[1],
+            a[2] * b[0] - a[0] * b[2],
+            a[0] * b[1] - a[1] * b[0],
+        ];
+        let cross_sq: f64 = cross.iter().map(|c| c * c).sum();
+        let a_sq: f64 = a.iter().map(|c| c * c).sum();
+        let b_sq: f64 = b.iter().map(|c| c * c).sum();
+
+        cross_sq <= 1e-24 * a_sq * b_sq
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    #[test]
+    fn test_connect_to_mesh() {
+        let points = array![
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [10.0, 10.0, 10.0], // Too far from anything
+        ];
+        let bin_widths = array![2.0, 2.0, 2.0];
+        let point_bin = PointBin3D::new(points, bin_widths);
+
+        let triangles = point_bin.connect_to_mesh(1.5);
+        assert_eq!(triangles.len(), 1);
+
+        let mut corners = triangles[0].to_vec();
+        corners.sort();
+        assert_eq!(corners, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_connect_to_mesh_skips_collinear() {
+        let points = array![
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [2.0, 0.0, 0.0],
+        ];
+        let bin_widths = array![1.0, 1.0, 1.0];
+        let point_bin = PointBin3D::new(points, bin_widths);
+
+        assert!(point_bin.connect_to_mesh(2.5).is_empty());
+    }
+}
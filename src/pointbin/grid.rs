@@ -0,0 +1,223 @@
+//! Description of the spatial frame covered by the bin grid, and searches in bin units
+
+use ndarray::{Array1, Array2, ArrayView1};
+
+use super::PointBin3D;
+use crate::utils::squared_distance;
+
+/// Origin, spacing and extent of a PointBin3D's bin grid
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridMetadata {
+    /// Origin of the grid, as reported by `origin()` (3,)
+    pub origin: Array1<f64>,
+    /// Whole bins the origin was moved by with `shift_origin_by_bins`; bin `(i, j, k)`
+    /// spans `origin + ([i, j, k] - bin_offset) * bin_widths`
+    pub bin_offset: [i64; 3],
+    /// Width of each bin in x, y, z (3,)
+    pub bin_widths: Array1<f64>,
+    /// Number of bins along x, y, z (3,)
+    pub bin_shape: Array1<i64>,
+    /// Maximum corner of the grid, `origin + (bin_shape - bin_offset) * bin_widths` (3,)
+    pub max_corner: Array1<f64>,
+}
+
+/// Shape of the neighbourhood of bins used by `radius_search_bins`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinMetric {
+    /// Bins at most `bin_radius` cells away along every axis, a cube of
+    /// `(2 * bin_radius + 1)^3` bins
+    Chebyshev,
+    /// Bins whose offset `(dx, dy, dz)` in cells satisfies
+    /// `dx² + dy² + dz² <= bin_radius²`, a sphere of bins
+    Euclidean,
+}
+
+impl PointBin3D {
+    /// Get the origin, bin widths, bin shape and maximum corner of the grid in one call
+    pub fn grid_metadata(&self) -> GridMetadata {
+        let max_corner = &self.grid_origin + &(self.bin_shape.mapv(|n| n as f64) * &self.bin_widths);
+
+        GridMetadata {
+            origin: self.reported_origin.clone(),
+            bin_offset: self.bin_offset,
+            bin_widths: self.bin_widths.clone(),
+            bin_shape: self.bin_shape.clone(),
+            max_corner,
+        }
+    }
+
+    /// Snap every point to the centre of its bin
+    ///
+    /// Each coordinate becomes `origin[j] + (bin[j] + 0.5) * bin_widths[j]`, quantizing
+    /// the cloud onto the grid. The structure itself is not modified, and removed points
+    /// are included.
+    ///
+    /// # Returns
+    /// 2D array of shape (n_points, 3) with the bin centres, in original order
+    pub fn align_to_grid(&self) -> Array2<f64> {
+        let mut aligned = self.original_points.clone();
+        for mut point in aligned.rows_mut() {
+            for j in 0..3 {
+                let bin = ((point[j] - self.grid_origin[j]) / self.bin_widths[j]).floor();
+                point[j] = self.grid_origin[j] + (bin + 0.5) * self.bin_widths[j];
+            }
+        }
+        aligned
+    }
+}
+
+impl PointBin3D {
+    /// Radius search with the radius counted in bins rather than distance
+    ///
+    /// Removes the remaining points of every bin within `bin_radius` cells of the
+    /// query's bin, measured with `metric`, and adds them to the found indices. Points
+    /// are taken whole-bin, without a distance test, unless `max_distance` is given, in
+    /// which case only points within that distance of the query are removed. The query's
+    /// bin is clamped to the grid and the neighbourhood is clipped to it.
+    ///
+    /// # Arguments
+    /// * `query_point` - 3D point whose bin is the centre of the neighbourhood
+    /// * `bin_radius` - Neighbourhood radius in cells; 0 searches only the query's bin
+    /// * `metric` - Whether the neighbourhood is a cube or a sphere of bins
+    /// * `max_distance` - Optional physical radius the points must also lie within
+    ///
+    /// # Returns
+    /// Original indices of the points removed by this call
+    ///
+    /// # Panics
+    /// Panics if query_point doesn't have exactly 3 elements
+    pub fn radius_search_bins(
+        &mut self,
+        query_point: &ArrayView1<f64>,
+        bin_radius: i64,
+        metric: BinMetric,
+        max_distance: Option<f64>,
+    ) -> Array1<i64> {
+        assert_eq!(query_point.len(), 3, "Query point must have 3 dimensions");
+
+        let start = self.found_indices_buffer.len();
+        let centre = self.bin_of(query_point);
+        let max_dist_sq = max_distance.map_or(f64::INFINITY, |d| d * d);
+
+        // Only the offsets that land inside the grid, so huge radii stay cheap
+        let lo: [i64; 3] = std::array::from_fn(|j| bin_radius.saturating_neg().max(-centre[j]));
+        let hi: [i64; 3] = std::array::from_fn(|j| bin_radius.min(self.bin_shape[j] - 1 - centre[j]));
+        let radius_sq = i128::from(bin_radius) * i128::from(bin_radius);
+
+        for dx in lo[0]..=hi[0] {
+            for dy in lo[1]..=hi[1] {
+                for dz in lo[2]..=hi[2] {
+                    let dist_sq: i128 = [dx, dy, dz].iter().map(|&d| i128::from(d) * i128::from(d)).sum();
+                    if metric == BinMetric::Euclidean && dist_sq > radius_sq {
+                        continue;
+                    }
+                    let bin = [centre[0] + dx, centre[1] + dy, centre[2] + dz];
+                    self.unlink_matching_in_bin(bin.map(|b| b as usize), true, |_, point| {
+                        max_dist_sq == f64::INFINITY || squared_distance(&point, query_point) <= max_dist_sq
+                    });
+                }
+            }
+        }
+
+        self.found_indices_buffer[start..]
+            .iter()
+            .map(|&sorted_idx| self.original_indices[sorted_idx as usize])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    #[test]
+    fn test_grid_metadata_matches_accessors() {
+        let points = array![
+            [1.0, 2.0, 3.0],
+            [4.5, 2.5, 3.5],
+        ];
+        let bin_widths = array![1.0, 0.5, 2.0];
+        let point_bin = PointBin3D::new(points, bin_widths);
+
+        let metadata = point_bin.grid_metadata();
+        assert_eq!(&metadata.origin, point_bin.origin());
+        assert_eq!(&metadata.bin_widths, point_bin.bin_widths());
+        assert_eq!(&metadata.bin_shape, point_bin.bin_shape());
+        assert_eq!(metadata.max_corner, array![5.0, 3.0, 5.0]);
+    }
+
+    #[test]
+    fn test_align_to_grid() {
+        let points = array![
+            [1.0, 2.0, 3.0],
+            [1.9, 2.2, 4.9],
+            [4.5, 2.5, 3.5],
+        ];
+        let bin_widths = array![1.0, 0.5, 2.0];
+        let point_bin = PointBin3D::new(points.clone(), bin_widths);
+
+        let aligned = point_bin.align_to_grid();
+        assert_eq!(
+            aligned,
+            array![
+                [1.5, 2.25, 4.0],
+                [1.5, 2.25, 4.0],
+                [4.5, 2.75, 4.0],
+            ]
+        );
+        assert_eq!(point_bin.original_points(), &points);
+    }
+
+    #[test]
+    fn test_radius_search_bins() {
+        // One point per bin of a 5x5x5 grid with its origin at the first point
+        let points = Array2::from_shape_fn((125, 3), |(i, j)| [i / 25, (i / 5) % 5, i % 5][j] as f64 + 0.5);
+        let query = array![2.6, 3.4, 2.9];
+        let neighbourhood = |point_bin: &mut PointBin3D, bin_radius, metric, max_distance| {
+            let mut found = point_bin.radius_search_bins(&query.view(), bin_radius, metric, max_distance);
+            point_bin.reset();
+            found.as_slice_mut().unwrap().sort();
+            found
+        };
+        let mut point_bin = PointBin3D::new(points.clone(), array![1.0, 1.0, 1.0]);
+
+        // The 27-bin cube around bin (2, 2, 2)
+        let cube = neighbourhood(&mut point_bin, 1, BinMetric::Chebyshev, None);
+        let expected: Vec<i64> = (0..125)
+            .filter(|&i| points.row(i as usize).iter().all(|&c| (c - 2.5).abs() <= 1.0))
+            .collect();
+        assert_eq!(cube.len(), 27);
+        assert_eq!(cube.to_vec(), expected);
+
+        // The sphere of bins keeps the centre and its 6 face neighbours, and a distance
+        // filter keeps only the points close enough
+        assert_eq!(neighbourhood(&mut point_bin, 1, BinMetric::Euclidean, None).len(), 7);
+        assert_eq!(neighbourhood(&mut point_bin, 2, BinMetric::Euclidean, None).len(), 33);
+        let within: Vec<i64> = expected
+            .iter()
+            .copied()
+            .filter(|&i| squared_distance(&points.row(i as usize), &query.view()) <= 1.0)
+            .collect();
+        assert!(!within.is_empty() && within.len() < 27);
+        assert_eq!(neighbourhood(&mut point_bin, 1, BinMetric::Chebyshev, Some(1.0)).to_vec(), within);
+        assert_eq!(neighbourhood(&mut point_bin, 0, BinMetric::Chebyshev, None).to_vec(), vec![62]);
+
+        // Clipped at the grid edge, and removed points aren't found twice
+        let corner = point_bin.radius_search_bins(&array![-3.0, 0.0, 0.0].view(), 1, BinMetric::Chebyshev, None);
+        assert_eq!(corner.len(), 8);
+        assert_eq!(point_bin.radius_search_bins(&array![0.0, 0.0, 0.0].view(), 1, BinMetric::Chebyshev, None).len(), 0);
+    }
+
+    #[test]
+    fn test_radius_search_bins_huge_radius() {
+        let points = array![[0.5, 0.5, 0.5], [3.5, 0.5, 0.5], [3.5, 3.5, 3.5]];
+        for metric in [BinMetric::Chebyshev, BinMetric::Euclidean] {
+            let mut point_bin = PointBin3D::new(points.clone(), array![1.0, 1.0, 1.0]);
+            let mut found = point_bin.radius_search_bins(&array![0.5, 0.5, 0.5].view(), i64::MAX, metric, None).to_vec();
+            found.sort();
+            assert_eq!(found, vec![0, 1, 2]);
+            assert_eq!(point_bin.remaining_count(), 0);
+        }
+    }
+}
@@ -0,0 +1,332 @@
+//! Lightweight JSON snapshots of a PointBin3D, without serde
+
+use std::fmt::{self, Display, Write};
+
+use ndarray::{Array1, Array2};
+
+use super::{BucketError, PointBin3D};
+use crate::json::{self, JsonValue};
+
+/// Error returned when a JSON snapshot cannot be loaded
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonParseError {
+    /// The text is not valid JSON
+    Syntax { position: usize, message: String },
+    /// A required field is absent
+    MissingField(&'static str),
+    /// A field has the wrong type or a value inconsistent with the rest of the snapshot
+    InvalidField { field: &'static str, message: String },
+}
+
+impl fmt::Display for JsonParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonParseError::Syntax { position, message } => write!(f, "{} at byte {}", message, position),
+            JsonParseError::MissingField(field) => write!(f, "Missing field '{}'", field),
+            JsonParseError::InvalidField { field, message } => write!(f, "Invalid field '{}': {}", field, message),
+        }
+    }
+}
+
+impl std::error::Error for JsonParseError {}
+
+impl PointBin3D {
+    /// Serialize the structure and its search state to a JSON string
+    ///
+    /// The object holds `n_points`, `bin_widths`, `origin`, `bin_shape`, `found_count`,
+    /// the original `points` flattened row by row, the `next_member` linked list in
+    /// bin-sorted order (with removed points marked -2), the `original_indices` of its
+    /// entries, the `found_indices` and the `bin_offset` of `grid_metadata()`. Per-point
+    /// radii are not included. JSON has no NaN or infinity, so non-finite values are
+    /// written as the strings `"NaN"`, `"Infinity"` and `"-Infinity"`.
+    pub fn to_json_string(&self) -> String {
+        fn list<T: Display>(values: impl IntoIterator<Item = T>) -> String {
+            let items: Vec<String> = values.into_iter().map(|v| v.to_string()).collect();
+            format!("[{}]", items.join(","))
+        }
+        fn number(v: &f64) -> String {
+            match *v {
+                v if v.is_nan() => "\"NaN\"".to_string(),
+                f64::INFINITY => "\"Infinity\"".to_string(),
+                f64::NEG_INFINITY => "\"-Infinity\"".to_string(),
+                v => v.to_string(),
+            }
+        }
+
+        let mut out = String::from("{");
+        let _ = write!(out, "\"n_points\":{},", self.points.nrows());
+        let _ = write!(out, "\"bin_widths\":{},", list(self.bin_widths.iter().map(number)));
+        let _ = write!(out, "\"origin\":{},", list(self.grid_origin.iter().map(number)));
+        let _ = write!(out, "\"bin_shape\":{},", list(self.bin_shape.iter()));
+        let _ = write!(out, "\"found_count\":{},", self.found_count());
+        let _ = write!(out, "\"points\":{},", list(self.original_points.iter().map(number)));
+        let _ = write!(out, "\"next_member\":{},", list(self.next_member.iter()));
+        let _ = write!(out, "\"original_indices\":{},", list(self.original_indices.iter()));
+        let _ = write!(out, "\"found_indices\":{},", list(self.found_indices().iter()));
+        let _ = write!(out, "\"bin_offset\":{}", list(self.bin_offset.iter()));
+        out.push('}');
+        out
+    }
+
+    /// Rebuild a structure from the output of `to_json_string`
+    ///
+    /// Like `from_bytes`, the points are binned on the stored grid, cut back to the bins
+    /// the points fall in, and then the removed
+    /// points and found indices are restored. `reset()` brings back every point as
+    /// usual. Snapshots without `original_indices` are read as listing `next_member` in
+    /// the order the points are rebinned in.
+    ///
+    /// # Arguments
+    /// * `s` - JSON text produced by `to_json_string`
+    ///
+    /// # Returns
+    /// The restored structure, or a `JsonParseError` if the text is not a valid snapshot
+    pub fn from_json_string(s: &str) -> Result<Self, JsonParseError> {
+        let doc = json::parse(s).map_err(|err| JsonParseError::Syntax {
+            position: err.position,
+            message: err.message.to_string(),
+        })?;
+
+        let n_points = integer(field(&doc, "n_points")?, "n_points")?;
+        let bin_widths = Array1::from(numbers(&doc, "bin_widths")?);
+        let origin = Array1::from(numbers(&doc, "origin")?);
+        let bin_shape = Array1::from(integers(&doc, "bin_shape")?);
+        let coords = numbers(&doc, "points")?;
+        let next_member = integers(&doc, "next_member")?;
+        let found = integers(&doc, "found_indices")?;
+        let found_count = integer(field(&doc, "found_count")?, "found_count")?;
+
+        let invalid = |field: &'static str, message: &str| JsonParseError::InvalidField { field, message: message.to_string() };
+        if bin_widths.len() != 3 {
+            return Err(invalid("bin_widths", "expected 3 values"));
+        }
+        if origin.len() != 3 {
+            return Err(invalid("origin", "expected 3 values"));
+        }
+        if bin_shape.len() != 3 || !bin_shape.iter().all(|&n| n > 0) {
+            return Err(invalid("bin_shape", "expected 3 positive bin counts"));
+        }
+        if n_points == 0 || coords.len() != 3 * n_points as usize {
+            return Err(invalid("points", "expected 3 coordinates for each of n_points points"));
+        }
+        let n_points = n_points as usize;
+        if next_member.len() != n_points {
+            return Err(invalid("next_member", "expected one entry per point"));
+        }
+        if !next_member.iter().all(|&next| (-2..n_points as i64).contains(&next)) {
+            return Err(invalid("next_member", "entry out of range"));
+        }
+        if found.len() != found_count as usize {
+            return Err(invalid("found_indices", "length does not match found_count"));
+        }
+
+        let points = Array2::from_shape_vec((n_points, 3), coords).expect("length checked above");
+        BucketError::check(&points, &bin_widths).map_err(|err| invalid("bin_widths", &err.to_string()))?;
+        let (bin_indices, bin_shape) = Self::bins_on_stored_grid(&points, &bin_widths, &origin, &bin_shape)
+            .ok_or_else(|| invalid("points", "point outside the stored grid"))?;
+        let mut point_bin = Self::index_from_bins(points, bin_widths, origin, bin_indices, bin_shape, true);
+
+        // A swap can leave the stored order differing from the rebinned one
+        let stored_order = match doc.get("original_indices") {
+            Some(_) => integers(&doc, "original_indices")?,
+            None => point_bin.original_indices.to_vec(),
+        };
+        let mut seen = vec![false; n_points];
+        for &orig_idx in &stored_order {
+            match usize::try_from(orig_idx).ok().and_then(|i| seen.get_mut(i)) {
+                Some(seen) if !*seen => *seen = true,
+                _ => return Err(invalid("original_indices", "not a permutation of the points")),
+            }
+        }
+        if stored_order.len() != n_points {
+            return Err(invalid("original_indices", "expected one entry per point"));
+        }
+
+        let removed: Vec<usize> = next_member
+            .iter()
+            .zip(&stored_order)
+            .filter(|&(&next, _)| next == -2)
+            .map(|(_, &orig_idx)| orig_idx as usize)
+            .collect();
+        point_bin.remove_by_indices(&removed).expect("indices checked above");
+
+        if doc.get("bin_offset").is_some() {
+            let bin_offset = integers(&doc, "bin_offset")?;
+            if bin_offset.len() != 3 {
+                return Err(invalid("bin_offset", "expected 3 values"));
+            }
+            point_bin.shift_origin_by_bins([bin_offset[0], bin_offset[1], bin_offset[2]]);
+        }
+
+        let mut sorted_of_original = vec![0i64; n_points];
+        for (sorted_idx, &orig_idx) in point_bin.original_indices.iter().enumerate() {
+            sorted_of_original[orig_idx as usize] = sorted_idx as i64;
+        }
+        for orig_idx in found {
+            let sorted_idx = usize::try_from(orig_idx)
+                .ok()
+                .and_then(|i| sorted_of_original.get(i))
+                .ok_or_else(|| invalid("found_indices", "index out of range"))?;
+            point_bin.found_indices_buffer.push(*sorted_idx);
+        }
+
+        Ok(point_bin)
+    }
+}
+
+fn field<'a>(doc: &'a JsonValue, name: &'static str) -> Result<&'a JsonValue, JsonParseError> {
+    doc.get(name).ok_or(JsonParseError::MissingField(name))
+}
+
+fn numbers(doc: &JsonValue, name: &'static str) -> Result<Vec<f64>, JsonParseError> {
+    field(doc, name)?
+        .as_array()
+        .and_then(|items| {
+            items
+                .iter()
+                .map(|item| match item {
+                    JsonValue::String(s) if s == "NaN" => Some(f64::NAN),
+                    JsonValue::String(s) if s == "Infinity" => Some(f64::INFINITY),
+                    JsonValue::String(s) if s == "-Infinity" => Some(f64::NEG_INFINITY),
+                    _ => item.as_f64(),
+                })
+                .collect()
+        })
+        .ok_or(JsonParseError::InvalidField { field: name, message: "expected an array of numbers".to_string() })
+}
+
+fn integers(doc: &JsonValue, name: &'static str) -> Result<Vec<i64>, JsonParseError> {
+    numbers(doc, name)?
+        .into_iter()
+        .map(|value| integer(&JsonValue::Number(value), name))
+        .collect()
+}
+
+fn integer(value: &JsonValue, name: &'static str) -> Result<i64, JsonParseError> {
+    value
+        .as_f64()
+        .filter(|v| v.fract() == 0.0 && v.abs() < 2f64.powi(53))
+        .map(|v| v as i64)
+        .ok_or(JsonParseError::InvalidField { field: name, message: "expected an integer".to_string() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    fn sample() -> PointBin3D {
+        let points = array![
+            [0.1, 0.2, 0.3],
+            [1.5, -0.25, 2.0],
+            [0.4, 0.1, 0.2],
+            [3.0, 3.0, 3.0],
+            [0.3, 0.3, 0.3],
+        ];
+        PointBin3D::new(points, array![1.0, 0.5, 1.0])
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut point_bin = sample();
+        point_bin.radius_search(&array![0.25, 0.2, 0.25].view(), 0.2);
+        assert_eq!(point_bin.found_count(), 3);
+
+        let text = point_bin.to_json_string();
+        assert!(text.starts_with("{\"n_points\":5,\"bin_widths\":[1,0.5,1],"));
+
+        let mut restored = PointBin3D::from_json_string(&text).unwrap();
+        assert_eq!(restored.original_points(), point_bin.original_points());
+        assert_eq!(restored.found_indices(), point_bin.found_indices());
+        assert_eq!(restored.remaining_count(), point_bin.remaining_count());
+        assert_eq!(restored.to_count_grid(), point_bin.to_count_grid());
+        assert_eq!(restored.to_json_string(), text);
+
+        // Searches and reset behave as on the original
+        restored.radius_search(&array![0.0, 0.0, 0.0].view(), 1.0);
+        point_bin.radius_search(&array![0.0, 0.0, 0.0].view(), 1.0);
+        assert_eq!(restored.found_indices(), point_bin.found_indices());
+        restored.reset();
+        assert_eq!(restored.remaining_count(), 5);
+    }
+
+    #[test]
+    fn test_from_json_string_errors() {
+        let text = sample().to_json_string();
+
+        assert!(matches!(PointBin3D::from_json_string("{\"n_points\":"), Err(JsonParseError::Syntax { .. })));
+        assert_eq!(
+            PointBin3D::from_json_string(&text.replace("\"found_count\"", "\"count\"")).err(),
+            Some(JsonParseError::MissingField("found_count"))
+        );
+        assert!(matches!(
+            PointBin3D::from_json_string(&text.replace("\"n_points\":5", "\"n_points\":4")),
+            Err(JsonParseError::InvalidField { field: "points", .. })
+        ));
+        for widths in ["[1,0,1]", "[1,-0.5,1]"] {
+            let text = text.replace("\"bin_widths\":[1,0.5,1]", &format!("\"bin_widths\":{}", widths));
+            assert!(matches!(
+                PointBin3D::from_json_string(&text),
+                Err(JsonParseError::InvalidField { field: "bin_widths", .. })
+            ));
+        }
+        assert!(matches!(
+            PointBin3D::from_json_string(&text.replace("\"bin_shape\":[", "\"bin_shape\":[0,")),
+            Err(JsonParseError::InvalidField { field: "bin_shape", .. })
+        ));
+        assert!(matches!(
+            PointBin3D::from_json_string(&text.replace("\"original_indices\":[", "\"original_indices\":[0,")),
+            Err(JsonParseError::InvalidField { field: "original_indices", .. })
+        ));
+
+        // An oversized grid is cut back to the points rather than allocated, and a grid
+        // too small for them is rejected
+        let shape = "\"bin_shape\":[3,7,3]";
+        let huge = PointBin3D::from_json_string(&text.replace(shape, "\"bin_shape\":[1000000000,1000000,1000]")).unwrap();
+        assert_eq!(huge.grid_metadata().bin_shape, array![3, 7, 3]);
+        assert!(matches!(
+            PointBin3D::from_json_string(&text.replace(shape, "\"bin_shape\":[1,1,1]")),
+            Err(JsonParseError::InvalidField { field: "points", .. })
+        ));
+        assert!(matches!(PointBin3D::from_json_string(&"[".repeat(1_000_000)), Err(JsonParseError::Syntax { .. })));
+    }
+
+    #[test]
+    fn test_json_non_finite_values() {
+        let points = array![[0.0, 0.0, 0.0], [1.0, f64::NAN, 2.0]];
+        let point_bin = PointBin3D::new(points, array![1.0, f64::INFINITY, 1.0]);
+        let text = point_bin.to_json_string();
+        assert!(text.contains("\"bin_widths\":[1,\"Infinity\",1]"));
+        assert!(text.contains("\"points\":[0,0,0,1,\"NaN\",2]"));
+        let restored = PointBin3D::from_json_string(&text).unwrap();
+        assert_eq!(restored.to_json_string(), text);
+    }
+
+    #[test]
+    fn test_json_round_trip_after_swap() {
+        let mut point_bin = sample();
+        point_bin.shift_origin_by_bins([2, 0, -1]);
+        point_bin
+            .radius_search_and_swap(&array![3.0, 3.0, 3.0].view(), 0.1, array![[0.2, 2.5, 0.2]])
+            .unwrap();
+        point_bin.radius_search(&array![0.3, 0.3, 0.3].view(), 0.05);
+
+        let text = point_bin.to_json_string();
+        let mut restored = PointBin3D::from_json_string(&text).unwrap();
+        assert_eq!(restored.original_points(), point_bin.original_points());
+        assert_eq!(restored.found_indices(), point_bin.found_indices());
+        assert_eq!(restored.remaining_count(), point_bin.remaining_count());
+
+        // The grid is cut back to the bins the points still reach
+        let (metadata, restored_metadata) = (point_bin.grid_metadata(), restored.grid_metadata());
+        assert_eq!(restored_metadata.origin, metadata.origin);
+        assert_eq!(restored_metadata.bin_offset, metadata.bin_offset);
+        assert_eq!((metadata.bin_shape, restored_metadata.bin_shape), (array![3, 7, 3], array![2, 6, 2]));
+
+        // The moved point is found at its new location
+        restored.radius_search(&array![0.2, 2.5, 0.2].view(), 0.1);
+        assert_eq!(restored.found_indices_sorted_unique(), array![3, 4]);
+        restored.reset();
+        assert_eq!(restored.remaining_count(), 5);
+    }
+}
@@ -0,0 +1,561 @@
+//! Nearest-neighbour queries by expanding shells of bins around the query
+
+use std::ops::ControlFlow;
+
+use ndarray::{Array1, ArrayView1};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use super::PointBin3D;
+
+impl PointBin3D {
+    /// Exact k-nearest-neighbour search
+    ///
+    /// Visits bins in shells of increasing Chebyshev distance around the query's bin
+    /// until `k` candidates are held and the nearest unvisited bin is farther than the
+    /// k-th of them, so no closer point can remain. Queries outside the grid work too.
+    /// Points removed by earlier searches are ignored and nothing is removed.
+    ///
+    /// # Arguments
+    /// * `query_point` - 3D point to search around
+    /// * `k` - Number of neighbours to return
+    ///
+    /// # Returns
+    /// `(original index, squared distance)` pairs, nearest first; fewer than `k` only
+    /// if fewer than `k` points remain
+    ///
+    /// # Panics
+    /// Panics if query_point doesn't have exactly 3 elements
+    pub fn k_nearest(&self, query_point: &ArrayView1<f64>, k: usize) -> Vec<(i64, f64)> {
+        self.knn_approx(query_point, k, usize::MAX)
+    }
+
+    /// Nearest remaining point to a query
+    ///
+    /// `k_nearest` with `k = 1`. Points removed by earlier searches are ignored and
+    /// nothing is removed.
+    ///
+    /// # Arguments
+    /// * `query_point` - 3D point to search around
+    ///
+    /// # Returns
+    /// `(original index, squared distance)` of the nearest point, or `None` if no points
+    /// remain
+    ///
+    /// # Panics
+    /// Panics if query_point doesn't have exactly 3 elements
+    pub fn nearest(&self, query_point: &ArrayView1<f64>) -> Option<(i64, f64)> {
+        self.k_nearest(query_point, 1).into_iter().next()
+    }
+
+    /// Approximate k-nearest-neighbour search with a cap on the bins visited
+    ///
+    /// Visits bins in shells of increasing Chebyshev distance around the query's bin,
+    /// stopping once the k-th best candidate is provably closer than any unvisited bin
+    /// or after `max_bins` bins have been visited, whichever comes first. A small
+    /// `max_bins` bounds the cost of each query but may miss a closer point lying in
+    /// a bin that was never reached; once `max_bins` covers every shell the search
+    /// needs, the result is exact. Points removed by earlier searches are ignored.
+    ///
+    /// # Arguments
+    /// * `query_point` - 3D point to search around
+    /// * `k` - Number of neighbours to return
+    /// * `max_bins` - Maximum number of bins to visit
+    ///
+    /// # Returns
+    /// Up to `k` `(original index, squared distance)` pairs, nearest first
+    ///
+    /// # Panics
+    /// Panics if query_point doesn't have exactly 3 elements
+    pub fn knn_approx(&self, query_point: &ArrayView1<f64>, k: usize, max_bins: usize) -> Vec<(i64, f64)> {
+        assert_eq!(query_point.len(), 3, "Query point must have 3 dimensions");
+
+        self.knn_shells(query_point, k, max_bins)
+            .into_iter()
+            .map(|(i, dist_sq)| (self.original_indices[i], dist_sq))
+            .collect()
+    }
+
+    /// Nearest neighbour search with a cap on the bins visited
+    ///
+    /// Runs the same shell-expanding search as `knn_approx` with `k = 1`, giving a
+    /// bounded worst-case cost per query. If the budget runs out first, the closest
+    /// point seen so far is returned even though a closer one may lie in a bin that was
+    /// never reached. With an ample budget the result is the exact nearest neighbour.
+    /// Points removed by earlier searches are ignored.
+    ///
+    /// # Arguments
+    /// * `query_point` - 3D point to search around
+    /// * `max_bins_visited` - Maximum number of bins to visit
+    ///
+    /// # Returns
+    /// `(original index, squared distance)` of the closest point found, or `None` if no
+    /// point was found within the budget
+    ///
+    /// # Panics
+    /// Panics if query_point doesn't have exactly 3 elements
+    pub fn nearest_budgeted(&self, query_point: &ArrayView1<f64>, max_bins_visited: usize) -> Option<(i64, f64)> {
+        self.knn_approx(query_point, 1, max_bins_visited).into_iter().next()
+    }
+
+    /// Distance from a query to the nearest remaining point
+    ///
+    /// Runs radius queries starting at the largest bin width and doubling the radius
+    /// until one finds a point; the closest point within that radius is the nearest
+    /// overall. Points removed by earlier searches are ignored and nothing is removed.
+    ///
+    /// # Arguments
+    /// * `query_point` - 3D point to measure from
+    ///
+    /// # Returns
+    /// The Euclidean distance, or `None` if no points remain
+    ///
+    /// # Panics
+    /// Panics if query_point doesn't have exactly 3 elements
+    pub fn distance_to_nearest(&self, query_point: &ArrayView1<f64>) -> Option<f64> {
+        assert_eq!(query_point.len(), 3, "Query point must have 3 dimensions");
+        if self.remaining_count() == 0 {
+            return None;
+        }
+
+        let mut radius = self.bin_widths.iter().cloned().fold(0.0, f64::max);
+        // Stops once the radius overflows, which only a non-finite query can cause
+        while radius.is_finite() {
+            let mut best_sq = f64::INFINITY;
+            let _ = self.visit_within(query_point, radius, |_, dist_sq| {
+                best_sq = best_sq.min(dist_sq);
+                ControlFlow::Continue(())
+            });
+            if best_sq.is_finite() {
+                return Some(best_sq.sqrt());
+            }
+            radius *= 2.0;
+        }
+        None
+    }
+
+    /// Radius search with the radius chosen per query from the local point density
+    ///
+    /// Counts the remaining points within `density_estimate_radius` of the query and
+    /// scales that radius by `cbrt(target_k / count)`, the radius that would hold about
+    /// `target_k` points at the same density. While fewer than `target_k` points lie
+    /// within the radius it is doubled, stopping early once every remaining point is
+    /// inside. The points within the final radius are then removed and recorded like
+    /// `radius_search`. Only the final search removes anything.
+    ///
+    /// # Arguments
+    /// * `query_point` - 3D point to search around
+    /// * `target_k` - Number of points the search should aim to find
+    /// * `density_estimate_radius` - Radius of the sphere used to estimate the density
+    ///
+    /// # Returns
+    /// Original indices of the points removed by this search; at least `target_k` unless
+    /// fewer points remain, and possibly more in the outer shell of the final radius
+    ///
+    /// # Panics
+    /// Panics if query_point doesn't have exactly 3 elements or density_estimate_radius
+    /// isn't positive
+    pub fn adaptive_knn(&mut self, query_point: &ArrayView1<f64>, target_k: usize, density_estimate_radius: f64) -> Array1<i64> {
+        assert_eq!(query_point.len(), 3, "Query point must have 3 dimensions");
+        assert!(density_estimate_radius > 0.0, "Density estimate radius must be positive");
+        if target_k == 0 {
+            return Array1::zeros(0);
+        }
+
+        let count_within = |radius: f64| {
+            let mut count = 0;
+            let _ = self.visit_within(query_point, radius, |_, _| {
+                count += 1;
+                ControlFlow::Continue(())
+            });
+            count
+        };
+
+        let density_count = count_within(density_estimate_radius);
+        let mut radius = if density_count == 0 {
+            2.0 * density_estimate_radius
+        } else {
+            density_estimate_radius * (target_k as f64 / density_count as f64).cbrt()
+        };
+
+        let remaining = self.remaining_count();
+        let mut count = count_within(radius);
+        // A non-finite radius already covers every point a search could reach
+        while count < target_k && count < remaining && radius.is_finite() {
+            radius *= 2.0;
+            count = count_within(radius);
+        }
+
+        let start = self.found_indices_buffer.len();
+        self.radius_search(query_point, radius);
+        self.found_indices_buffer[start..]
+            .iter()
+            .map(|&sorted_idx| self.original_indices[sorted_idx as usize])
+            .collect()
+    }
+
+    /// Smallest radius within which every remaining point has at least `k` neighbours
+    ///
+    /// Computes the distance from each remaining point to its k-th nearest other
+    /// remaining point and returns the largest of these, i.e. the radius that guarantees
+    /// k-connectivity. With the `rayon` feature the points are processed in parallel.
+    ///
+    /// # Arguments
+    /// * `k` - Number of neighbours each point must have
+    ///
+    /// # Returns
+    /// The radius, or infinity if fewer than `k + 1` points remain
+    pub fn min_radius_for_knn(&self, k: usize) -> f64 {
+        let live: Vec<usize> = (0..self.points.nrows())
+            .filter(|&i| !self.is_removed(i))
+            .collect();
+
+        // The point itself is its own nearest neighbour, so look one further
+        let kth_distance_sq = |&i: &usize| {
+            let neighbours = self.knn_shells(&self.points.row(i), k + 1, usize::MAX);
+            neighbours.get(k).map_or(f64::INFINITY, |&(_, dist_sq)| dist_sq)
+        };
+
+        #[cfg(feature = "rayon")]
+        let max_dist_sq = live.par_iter().map(kth_distance_sq).reduce(|| 0.0, f64::max);
+        #[cfg(not(feature = "rayon"))]
+        let max_dist_sq = live.iter().map(kth_distance_sq).fold(0.0, f64::max);
+
+        max_dist_sq.sqrt()
+    }
+
+    /// Median distance from each remaining point to its nearest other remaining point
+    ///
+    /// A standard scale estimate for the cloud, e.g. as a kernel density bandwidth.
+    /// With the `rayon` feature the points are processed in parallel.
+    ///
+    /// # Returns
+    /// The median nearest-neighbour distance, or NaN if fewer than two points remain
+    pub fn median_nn_distance(&self) -> f64 {
+        let live: Vec<usize> = (0..self.points.nrows())
+            .filter(|&i| !self.is_removed(i))
+            .collect();
+        if live.len() < 2 {
+            return f64::NAN;
+        }
+
+        // The point itself is its own nearest neighbour, so take the second
+        let nn_distance = |&i: &usize| self.knn_shells(&self.points.row(i), 2, usize::MAX)[1].1.sqrt();
+
+        #[cfg(feature = "rayon")]
+        let mut distances: Vec<f64> = live.par_iter().map(nn_distance).collect();
+        #[cfg(not(feature = "rayon"))]
+        let mut distances: Vec<f64> = live.iter().map(nn_distance).collect();
+
+        distances.sort_unstable_by(f64::total_cmp);
+        let n = distances.len();
+        if n % 2 == 1 {
+            distances[n / 2]
+        } else {
+            (distances[n / 2 - 1] + distances[n / 2]) / 2.0
+        }
+    }
+
+    /// Shell-expanding k-nearest search returning `(sorted index, squared distance)` pairs
+    pub(crate) fn knn_shells(&self, query_point: &ArrayView1<f64>, k: usize, max_bins: usize) -> Vec<(usize, f64)> {
+        let mut best: Vec<(usize, f64)> = Vec::with_capacity(k + 1);
+        if k == 0 {
+            return best;
+        }
+
+        let home = self.bin_of(query_point);
+        let max_shell = (0..3)
+            .map(|j| home[j].max(self.bin_shape[j] - 1 - home[j]))
+            .max()
+            .unwrap_or(0);
+
+        let mut bins_visited = 0;
+        for shell in 0..=max_shell {
+            for ix in (home[0] - shell).max(0)..=(home[0] + shell).min(self.bin_shape[0] - 1) {
+                for iy in (home[1] - shell).max(0)..=(home[1] + shell).min(self.bin_shape[1] - 1) {
+                    let on_face = (ix - home[0]).abs() == shell || (iy - home[1]).abs() == shell;
+                    for iz in (home[2] - shell).max(0)..=(home[2] + shell).min(self.bin_shape[2] - 1) {
+                        if !on_face && (iz - home[2]).abs() != shell {
+                            continue;
+                        }
+                        if bins_visited == max_bins {
+                            return best;
+                        }
+                        bins_visited += 1;
+
+                        let mut i = self.first_member[[ix as usize, iy as usize, iz as usize]];
+                        while i != -1 {
+                            let dist_sq = self.dist_sq_to(i as usize, query_point);
+                            if best.len() < k || dist_sq < best[best.len() - 1].1 {
+                                let pos = best.partition_point(|&(_, d)| d <= dist_sq);
+                                best.insert(pos, (i as usize, dist_sq));
+                                best.truncate(k);
+                            }
+                            i = self.next_member[i as usize];
+                        }
+                    }
+                }
+            }
+
+            if best.len() == k {
+                let bound = self.shell_clearance(query_point, home, shell);
+                if best[k - 1].1 <= bound * bound {
+                    break;
+                }
+            }
+        }
+
+        best
+    }
+
+    /// Lower bound on the distance from the query to any bin outside shell `shell`
+    fn shell_clearance(&self, query_point: &ArrayView1<f64>, home: [i64; 3], shell: i64) -> f64 {
+        let mut clearance = f64::INFINITY;
+        for j in 0..3 {
+            let lower = self.grid_origin[j] + (home[j] - shell) as f64 * self.bin_widths[j];
+            let upper = self.grid_origin[j] + (home[j] + shell + 1) as f64 * self.bin_widths[j];
+            clearance = clearance.min(query_point[j] - lower).min(upper - query_point[j]);
+        }
+        clearance.max(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pointbin::scattered_points;
+    use ndarray::{array, Array2};
+
+    fn brute_force_knn(points: &Array2<f64>, query: &ArrayView1<f64>, k: usize) -> Vec<i64> {
+        let mut by_dist: Vec<(i64, f64)> = points
+            .rows()
+            .into_iter()
+            .enumerate()
+            .map(|(i, p)| (i as i64, (&p - query).mapv(|d| d * d).sum()))
+            .collect();
+        by_dist.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+        by_dist.into_iter().take(k).map(|(i, _)| i).collect()
+    }
+
+    #[test]
+    fn test_knn_approx_recall() {
+        // Four tight clusters in a 20x20x20 box, with a little background noise
+        let centers = [[3.0, 3.0, 3.0], [15.0, 4.0, 10.0], [8.0, 16.0, 5.0], [12.0, 12.0, 16.0]];
+        let mut state: u64 = 12345;
+        let mut next = || {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (state >> 11) as f64 / (1u64 << 53) as f64
+        };
+        let mut points = Array2::<f64>::zeros((800, 3));
+        for i in 0..800 {
+            for j in 0..3 {
+                points[[i, j]] = if i % 10 == 0 {
+                    next() * 20.0
+                } else {
+                    centers[i % 4][j] + (next() - 0.5) * 3.0
+                };
+            }
+        }
+        let point_bin = PointBin3D::new(points.clone(), array![1.0, 1.0, 1.0]);
+
+        let queries = [array![5.0, 5.0, 5.0], array![10.0, 10.0, 10.0], array![14.0, 6.0, 9.0], array![1.0, 19.0, 1.0]];
+        let k = 10;
+        let recall = |max_bins: usize| {
+            let mut hits = 0;
+            for query in &queries {
+                let exact = brute_force_knn(&points, &query.view(), k);
+                let approx = point_bin.knn_approx(&query.view(), k, max_bins);
+                hits += approx.iter().filter(|(i, _)| exact.contains(i)).count();
+            }
+            hits as f64 / (k * queries.len()) as f64
+        };
+
+        let recalls: Vec<f64> = [1, 27, 125, usize::MAX].iter().map(|&m| recall(m)).collect();
+        assert!(recalls.windows(2).all(|w| w[0] <= w[1]));
+        assert!(recalls[0] < 1.0);
+        assert_eq!(recalls[3], 1.0);
+
+        // Exact results come back nearest first with squared distances
+        let result = point_bin.knn_approx(&queries[1].view(), k, usize::MAX);
+        assert_eq!(result.len(), k);
+        assert!(result.windows(2).all(|w| w[0].1 <= w[1].1));
+        let first = points.row(result[0].0 as usize);
+        assert_eq!(result[0].1, (&first - &queries[1]).mapv(|d| d * d).sum());
+    }
+
+    #[test]
+    fn test_k_nearest_matches_brute_force() {
+        let points = scattered_points(400, [10.0, 6.0, 8.0]);
+        let mut point_bin = PointBin3D::new(points.clone(), array![1.0, 1.0, 1.0]);
+        point_bin.radius_search(&array![5.0, 3.0, 4.0].view(), 1.5);
+        let found = point_bin.found_indices();
+        assert!(!found.is_empty());
+
+        // Brute force over the points that remain
+        let mut remaining = points.clone();
+        for &i in &found {
+            remaining.row_mut(i as usize).fill(f64::INFINITY);
+        }
+
+        for query in [array![5.0, 3.0, 4.0], array![0.2, 5.9, 0.1], array![-4.0, 3.0, 12.0]] {
+            let result = point_bin.k_nearest(&query.view(), 15);
+            assert_eq!(result.len(), 15);
+            assert!(result.windows(2).all(|w| w[0].1 <= w[1].1));
+
+            let expected = brute_force_knn(&remaining, &query.view(), 15);
+            let kth_sq = (&remaining.row(expected[14] as usize) - &query).mapv(|d| d * d).sum();
+            assert!((result[14].1 - kth_sq).abs() < 1e-12);
+            for &(i, dist_sq) in &result {
+                assert!(!found.iter().any(|&f| f == i));
+                assert_eq!(dist_sq, (&points.row(i as usize) - &query).mapv(|d| d * d).sum());
+            }
+        }
+
+        // Fewer than k results only when fewer than k points remain
+        let all = point_bin.k_nearest(&array![0.0, 0.0, 0.0].view(), 1000);
+        assert_eq!(all.len(), point_bin.remaining_count());
+        assert!(point_bin.k_nearest(&array![0.0, 0.0, 0.0].view(), 0).is_empty());
+    }
+
+    #[test]
+    fn test_adaptive_knn_varying_density() {
+        // A dense cluster near the origin and a sparse one far away
+        let mut state: u64 = 99;
+        let mut next = || {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (state >> 11) as f64 / (1u64 << 53) as f64
+        };
+        let mut points = Array2::<f64>::zeros((600, 3));
+        for i in 0..600 {
+            let (center, spread) = if i < 500 { (2.0, 2.0) } else { (30.0, 20.0) };
+            for j in 0..3 {
+                points[[i, j]] = center + (next() - 0.5) * spread;
+            }
+        }
+        let mut point_bin = PointBin3D::new(points.clone(), array![2.0, 2.0, 2.0]);
+
+        let (dense_query, sparse_query) = (array![2.0, 2.0, 2.0], array![30.0, 30.0, 30.0]);
+        let dense = point_bin.adaptive_knn(&dense_query.view(), 20, 0.8);
+        let sparse = point_bin.adaptive_knn(&sparse_query.view(), 20, 0.8);
+        assert!(dense.len() >= 20 && dense.iter().all(|&i| i < 500));
+        assert!(sparse.len() >= 20 && sparse.iter().all(|&i| i >= 500));
+        assert!(dense.len() < 200 && sparse.len() < 200);
+        assert_eq!(point_bin.found_count(), dense.len() + sparse.len());
+
+        // The sparse region needed a much larger radius, and each result is a ball:
+        // everything closer than the farthest match was taken
+        let dist_sq = |i: usize, query: &Array1<f64>| (&points.row(i) - query).mapv(|d| d * d).sum();
+        let farthest = |found: &Array1<i64>, query| found.iter().map(|&i| dist_sq(i as usize, query)).fold(0.0, f64::max);
+        let (dense_sq, sparse_sq) = (farthest(&dense, &dense_query), farthest(&sparse, &sparse_query));
+        assert!(sparse_sq > 16.0 * dense_sq);
+        assert_eq!((0..600).filter(|&i| dist_sq(i, &dense_query) <= dense_sq).count(), dense.len());
+        let query = dense_query;
+
+        // Asking for more points than remain takes them all
+        let rest = point_bin.adaptive_knn(&query.view(), 1000, 0.8);
+        assert_eq!(rest.len(), 600 - dense.len() - sparse.len());
+        assert_eq!(point_bin.remaining_count(), 0);
+        assert!(point_bin.adaptive_knn(&query.view(), 5, 0.8).is_empty());
+    }
+
+    #[test]
+    fn test_min_radius_for_knn_lattice() {
+        // 5x5x5 lattice with unit spacing
+        let mut points = Array2::<f64>::zeros((125, 3));
+        for i in 0..125 {
+            points[[i, 0]] = (i % 5) as f64;
+            points[[i, 1]] = ((i / 5) % 5) as f64;
+            points[[i, 2]] = (i / 25) as f64;
+        }
+        let point_bin = PointBin3D::new(points, array![1.5, 1.5, 1.5]);
+
+        // Corner points have three neighbours at distance 1, then three at sqrt(2)
+        assert_eq!(point_bin.min_radius_for_knn(0), 0.0);
+        assert!((point_bin.min_radius_for_knn(3) - 1.0).abs() < 1e-12);
+        assert!((point_bin.min_radius_for_knn(6) - 2.0_f64.sqrt()).abs() < 1e-12);
+        assert_eq!(point_bin.min_radius_for_knn(125), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_nearest_budgeted() {
+        // The query's own bin holds a far point; the true nearest is just across the boundary
+        let points = array![
+            [0.0, 0.0, 0.0],
+            [1.05, 0.95, 0.95],
+            [5.0, 5.0, 5.0],
+        ];
+        let point_bin = PointBin3D::new(points, array![1.0, 1.0, 1.0]);
+        let query = array![0.95, 0.95, 0.95];
+
+        let (idx, dist_sq) = point_bin.nearest_budgeted(&query.view(), 1).unwrap();
+        assert_eq!(idx, 0);
+        assert!((dist_sq - 3.0 * 0.95 * 0.95).abs() < 1e-12);
+
+        let (idx, dist_sq) = point_bin.nearest_budgeted(&query.view(), usize::MAX).unwrap();
+        assert_eq!(idx, 1);
+        assert!((dist_sq - 0.01).abs() < 1e-12);
+
+        // Nothing within a zero budget
+        assert!(point_bin.nearest_budgeted(&query.view(), 0).is_none());
+    }
+
+    #[test]
+    fn test_nearest_skips_removed() {
+        let points = array![
+            [0.0, 0.0, 0.0],
+            [1.05, 0.95, 0.95],
+            [3.0, 0.5, 0.5],
+            [5.0, 5.0, 5.0],
+        ];
+        let mut point_bin = PointBin3D::new(points, array![1.0, 1.0, 1.0]);
+        let query = array![0.95, 0.95, 0.95];
+
+        let (idx, dist_sq) = point_bin.nearest(&query.view()).unwrap();
+        assert_eq!(idx, 1);
+        assert!((dist_sq - 0.01).abs() < 1e-12);
+
+        point_bin.radius_search(&array![1.0, 1.0, 1.0].view(), 2.0);
+        let (idx, dist_sq) = point_bin.nearest(&query.view()).unwrap();
+        assert_eq!(idx, 2);
+        assert!((dist_sq - (2.05 * 2.05 + 2.0 * 0.45 * 0.45)).abs() < 1e-12);
+
+        point_bin.radius_search(&array![4.0, 4.0, 4.0].view(), 10.0);
+        assert!(point_bin.nearest(&query.view()).is_none());
+    }
+
+    #[test]
+    fn test_distance_to_nearest() {
+        let points = array![
+            [0.0, 0.0, 0.0],
+            [1.05, 0.95, 0.95],
+            [5.0, 5.0, 5.0],
+        ];
+        let mut point_bin = PointBin3D::new(points, array![1.0, 1.0, 1.0]);
+
+        let distance = point_bin.distance_to_nearest(&array![0.95, 0.95, 0.95].view()).unwrap();
+        assert!((distance - 0.1).abs() < 1e-12);
+
+        // Far outside the grid the radius has to double several times
+        let distance = point_bin.distance_to_nearest(&array![45.0, 5.0, 5.0].view()).unwrap();
+        assert!((distance - 40.0).abs() < 1e-12);
+
+        point_bin.radius_search(&array![0.0, 0.0, 0.0].view(), 100.0);
+        assert!(point_bin.distance_to_nearest(&array![0.0, 0.0, 0.0].view()).is_none());
+    }
+
+    #[test]
+    fn test_median_nn_distance_lattice() {
+        // 4x4x4 lattice with spacing 0.75
+        let mut points = Array2::<f64>::zeros((64, 3));
+        for i in 0..64 {
+            points[[i, 0]] = (i % 4) as f64 * 0.75;
+            points[[i, 1]] = ((i / 4) % 4) as f64 * 0.75;
+            points[[i, 2]] = (i / 16) as f64 * 0.75;
+        }
+        let point_bin = PointBin3D::new(points, array![1.0, 1.0, 1.0]);
+        assert!((point_bin.median_nn_distance() - 0.75).abs() < 1e-12);
+
+        let single = PointBin3D::new(array![[1.0, 2.0, 3.0]], array![1.0, 1.0, 1.0]);
+        assert!(single.median_nn_distance().is_nan());
+    }
+}
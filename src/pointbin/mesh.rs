@@ -0,0 +1,250 @@
+//! Triangle meshes built from the radius-neighbour graph
+
+use std::collections::{HashMap, VecDeque};
+
+use ndarray::{Array2, ArrayView1};
+
+use super::{PointBin3D, ProximityGraph};
+use crate::utils::squared_distance;
+
+/// One directed edge of a triangle in a `HalfEdgeMesh`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HalfEdge {
+    /// Vertex the half-edge starts at
+    pub origin: usize,
+    /// Oppositely directed half-edge of the neighbouring face, `None` on the boundary
+    pub twin: Option<usize>,
+    /// Next half-edge around the same face
+    pub next: usize,
+    /// Face the half-edge belongs to
+    pub face: usize,
+}
+
+/// Half-edge triangle mesh whose vertices are the points of a PointBin3D
+///
+/// Face `f` owns half-edges `3f`, `3f + 1` and `3f + 2`, in winding order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HalfEdgeMesh {
+    /// Vertex coordinates (n_vertices, 3); vertex `i` is original index `i`
+    pub vertices: Array2<f64>,
+    /// All half-edges, three per face
+    pub half_edges: Vec<HalfEdge>,
+    /// One outgoing half-edge per vertex, `None` for vertices in no face
+    pub vertex_half_edge: Vec<Option<usize>>,
+}
+
+impl HalfEdgeMesh {
+    /// Number of triangles
+    pub fn n_faces(&self) -> usize {
+        self.half_edges.len() / 3
+    }
+
+    /// Vertex indices of every triangle, in winding order
+    pub fn triangles(&self) -> Vec<[usize; 3]> {
+        self.half_edges
+            .chunks(3)
+            .map(|face| [face[0].origin, face[1].origin, face[2].origin])
+            .collect()
+    }
+}
+
+impl PointBin3D {
+    /// Triangulate a radius-neighbour graph into a half-edge mesh
+    ///
+    /// Candidate triangles are the 3-cliques of the graph, skipping degenerate
+    /// (collinear) ones. They are accepted shortest perimeter first as long as none of
+    /// their edges already borders two triangles, so every edge is shared by at most two
+    /// faces. Faces are then oriented consistently across shared edges where the surface
+    /// allows it, and half-edges on an edge used by a single face, or by two faces that
+    /// could not be oriented consistently, have no twin.
+    ///
+    /// This is a local heuristic, not a surface reconstruction: the result depends on
+    /// the radius used for the graph and need not be a closed or vertex-manifold surface.
+    ///
+    /// # Arguments
+    /// * `graph` - Graph from `proximity_graph`
+    /// * `points` - 2D array of shape (n_points, 3) with the vertex coordinates, usually
+    ///   `original_points()`
+    ///
+    /// # Returns
+    /// The mesh, with one vertex per row of `points`
+    ///
+    /// # Panics
+    /// Panics if points don't have 3 columns or one row per graph vertex
+    pub fn build_half_edge_mesh(graph: &ProximityGraph, points: &Array2<f64>) -> HalfEdgeMesh {
+        assert_eq!(points.ncols(), 3, "Points must have 3 dimensions");
+        assert_eq!(points.nrows(), graph.adj.len(), "Points must have one row per graph vertex");
+
+        let triangles = orient_triangles(select_triangles(graph, points));
+
+        let mut half_edges = Vec::with_capacity(3 * triangles.len());
+        let mut vertex_half_edge = vec![None; points.nrows()];
+        let mut by_direction = HashMap::new();
+        for (face, triangle) in triangles.iter().enumerate() {
+            for corner in 0..3 {
+                let h = 3 * face + corner;
+                let (origin, dest) = (triangle[corner], triangle[(corner + 1) % 3]);
+                half_edges.push(HalfEdge { origin, twin: None, next: 3 * face + (corner + 1) % 3, face });
+                vertex_half_edge[origin].get_or_insert(h);
+                by_direction.insert((origin, dest), h);
+            }
+        }
+        for h in 0..half_edges.len() {
+            let dest = half_edges[half_edges[h].next].origin;
+            half_edges[h].twin = by_direction.get(&(dest, half_edges[h].origin)).copied();
+        }
+
+        HalfEdgeMesh { vertices: points.clone(), half_edges, vertex_half_edge }
+    }
+}
+
+/// Pick non-degenerate 3-cliques, shortest first, with at most two per edge
+fn select_triangles(graph: &ProximityGraph, points: &Array2<f64>) -> Vec<[usize; 3]> {
+    let mut candidates = Vec::new();
+    for (i, neighbours) in graph.adj.iter().enumerate() {
+        for &j in neighbours.iter().filter(|&&j| j > i) {
+            for &k in graph.adj[j].iter().filter(|&&k| k > j) {
+                if neighbours.binary_search(&k).is_err() {
+                    continue;
+                }
+                let (a, b, c) = (points.row(i), points.row(j), points.row(k));
+                let perimeter = [(&a, &b), (&b, &c), (&c, &a)]
+                    .iter()
+                    .map(|(p, q)| squared_distance(p, q).sqrt())
+                    .sum::<f64>();
+                if twice_area(&a, &b, &c) > 1e-12 * perimeter * perimeter {
+                    candidates.push((perimeter, [i, j, k]));
+                }
+            }
+        }
+    }
+    candidates.sort_by(|x, y| x.0.total_cmp(&y.0));
+
+    let mut edge_faces: HashMap<(usize, usize), u8> = HashMap::new();
+    let mut triangles = Vec::new();
+    for (_, triangle) in candidates {
+        let edges = triangle_edges(triangle);
+        if edges.iter().all(|edge| edge_faces.get(edge).copied().unwrap_or(0) < 2) {
+            for edge in edges {
+                *edge_faces.entry(edge).or_insert(0) += 1;
+            }
+            triangles.push(triangle);
+        }
+    }
+    triangles
+}
+
+/// Flip triangles so neighbours traverse each shared edge in opposite directions
+fn orient_triangles(mut triangles: Vec<[usize; 3]>) -> Vec<[usize; 3]> {
+    let mut edge_faces: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+    for (face, &triangle) in triangles.iter().enumerate() {
+        for edge in triangle_edges(triangle) {
+            edge_faces.entry(edge).or_default().push(face);
+        }
+    }
+
+    let mut visited = vec![false; triangles.len()];
+    let mut queue = VecDeque::new();
+    for start in 0..triangles.len() {
+        if visited[start] {
+            continue;
+        }
+        visited[start] = true;
+        queue.push_back(start);
+
+        while let Some(face) = queue.pop_front() {
+            for corner in 0..3 {
+                let (a, b) = (triangles[face][corner], triangles[face][(corner + 1) % 3]);
+                for &other in &edge_faces[&(a.min(b), a.max(b))] {
+                    if visited[other] {
+                        continue;
+                    }
+                    // The neighbour must run b -> a; if it also runs a -> b, reverse it
+                    let t = triangles[other];
+                    if (0..3).any(|c| t[c] == a && t[(c + 1) % 3] == b) {
+                        triangles[other].swap(1, 2);
+                    }
+                    visited[other] = true;
+                    queue.push_back(other);
+                }
+            }
+        }
+    }
+    triangles
+}
+
+/// Undirected edges of a triangle as `(low, high)` vertex pairs
+fn triangle_edges([i, j, k]: [usize; 3]) -> [(usize, usize); 3] {
+    [(i.min(j), i.max(j)), (j.min(k), j.max(k)), (i.min(k), i.max(k))]
+}
+
+/// Twice the area of a triangle, the norm of the cross product of two edges
+fn twice_area(a: &ArrayView1<f64>, b: &ArrayView1<f64>, c: &ArrayView1<f64>) -> f64 {
+    let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+    let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+    let cross = [u[1] * v[2] - u[2] * v[1], u[2] * v[0] - u[0] * v[2], u[0] * v[1] - u[1] * v[0]];
+    cross.iter().map(|x| x * x).sum::<f64>().sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    #[test]
+    fn test_build_half_edge_mesh_tetrahedron() {
+        // A tetrahedron plus an isolated point
+        let points = array![
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, 1.0],
+            [10.0, 10.0, 10.0],
+        ];
+        let point_bin = PointBin3D::new(points.clone(), array![1.0, 1.0, 1.0]);
+        let graph = point_bin.proximity_graph(1.5);
+        let mesh = PointBin3D::build_half_edge_mesh(&graph, point_bin.original_points());
+
+        assert_eq!(mesh.n_faces(), 4);
+        assert_eq!(mesh.vertices, points);
+        assert_eq!(mesh.vertex_half_edge[4], None);
+
+        // Closed and consistently oriented: every half-edge has a twin running the other way
+        for (h, half_edge) in mesh.half_edges.iter().enumerate() {
+            let twin = half_edge.twin.expect("closed surface has no boundary");
+            assert_eq!(mesh.half_edges[twin].twin, Some(h));
+            assert_eq!(mesh.half_edges[twin].origin, mesh.half_edges[half_edge.next].origin);
+            assert_ne!(mesh.half_edges[twin].face, half_edge.face);
+            assert_eq!(mesh.half_edges[mesh.half_edges[half_edge.next].next].next, h);
+        }
+        for (v, h) in mesh.vertex_half_edge.iter().take(4).enumerate() {
+            assert_eq!(mesh.half_edges[h.unwrap()].origin, v);
+        }
+    }
+
+    #[test]
+    fn test_build_half_edge_mesh_skips_collinear() {
+        // Points 0, 1 and 2 are collinear, so that 3-clique gives no triangle
+        let points = array![
+            [0.0, 0.0, 0.0],
+            [0.5, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.5, 0.5, 0.0],
+        ];
+        let point_bin = PointBin3D::new(points, array![1.0, 1.0, 1.0]);
+        let graph = point_bin.proximity_graph(1.1);
+        let mesh = PointBin3D::build_half_edge_mesh(&graph, point_bin.original_points());
+
+        let mut triangles: Vec<[usize; 3]> = mesh
+            .triangles()
+            .into_iter()
+            .map(|mut t| {
+                t.sort();
+                t
+            })
+            .collect();
+        triangles.sort();
+        assert_eq!(triangles, vec![[0, 1, 3], [0, 2, 3], [1, 2, 3]]);
+        assert_eq!(mesh.half_edges.iter().filter(|h| h.twin.is_none()).count(), 3);
+    }
+}
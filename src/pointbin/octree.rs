@@ -0,0 +1,158 @@
+//! Octree decomposition of the points in a PointBin3D
+
+use super::PointBin3D;
+
+/// A node of an octree decomposition
+///
+/// Leaves hold the original indices of their points. Internal nodes split their
+/// bounding box in half along each axis; child `c` covers the upper half of x if
+/// `c & 4`, of y if `c & 2`, and of z if `c & 1`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OctreeNode {
+    /// A node that was not subdivided further
+    Leaf(Vec<i64>),
+    /// A node subdivided into eight octants
+    Internal([Box<OctreeNode>; 8]),
+}
+
+impl OctreeNode {
+    /// Total number of points stored in the leaves below this node
+    pub fn n_points(&self) -> usize {
+        match self {
+            OctreeNode::Leaf(indices) => indices.len(),
+            OctreeNode::Internal(children) => children.iter().map(|child| child.n_points()).sum(),
+        }
+    }
+}
+
+impl PointBin3D {
+    /// Recursively decompose the remaining points into an octree
+    ///
+    /// Starting from the bounding box of the points still in the structure, each node
+    /// is halved along every axis until `max_depth` is reached or it holds fewer than
+    /// `min_pts_per_node` points.
+    ///
+    /// # Arguments
+    /// * `max_depth` - Maximum depth of the tree (the root has depth 0)
+    /// * `min_pts_per_node` - Nodes with fewer points than this become leaves
+    ///
+    /// # Returns
+    /// The root node of the decomposition
+    pub fn octree_decompose(&self, max_depth: usize, min_pts_per_node: usize) -> OctreeNode {
+        let live: Vec<usize> = (0..self.points.nrows())
+            .filter(|&i| !self.is_removed(i))
+            .collect();
+
+        let mut min_corner = [f64::INFINITY; 3];
+        let mut max_corner = [f64::NEG_INFINITY; 3];
+        for &i in &live {
+            for j in 0..3 {
+                min_corner[j] = min_corner[j].min(self.points[[i, j]]);
+                max_corner[j] = max_corner[j].max(self.points[[i, j]]);
+            }
+        }
+
+        self.build_octree_node(live, min_corner, max_corner, 0, max_depth, min_pts_per_node)
+    }
+
+    fn build_octree_node(
+        &self,
+        members: Vec<usize>,
+        min_corner: [f64; 3],
+        max_corner: [f64; 3],
+        depth: usize,
+        max_depth: usize,
+        min_pts_per_node: usize,
+    ) -> OctreeNode {
+        if depth >= max_depth || members.len() < min_pts_per_node {
+            let mut indices: Vec<i64> = members.iter().map(|&i| self.original_indices[i]).collect();
+            indices.sort_unstable();
+            return OctreeNode::Leaf(indices);
+        }
+
+        let mut center = [0.0; 3];
+        for j in 0..3 {
+            center[j] = 0.5 * (min_corner[j] + max_corner[j]);
+        }
+
+        let mut octants: [Vec<usize>; 8] = Default::default();
+        for i in members {
+            let mut child = 0;
+            if self.points[[i, 0]] > center[0] {
+                child |= 4;
+            }
+            if self.points[[i, 1]] > center[1] {
+                child |= 2;
+            }
+            if self.points[[i, 2]] > center[2] {
+                child |= 1;
+            }
+            octants[child].push(i);
+        }
+
+        let children = std::array::from_fn(|child| {
+            let mut child_min = min_corner;
+            let mut child_max = max_corner;
+            for (j, bit) in [4, 2, 1].into_iter().enumerate() {
+                if child & bit != 0 {
+                    child_min[j] = center[j];
+                } else {
+                    child_max[j] = center[j];
+                }
+            }
+            Box::new(self.build_octree_node(
+                std::mem::take(&mut octants[child]),
+                child_min,
+                child_max,
+                depth + 1,
+                max_depth,
+                min_pts_per_node,
+            ))
+        });
+
+        OctreeNode::Internal(children)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    #[test]
+    fn test_octree_decompose() {
+        let points = array![
+            [0.0, 0.0, 0.0],
+            [0.1, 0.1, 0.1],
+            [4.0, 4.0, 4.0],
+            [3.9, 4.0, 3.9],
+            [4.0, 0.0, 0.0],
+        ];
+        let bin_widths = array![1.0, 1.0, 1.0];
+        let point_bin = PointBin3D::new(points, bin_widths);
+
+        let root = point_bin.octree_decompose(3, 2);
+        assert_eq!(root.n_points(), 5);
+
+        let OctreeNode::Internal(children) = root else {
+            panic!("Root should be subdivided");
+        };
+        assert_eq!(children[0].n_points(), 2);
+        assert_eq!(*children[4], OctreeNode::Leaf(vec![4]));
+        assert_eq!(children[7].n_points(), 2);
+        assert_eq!(*children[1], OctreeNode::Leaf(vec![]));
+    }
+
+    #[test]
+    fn test_octree_respects_max_depth() {
+        let points = array![
+            [0.0, 0.0, 0.0],
+            [1.0, 1.0, 1.0],
+            [2.0, 2.0, 2.0],
+        ];
+        let bin_widths = array![1.0, 1.0, 1.0];
+        let point_bin = PointBin3D::new(points, bin_widths);
+
+        assert_eq!(point_bin.octree_decompose(0, 1), OctreeNode::Leaf(vec![0, 1, 2]));
+    }
+}
@@ -0,0 +1,394 @@
+//! All-pairs radius queries for building neighbourhood graphs
+
+use std::ops::ControlFlow;
+
+use ndarray::{Array1, ArrayView1};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use super::PointBin3D;
+use crate::utils::{ravel_bin_index, squared_distance};
+
+/// Radius-neighbour graph of the points in a PointBin3D
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProximityGraph {
+    /// Neighbours of each original index, ascending; empty for removed points
+    pub adj: Vec<Vec<usize>>,
+}
+
+impl PointBin3D {
+    /// Find every pair of remaining points within `radius` of each other
+    ///
+    /// Each pair is reported once as `(i, j)` original indices with `i < j`. Every point
+    /// only scans bins at or after its own in bin-sorted order, so no pair is tested twice.
+    ///
+    /// # Arguments
+    /// * `radius` - Maximum distance between paired points
+    ///
+    /// # Returns
+    /// The pairs, grouped by the bin-sorted position of their first-visited point
+    pub fn radius_pairs(&self, radius: f64) -> Vec<(i64, i64)> {
+        let mut pairs = Vec::new();
+        for a in 0..self.points.nrows() {
+            self.forward_pairs(a, radius, &mut pairs);
+        }
+        pairs
+    }
+
+    /// Parallel version of `radius_pairs`
+    ///
+    /// Points are processed in parallel into per-thread pair buffers that are then
+    /// concatenated, so the set of pairs equals that of `radius_pairs` but the order is
+    /// unspecified. Sort the result if a deterministic order is needed.
+    ///
+    /// # Arguments
+    /// * `radius` - Maximum distance between paired points
+    ///
+    /// # Returns
+    /// The pairs as `(i, j)` original indices with `i < j`, in no particular order
+    #[cfg(feature = "rayon")]
+    pub fn par_radius_pairs(&self, radius: f64) -> Vec<(i64, i64)> {
+        (0..self.points.nrows())
+            .into_par_iter()
+            .fold(Vec::new, |mut pairs, a| {
+                self.forward_pairs(a, radius, &mut pairs);
+                pairs
+            })
+            .reduce(Vec::new, |mut left, mut right| {
+                left.append(&mut right);
+                left
+            })
+    }
+
+    /// Build the radius-neighbour graph as adjacency lists
+    ///
+    /// Two remaining points are adjacent when they lie within `radius` of each other.
+    ///
+    /// # Arguments
+    /// * `radius` - Linking distance
+    ///
+    /// # Returns
+    /// A graph with one adjacency list per original index
+    pub fn proximity_graph(&self, radius: f64) -> ProximityGraph {
+        let mut adj = vec![Vec::new(); self.points.nrows()];
+        let mut pairs = Vec::new();
+        for a in 0..self.points.nrows() {
+            pairs.clear();
+            self.forward_pairs(a, radius, &mut pairs);
+            for &(i, j) in &pairs {
+                adj[i as usize].push(j as usize);
+                adj[j as usize].push(i as usize);
+            }
+        }
+        for neighbours in &mut adj {
+            neighbours.sort_unstable();
+        }
+        ProximityGraph { adj }
+    }
+
+    /// Label the connected components of the radius-neighbour graph
+    ///
+    /// Two remaining points are connected when they lie within `radius` of each other,
+    /// so this is single-linkage clustering at a fixed cutoff. The pairs come from the
+    /// same forward scan as `radius_pairs` but are merged into a union-find one point at
+    /// a time, so the full pair list is never held in memory.
+    ///
+    /// # Arguments
+    /// * `radius` - Linking distance
+    ///
+    /// # Returns
+    /// 1D array of shape (n_points,) with a component label per original index. Labels
+    /// are 0-based and numbered in order of each component's lowest original index;
+    /// removed points are labelled -1.
+    pub fn connected_components(&self, radius: f64) -> Array1<i64> {
+        let n_points = self.points.nrows();
+        let mut parent: Vec<usize> = (0..n_points).collect();
+        let mut size = vec![1usize; n_points];
+
+        fn find(parent: &mut [usize], mut i: usize) -> usize {
+            while parent[i] != i {
+                parent[i] = parent[parent[i]];
+                i = parent[i];
+            }
+            i
+        }
+
+        let mut pairs = Vec::new();
+        for a in 0..n_points {
+            pairs.clear();
+            self.forward_pairs(a, radius, &mut pairs);
+            for &(i, j) in &pairs {
+                let (root_i, root_j) = (find(&mut parent, i as usize), find(&mut parent, j as usize));
+                if root_i != root_j {
+                    let (big, small) = if size[root_i] >= size[root_j] { (root_i, root_j) } else { (root_j, root_i) };
+                    parent[small] = big;
+                    size[big] += size[small];
+                }
+            }
+        }
+
+        // Parents are original indices, so removed points are looked up the same way
+        let mut removed = vec![false; n_points];
+        for sorted_idx in 0..n_points {
+            removed[self.original_indices[sorted_idx] as usize] = self.is_removed(sorted_idx);
+        }
+
+        let mut root_labels = vec![-1i64; n_points];
+        let mut n_components = 0;
+        let mut labels = Array1::from_elem(n_points, -1i64);
+        for orig_idx in (0..n_points).filter(|&i| !removed[i]) {
+            let root = find(&mut parent, orig_idx);
+            if root_labels[root] == -1 {
+                root_labels[root] = n_components;
+                n_components += 1;
+            }
+            labels[orig_idx] = root_labels[root];
+        }
+        labels
+    }
+
+    /// Histogram of the distances between pairs of remaining points, up to `r_max`
+    ///
+    /// Splits `[0, r_max]` into `n_bins` equal-width distance bins and counts each pair
+    /// of distinct remaining points once in the bin of its distance, the raw input for a
+    /// radial distribution function `g(r)`. Every point runs a non-destructive search
+    /// within `r_max`, so each pair is seen from both ends and the counts are halved.
+    ///
+    /// # Arguments
+    /// * `r_max` - Largest pair distance counted
+    /// * `n_bins` - Number of distance bins
+    ///
+    /// # Returns
+    /// 1D array of shape (n_bins,) where entry `b` counts pairs with distance in
+    /// `[b, b + 1) * r_max / n_bins` (the last bin also includes `r_max`)
+    ///
+    /// # Panics
+    /// Panics if r_max is not positive or n_bins is 0
+    pub fn count_pairs(&self, r_max: f64, n_bins: usize) -> Array1<u64> {
+        assert!(r_max > 0.0, "r_max must be positive");
+        assert!(n_bins > 0, "n_bins must be positive");
+
+        let bin_width = r_max / n_bins as f64;
+        let mut counts = Array1::<u64>::zeros(n_bins);
+        for a in (0..self.points.nrows()).filter(|&a| !self.is_removed(a)) {
+            let _ = self.visit_within(&self.points.row(a), r_max, |b, dist_sq| {
+                if b != a {
+                    let bin = ((dist_sq.sqrt() / bin_width) as usize).min(n_bins - 1);
+                    counts[bin] += 1;
+                }
+                ControlFlow::Continue(())
+            });
+        }
+
+        counts.mapv_inplace(|count| count / 2);
+        counts
+    }
+
+    /// Check whether two points are within `radius` of each other
+    ///
+    /// A plain distance test between the points at two original indices, for callers
+    /// that already know which pair they want. No spatial query is run, and removed
+    /// points are tested like any other.
+    ///
+    /// # Arguments
+    /// * `idx_a` - Original index of the first point
+    /// * `idx_b` - Original index of the second point
+    /// * `radius` - Maximum distance between the points
+    ///
+    /// # Panics
+    /// Panics if either index is out of range
+    pub fn are_within(&self, idx_a: i64, idx_b: i64, radius: f64) -> bool {
+        let n_points = self.original_points.nrows();
+        for idx in [idx_a, idx_b] {
+            assert!(
+                (0..n_points as i64).contains(&idx),
+                "Point index {} out of range for {} points",
+                idx,
+                n_points
+            );
+        }
+
+        let a = self.original_points.row(idx_a as usize);
+        let b = self.original_points.row(idx_b as usize);
+        squared_distance(&a, &b) <= radius * radius
+    }
+
+    /// Append the pairs between sorted point `a` and later points in forward bins
+    fn forward_pairs(&self, a: usize, radius: f64, pairs: &mut Vec<(i64, i64)>) {
+        if self.is_removed(a) {
+            return;
+        }
+
+        let point = self.points.row(a);
+        let (min_bin, max_bin) = self.bin_range(&point, radius);
+        let own_bin = self.bin_key(self.bin_of(&point));
+        let radius_sq = radius * radius;
+
+        for ix in min_bin[0]..=max_bin[0] {
+            for iy in min_bin[1]..=max_bin[1] {
+                for iz in min_bin[2]..=max_bin[2] {
+                    // Bins before our own only hold points that already scanned forward to us
+                    let bin_key = self.bin_key([ix, iy, iz]);
+                    if bin_key < own_bin {
+                        continue;
+                    }
+                    let mut b = self.first_member[[ix as usize, iy as usize, iz as usize]];
+                    while b != -1 {
+                        let b_idx = b as usize;
+                        // Compare bins rather than sorted indices across bins: points moved
+                        // by `radius_search_and_swap` no longer follow bin-sorted order
+                        let forward = bin_key > own_bin || b_idx > a;
+                        if forward && self.dist_sq_to(b_idx, &point) <= radius_sq {
+                            let (i, j) = (self.original_indices[a], self.original_indices[b_idx]);
+                            pairs.push((i.min(j), i.max(j)));
+                        }
+                        b = self.next_member[b_idx];
+                    }
+                }
+            }
+        }
+    }
+
+    /// Bin coordinates of a point, clamped to the grid
+    pub(crate) fn bin_of(&self, point: &ArrayView1<f64>) -> [i64; 3] {
+        std::array::from_fn(|j| {
+            let bin = ((point[j] - self.grid_origin[j]) / self.bin_widths[j]).floor() as i64;
+            bin.clamp(0, self.bin_shape[j] - 1)
+        })
+    }
+
+    /// Row-major linear index of a bin, matching the order points are sorted in
+    fn bin_key(&self, bin: [i64; 3]) -> i128 {
+        ravel_bin_index(bin, &self.bin_shape)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pointbin::scattered_points;
+    use ndarray::{array, Array2};
+
+    #[test]
+    fn test_radius_pairs_brute_force() {
+        let points = scattered_points(300, [10.0; 3]);
+        let point_bin = PointBin3D::new(points.clone(), array![1.0, 1.0, 1.0]);
+
+        let mut pairs = point_bin.radius_pairs(1.5);
+        pairs.sort_unstable();
+
+        let mut expected = Vec::new();
+        for i in 0..points.nrows() {
+            for j in (i + 1)..points.nrows() {
+                let dist_sq = (&points.row(i) - &points.row(j)).mapv(|d| d * d).sum();
+                if dist_sq <= 1.5 * 1.5 {
+                    expected.push((i as i64, j as i64));
+                }
+            }
+        }
+        assert!(!expected.is_empty());
+        assert_eq!(pairs, expected);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_radius_pairs_matches_serial() {
+        let point_bin = PointBin3D::new(scattered_points(300, [10.0; 3]), array![1.0, 1.0, 1.0]);
+
+        let mut serial = point_bin.radius_pairs(1.5);
+        let mut parallel = point_bin.par_radius_pairs(1.5);
+        serial.sort_unstable();
+        parallel.sort_unstable();
+        assert_eq!(parallel, serial);
+    }
+
+    #[test]
+    fn test_count_pairs_brute_force() {
+        let points = scattered_points(300, [10.0; 3]);
+        let point_bin = PointBin3D::new(points.clone(), array![1.0, 1.0, 1.0]);
+        let (r_max, n_bins) = (2.0, 8);
+
+        let mut expected = Array1::<u64>::zeros(n_bins);
+        for i in 0..points.nrows() {
+            for j in (i + 1)..points.nrows() {
+                let d = (&points.row(i) - &points.row(j)).mapv(|x| x * x).sum().sqrt();
+                if d <= r_max {
+                    expected[((d / (r_max / n_bins as f64)) as usize).min(n_bins - 1)] += 1;
+                }
+            }
+        }
+
+        let counts = point_bin.count_pairs(r_max, n_bins);
+        assert_eq!(counts, expected);
+        assert_eq!(counts.sum() as usize, point_bin.radius_pairs(r_max).len());
+    }
+
+    #[test]
+    fn test_connected_components_two_blobs() {
+        let mut points = Array2::<f64>::zeros((40, 3));
+        for i in 0..40 {
+            // Even indices form a blob near the origin, odd ones a blob near (20, 20, 20)
+            let offset = if i % 2 == 0 { 0.0 } else { 20.0 };
+            let fi = (i / 2) as f64;
+            points[[i, 0]] = offset + (fi * 0.37) % 2.0;
+            points[[i, 1]] = offset + (fi * 0.53) % 2.0;
+            points[[i, 2]] = offset + (fi * 0.71) % 2.0;
+        }
+        let mut point_bin = PointBin3D::new(points, array![1.0, 1.0, 1.0]);
+
+        let labels = point_bin.connected_components(4.0);
+        for (i, &label) in labels.iter().enumerate() {
+            assert_eq!(label, (i % 2) as i64);
+        }
+
+        // Removed points get no label
+        point_bin.radius_search(&array![20.0, 20.0, 20.0].view(), 0.0);
+        let labels = point_bin.connected_components(4.0);
+        assert_eq!(labels[1], -1);
+        assert_eq!(labels.iter().filter(|&&l| l == 1).count(), 19);
+    }
+
+    #[test]
+    fn test_proximity_graph_matches_pairs() {
+        let point_bin = PointBin3D::new(scattered_points(300, [10.0; 3]), array![1.0, 1.0, 1.0]);
+        let graph = point_bin.proximity_graph(1.5);
+
+        let mut edges = Vec::new();
+        for (i, neighbours) in graph.adj.iter().enumerate() {
+            assert!(neighbours.windows(2).all(|w| w[0] < w[1]));
+            edges.extend(neighbours.iter().filter(|&&j| j > i).map(|&j| (i as i64, j as i64)));
+        }
+        let mut pairs = point_bin.radius_pairs(1.5);
+        pairs.sort();
+        assert_eq!(edges, pairs);
+    }
+
+    #[test]
+    fn test_are_within() {
+        let points = array![
+            [0.0, 0.0, 0.0],
+            [0.3, 0.4, 0.0],
+            [5.0, 1.0, 2.0],
+        ];
+        let bin_widths = array![1.0, 1.0, 1.0];
+        let point_bin = PointBin3D::new(points.clone(), bin_widths);
+
+        let distance = |a: usize, b: usize| (&points.row(a) - &points.row(b)).mapv(|d| d * d).sum().sqrt();
+        assert!((distance(0, 1) - 0.5).abs() < 1e-12);
+
+        for (a, b) in [(0, 1), (1, 2), (0, 2)] {
+            for radius in [0.49, 0.5, 5.0, 6.0] {
+                assert_eq!(point_bin.are_within(a as i64, b as i64, radius), distance(a, b) <= radius);
+            }
+        }
+        assert!(point_bin.are_within(0, 1, 0.5));
+        assert!(!point_bin.are_within(0, 2, 5.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn test_are_within_rejects_bad_index() {
+        let point_bin = PointBin3D::new(array![[0.0, 0.0, 0.0]], array![1.0, 1.0, 1.0]);
+        point_bin.are_within(0, 1, 1.0);
+    }
+}
@@ -0,0 +1,473 @@
+//! Reading and writing the Point Cloud Library's PCD format
+
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use ndarray::{Array1, Array2};
+
+use super::{BucketError, PointBin3D};
+
+/// Error returned when a PCD file cannot be loaded
+#[derive(Debug)]
+pub enum PcdError {
+    /// Reading the file failed
+    Io(io::Error),
+    /// The header is missing a required entry or has an invalid one
+    MalformedHeader(String),
+    /// One of the x, y, z fields is not a FLOAT field
+    UnsupportedFieldType { field: String, field_type: String },
+    /// The header does not declare all of the x, y and z fields
+    MissingXyz,
+    /// The `DATA` section uses a layout this reader does not handle
+    UnsupportedData(String),
+    /// A line of an ASCII data section could not be parsed
+    InvalidAsciiData { line: usize, message: String },
+    /// A binary data section is not exactly `POINTS` records long
+    BinarySizeMismatch { expected: usize, actual: usize },
+    /// The file declares no points
+    Empty,
+    /// The bin widths were rejected by `PointBin3D::try_new`
+    InvalidInput(BucketError),
+}
+
+impl fmt::Display for PcdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PcdError::Io(err) => write!(f, "I/O error: {}", err),
+            PcdError::MalformedHeader(msg) => write!(f, "Malformed PCD header: {}", msg),
+            PcdError::UnsupportedFieldType { field, field_type } => write!(
+                f,
+                "Field '{}' has unsupported type '{}' (expected F)",
+                field, field_type
+            ),
+            PcdError::MissingXyz => write!(f, "PCD file does not have x, y and z fields"),
+            PcdError::UnsupportedData(kind) => write!(f, "Unsupported PCD data section '{}'", kind),
+            PcdError::InvalidAsciiData { line, message } => write!(f, "Line {}: {}", line, message),
+            PcdError::BinarySizeMismatch { expected, actual } => write!(
+                f,
+                "Binary data has {} bytes, expected {}",
+                actual, expected
+            ),
+            PcdError::Empty => write!(f, "PCD file contains no points"),
+            PcdError::InvalidInput(err) => write!(f, "Invalid input: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for PcdError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PcdError::Io(err) => Some(err),
+            PcdError::InvalidInput(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for PcdError {
+    fn from(err: io::Error) -> Self {
+        PcdError::Io(err)
+    }
+}
+
+impl From<BucketError> for PcdError {
+    fn from(err: BucketError) -> Self {
+        PcdError::InvalidInput(err)
+    }
+}
+
+/// Layout of the records described by a PCD header
+struct PcdHeader {
+    /// Byte size of each element of each field
+    sizes: Vec<usize>,
+    /// Number of elements of each field
+    counts: Vec<usize>,
+    /// Index of the x, y and z fields
+    xyz: [usize; 3],
+    /// Byte size of a whole binary record
+    record_size: usize,
+    n_points: usize,
+    binary: bool,
+}
+
+impl PcdHeader {
+    /// Parse the header lines, returning the header and the byte offset of the data
+    fn parse(bytes: &[u8]) -> Result<(Self, usize), PcdError> {
+        let malformed = |msg: &str| PcdError::MalformedHeader(msg.to_string());
+
+        let mut entries: Vec<(String, Vec<String>)> = Vec::new();
+        let mut pos = 0;
+        let data_kind = loop {
+            let line_end = bytes[pos..]
+                .iter()
+                .position(|&b| b == b'\n')
+                .map(|i| pos + i + 1)
+                .ok_or_else(|| malformed("missing DATA line"))?;
+            let line = std::str::from_utf8(&bytes[pos..line_end]).map_err(|_| malformed("header is not text"))?;
+            pos = line_end;
+
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut words = line.split_whitespace().map(str::to_string);
+            let key = words.next().unwrap_or_default();
+            let values: Vec<String> = words.collect();
+            if key == "DATA" {
+                break values.first().cloned().ok_or_else(|| malformed("DATA has no value"))?;
+            }
+            entries.push((key, values));
+        };
+
+        let entry = |key: &str| entries.iter().find(|(k, _)| k == key).map(|(_, v)| v);
+        let number = |key: &str| -> Result<Option<usize>, PcdError> {
+            entry(key)
+                .map(|v| v.first().and_then(|n| n.parse().ok()).ok_or_else(|| malformed(&format!("invalid {}", key))))
+                .transpose()
+        };
+        let numbers = |values: &Vec<String>, key: &str| -> Result<Vec<usize>, PcdError> {
+            values.iter().map(|n| n.parse().map_err(|_| malformed(&format!("invalid {}", key)))).collect()
+        };
+
+        let fields = entry("FIELDS").ok_or_else(|| malformed("missing FIELDS"))?;
+        let sizes = numbers(entry("SIZE").ok_or_else(|| malformed("missing SIZE"))?, "SIZE")?;
+        let types = entry("TYPE").ok_or_else(|| malformed("missing TYPE"))?;
+        let counts = match entry("COUNT") {
+            Some(values) => numbers(values, "COUNT")?,
+            None => vec![1; fields.len()],
+        };
+        if sizes.len() != fields.len() || types.len() != fields.len() || counts.len() != fields.len() {
+            return Err(malformed("FIELDS, SIZE, TYPE and COUNT have different lengths"));
+        }
+
+        let mut xyz = [0; 3];
+        for (slot, name) in xyz.iter_mut().zip(["x", "y", "z"]) {
+            *slot = fields.iter().position(|f| f == name).ok_or(PcdError::MissingXyz)?;
+            let field_type = &types[*slot];
+            if field_type != "F" {
+                return Err(PcdError::UnsupportedFieldType { field: name.to_string(), field_type: field_type.clone() });
+            }
+            if sizes[*slot] != 4 && sizes[*slot] != 8 {
+                return Err(malformed(&format!("field '{}' has size {}, expected 4 or 8", name, sizes[*slot])));
+            }
+            if counts[*slot] != 1 {
+                return Err(malformed(&format!("field '{}' has count {}, expected 1", name, counts[*slot])));
+            }
+        }
+
+        // Bounds every record offset and ASCII column computed from the header
+        let record_size = sizes
+            .iter()
+            .zip(&counts)
+            .try_fold(0usize, |total, (&size, &count)| size.checked_mul(count)?.checked_add(total))
+            .ok_or_else(|| malformed("record size overflows"))?;
+        counts
+            .iter()
+            .try_fold(0usize, |total, &count| total.checked_add(count))
+            .ok_or_else(|| malformed("total COUNT overflows"))?;
+
+        let n_points = match number("POINTS")? {
+            Some(n) => n,
+            None => {
+                let width = number("WIDTH")?.ok_or_else(|| malformed("missing WIDTH"))?;
+                width
+                    .checked_mul(number("HEIGHT")?.unwrap_or(1))
+                    .ok_or_else(|| malformed("WIDTH * HEIGHT overflows"))?
+            }
+        };
+
+        let binary = match data_kind.as_str() {
+            "ascii" => false,
+            "binary" => true,
+            other => return Err(PcdError::UnsupportedData(other.to_string())),
+        };
+
+        Ok((PcdHeader { sizes, counts, xyz, record_size, n_points, binary }, pos))
+    }
+
+    /// Byte offset of the first element of each field within a binary record
+    fn byte_offsets(&self) -> Vec<usize> {
+        let mut offsets = Vec::with_capacity(self.sizes.len());
+        let mut offset = 0;
+        for (size, count) in self.sizes.iter().zip(&self.counts) {
+            offsets.push(offset);
+            offset += size * count;
+        }
+        offsets
+    }
+
+    /// Column of the first element of each field within an ASCII line
+    fn columns(&self) -> Vec<usize> {
+        let mut columns = Vec::with_capacity(self.counts.len());
+        let mut column = 0;
+        for count in &self.counts {
+            columns.push(column);
+            column += count;
+        }
+        columns
+    }
+}
+
+impl PointBin3D {
+    /// Load a PointBin3D from a PCD file
+    ///
+    /// Reads `DATA ascii` and `DATA binary` files (v0.6 and v0.7) whose x, y and z
+    /// fields are FLOAT (`TYPE F`, 4 or 8 bytes). Any other fields are skipped. Points
+    /// are kept in file order, so row `i` of `original_points()` is the file's point `i`.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the PCD file
+    /// * `bin_widths` - 1D array of shape (3,) with bin widths for x, y, z
+    ///
+    /// # Returns
+    /// A new PointBin3D, or a `PcdError` describing why the file could not be read
+    pub fn from_pcd<P: AsRef<Path>>(path: P, bin_widths: Array1<f64>) -> Result<Self, PcdError> {
+        let bytes = fs::read(path)?;
+        let (header, data_start) = PcdHeader::parse(&bytes)?;
+        if header.n_points == 0 {
+            return Err(PcdError::Empty);
+        }
+        let data = &bytes[data_start..];
+
+        // The data length is checked against POINTS before the points are allocated
+        let mut points;
+        if header.binary {
+            let offsets = header.byte_offsets();
+            let record_size = header.record_size;
+            let expected = header
+                .n_points
+                .checked_mul(record_size)
+                .ok_or_else(|| PcdError::MalformedHeader("POINTS * record size overflows".to_string()))?;
+            if data.len() != expected {
+                return Err(PcdError::BinarySizeMismatch { expected, actual: data.len() });
+            }
+
+            points = Array2::<f64>::zeros((header.n_points, 3));
+            for (i, record) in data.chunks_exact(record_size).enumerate() {
+                for (j, &field) in header.xyz.iter().enumerate() {
+                    let value = &record[offsets[field]..offsets[field] + header.sizes[field]];
+                    points[[i, j]] = match header.sizes[field] {
+                        4 => f32::from_le_bytes(value.try_into().unwrap()) as f64,
+                        _ => f64::from_le_bytes(value.try_into().unwrap()),
+                    };
+                }
+            }
+        } else {
+            let columns = header.columns();
+            let text = String::from_utf8_lossy(data);
+            let header_lines = bytes[..data_start].iter().filter(|&&b| b == b'\n').count();
+            let n_lines = text.lines().filter(|line| !line.trim().is_empty()).count();
+            if n_lines < header.n_points {
+                return Err(PcdError::InvalidAsciiData {
+                    line: header_lines + text.lines().count(),
+                    message: format!("expected {} points, found {}", header.n_points, n_lines),
+                });
+            }
+
+            points = Array2::<f64>::zeros((header.n_points, 3));
+
+            let mut n_read = 0;
+            for (line_idx, line) in text.lines().enumerate() {
+                let line = line.trim();
+                if line.is_empty() {
+                    continue;
+                }
+                let invalid = |message: String| PcdError::InvalidAsciiData { line: header_lines + line_idx + 1, message };
+                if n_read == header.n_points {
+                    return Err(invalid(format!("more than the {} declared points", header.n_points)));
+                }
+
+                let values: Vec<&str> = line.split_whitespace().collect();
+                for (j, &field) in header.xyz.iter().enumerate() {
+                    let value = values
+                        .get(columns[field])
+                        .ok_or_else(|| invalid(format!("expected at least {} values, found {}", columns[field] + 1, values.len())))?;
+                    points[[n_read, j]] = value
+                        .parse::<f64>()
+                        .map_err(|_| invalid(format!("invalid coordinate '{}'", value)))?;
+                }
+                n_read += 1;
+            }
+        }
+
+        Ok(Self::try_new(points, bin_widths)?)
+    }
+
+    /// Write the remaining points as a PCD (v0.7) file with `x y z` fields
+    ///
+    /// Points are written in their original order as 32-bit floats, the layout of PCL's
+    /// `PointXYZ`, in an unorganized cloud (`HEIGHT 1`). Points removed by searches are
+    /// left out; call `reset()` first to export the whole cloud.
+    ///
+    /// # Arguments
+    /// * `writer` - Destination for the file contents
+    /// * `binary` - Write `DATA binary` (little-endian) instead of `DATA ascii`
+    pub fn write_pcd<W: Write>(&self, writer: &mut W, binary: bool) -> io::Result<()> {
+        let mut live: Vec<usize> = (0..self.points.nrows())
+            .filter(|&i| !self.is_removed(i))
+            .collect();
+        live.sort_unstable_by_key(|&i| self.original_indices[i]);
+
+        writeln!(writer, "# .PCD v0.7 - Point Cloud Data file format")?;
+        writeln!(writer, "VERSION 0.7")?;
+        writeln!(writer, "FIELDS x y z")?;
+        writeln!(writer, "SIZE 4 4 4")?;
+        writeln!(writer, "TYPE F F F")?;
+        writeln!(writer, "COUNT 1 1 1")?;
+        writeln!(writer, "WIDTH {}", live.len())?;
+        writeln!(writer, "HEIGHT 1")?;
+        writeln!(writer, "VIEWPOINT 0 0 0 1 0 0 0")?;
+        writeln!(writer, "POINTS {}", live.len())?;
+        writeln!(writer, "DATA {}", if binary { "binary" } else { "ascii" })?;
+
+        for i in live {
+            let p = self.points.row(i);
+            let (x, y, z) = (p[0] as f32, p[1] as f32, p[2] as f32);
+            if binary {
+                writer.write_all(&x.to_le_bytes())?;
+                writer.write_all(&y.to_le_bytes())?;
+                writer.write_all(&z.to_le_bytes())?;
+            } else {
+                writeln!(writer, "{} {} {}", x, y, z)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    #[test]
+    fn test_write_pcd_ascii() {
+        let points = array![
+            [0.0, 1.5, -2.0],
+            [5.0, 5.0, 5.0],
+            [0.25, 0.0, 0.0],
+        ];
+        let mut point_bin = PointBin3D::new(points, array![1.0, 1.0, 1.0]);
+        point_bin.radius_search(&array![5.0, 5.0, 5.0].view(), 0.1);
+
+        let mut out = Vec::new();
+        point_bin.write_pcd(&mut out, false).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+
+        assert_eq!(lines[2], "FIELDS x y z");
+        assert_eq!(lines[6], "WIDTH 2");
+        assert_eq!(lines[9], "POINTS 2");
+        assert_eq!(lines[10], "DATA ascii");
+        assert_eq!(&lines[11..], ["0 1.5 -2", "0.25 0 0"]);
+    }
+
+    #[test]
+    fn test_write_pcd_binary() {
+        let points = array![[1.0, 2.0, 3.0], [-4.0, 0.5, 6.0]];
+        let point_bin = PointBin3D::new(points, array![1.0, 1.0, 1.0]);
+
+        let mut out = Vec::new();
+        point_bin.write_pcd(&mut out, true).unwrap();
+
+        let header_end = out.windows(12).position(|w| w == b"DATA binary\n").unwrap() + 12;
+        let data = &out[header_end..];
+        assert_eq!(data.len(), 2 * 3 * 4);
+
+        let values: Vec<f32> = data
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        assert_eq!(values, vec![1.0, 2.0, 3.0, -4.0, 0.5, 6.0]);
+    }
+
+    #[test]
+    fn test_from_pcd_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let points = array![[1.0, 2.0, 3.0], [-4.0, 0.5, 6.0], [0.25, -1.5, 2.0]];
+        let point_bin = PointBin3D::new(points.clone(), array![1.0, 1.0, 1.0]);
+
+        for binary in [false, true] {
+            let path = dir.path().join(format!("cloud_{}.pcd", binary));
+            let mut file = std::fs::File::create(&path).unwrap();
+            point_bin.write_pcd(&mut file, binary).unwrap();
+            drop(file);
+
+            let loaded = PointBin3D::from_pcd(&path, array![1.0, 1.0, 1.0]).unwrap();
+            assert_eq!(loaded.original_points(), &points);
+        }
+    }
+
+    #[test]
+    fn test_from_pcd_extra_fields_and_doubles() {
+        let dir = tempfile::tempdir().unwrap();
+
+        // ASCII with a packed rgb field before xyz and a v0.6 header without POINTS
+        let path = dir.path().join("rgb.pcd");
+        std::fs::write(
+            &path,
+            "VERSION .6\nFIELDS rgb x y z\nSIZE 4 4 4 4\nTYPE U F F F\nCOUNT 1 1 1 1\n\
+             WIDTH 2\nHEIGHT 1\nDATA ascii\n4278190080 1 2 3\n255 4 5 6\n",
+        )
+        .unwrap();
+        let loaded = PointBin3D::from_pcd(&path, array![1.0, 1.0, 1.0]).unwrap();
+        assert_eq!(loaded.original_points(), &array![[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+
+        // Binary with 8-byte coordinates and a trailing intensity field
+        let path = dir.path().join("double.pcd");
+        let mut bytes = b"FIELDS x y z intensity\nSIZE 8 8 8 2\nTYPE F F F U\nCOUNT 1 1 1 1\nWIDTH 1\nHEIGHT 1\nPOINTS 1\nDATA binary\n".to_vec();
+        for value in [0.1_f64, 0.2, 0.3] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes.extend_from_slice(&7u16.to_le_bytes());
+        std::fs::write(&path, bytes).unwrap();
+        let loaded = PointBin3D::from_pcd(&path, array![1.0, 1.0, 1.0]).unwrap();
+        assert_eq!(loaded.original_points(), &array![[0.1, 0.2, 0.3]]);
+    }
+
+    #[test]
+    fn test_from_pcd_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let load = |name: &str, contents: &[u8]| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, contents).unwrap();
+            PointBin3D::from_pcd(&path, array![1.0, 1.0, 1.0]).err().unwrap()
+        };
+
+        let err = load("int.pcd", b"FIELDS x y z\nSIZE 4 4 4\nTYPE I F F\nPOINTS 1\nDATA ascii\n1 2 3\n");
+        assert!(matches!(err, PcdError::UnsupportedFieldType { ref field, ref field_type } if field == "x" && field_type == "I"));
+
+        let err = load("noz.pcd", b"FIELDS x y\nSIZE 4 4\nTYPE F F\nPOINTS 1\nDATA ascii\n1 2\n");
+        assert!(matches!(err, PcdError::MissingXyz));
+
+        let err = load("header.pcd", b"FIELDS x y z\nSIZE 4 4\nTYPE F F F\nPOINTS 1\nDATA ascii\n1 2 3\n");
+        assert!(matches!(err, PcdError::MalformedHeader(_)));
+
+        let err = load("nodata.pcd", b"FIELDS x y z\nSIZE 4 4 4\nTYPE F F F\nPOINTS 1\n");
+        assert!(matches!(err, PcdError::MalformedHeader(_)));
+
+        let err = load("short.pcd", b"FIELDS x y z\nSIZE 4 4 4\nTYPE F F F\nPOINTS 2\nDATA binary\n\0\0\0\0\0\0\0\0\0\0\0\0");
+        assert!(matches!(err, PcdError::BinarySizeMismatch { expected: 24, actual: 12 }));
+
+        let err = load("compressed.pcd", b"FIELDS x y z\nSIZE 4 4 4\nTYPE F F F\nPOINTS 1\nDATA binary_compressed\n");
+        assert!(matches!(err, PcdError::UnsupportedData(_)));
+
+        let err = load("count.pcd", b"FIELDS x y z\nSIZE 4 4 4\nTYPE F F F\nCOUNT 1 1 0\nPOINTS 1\nDATA binary\n\0\0\0\0\0\0\0\0");
+        assert!(matches!(err, PcdError::MalformedHeader(_)));
+
+        let err = load("huge.pcd", b"FIELDS x y z\nSIZE 4 4 4\nTYPE F F F\nPOINTS 99999999999999999\nDATA binary\n\0\0\0\0\0\0\0\0\0\0\0\0");
+        assert!(matches!(err, PcdError::BinarySizeMismatch { actual: 12, .. }));
+
+        let err = load("overflow.pcd", b"FIELDS x y z\nSIZE 4 4 4\nTYPE F F F\nWIDTH 18446744073709551615\nHEIGHT 2\nDATA binary\n");
+        assert!(matches!(err, PcdError::MalformedHeader(_)));
+
+        let err = load("huge_ascii.pcd", b"FIELDS x y z\nSIZE 4 4 4\nTYPE F F F\nPOINTS 99999999999999999\nDATA ascii\n1 2 3\n");
+        assert!(matches!(err, PcdError::InvalidAsciiData { .. }));
+
+        let err = load("ascii.pcd", b"FIELDS x y z\nSIZE 4 4 4\nTYPE F F F\nPOINTS 2\nDATA ascii\n1 2 3\n4 five 6\n");
+        assert!(matches!(err, PcdError::InvalidAsciiData { line: 7, .. }));
+    }
+}
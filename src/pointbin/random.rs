@@ -0,0 +1,124 @@
+//! Randomized construction and augmentation of point sets
+
+use ndarray::{Array1, Array2};
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+
+use super::PointBin3D;
+
+/// Draw a sample from the standard normal distribution using the Box-Muller transform
+fn standard_normal<R: Rng + ?Sized>(rng: &mut R) -> f64 {
+    // 1 - u lies in (0, 1], keeping the logarithm finite
+    let u1: f64 = 1.0 - rng.gen::<f64>();
+    let u2: f64 = rng.gen();
+    (-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos()
+}
+
+impl PointBin3D {
+    /// Build a structure over points drawn uniformly from a box
+    ///
+    /// # Arguments
+    /// * `n` - Number of points
+    /// * `domain` - `[min, max]` extent of the box along x, y, z
+    /// * `bin_widths` - 1D array of shape (3,) with bin widths for x, y, z
+    /// * `rng` - Random number generator to draw the coordinates from
+    ///
+    /// # Panics
+    /// Panics if n is 0, bin_widths doesn't have length 3, or some `min` exceeds its `max`
+    pub fn from_random(n: usize, domain: [[f64; 2]; 3], bin_widths: Array1<f64>, rng: &mut impl Rng) -> Self {
+        assert!(n > 0, "Number of points must be positive");
+        assert!(domain.iter().all(|[lo, hi]| lo <= hi), "Domain minimum must not exceed its maximum");
+
+        let points = Array2::from_shape_fn((n, 3), |(_, j)| {
+            let [lo, hi] = domain[j];
+            lo + (hi - lo) * rng.gen::<f64>()
+        });
+        Self::new(points, bin_widths)
+    }
+
+    /// `from_random` with a generator seeded from `seed`, for reproducible test data
+    ///
+    /// Uses `SmallRng`, so the points are reproducible for a given seed on the same
+    /// platform and `rand` version, but not guaranteed across them.
+    ///
+    /// # Arguments
+    /// * `n` - Number of points
+    /// * `domain` - `[min, max]` extent of the box along x, y, z
+    /// * `bin_widths` - 1D array of shape (3,) with bin widths for x, y, z
+    /// * `seed` - Seed for the random number generator
+    ///
+    /// # Panics
+    /// Panics under the same conditions as `from_random`
+    pub fn with_seed(n: usize, domain: [[f64; 2]; 3], bin_widths: Array1<f64>, seed: u64) -> Self {
+        Self::from_random(n, domain, bin_widths, &mut SmallRng::seed_from_u64(seed))
+    }
+
+    /// Build a copy of the structure with Gaussian noise added to every coordinate
+    ///
+    /// Each coordinate of every original point, including points removed by searches,
+    /// is perturbed independently by `N(0, std_dev²)`. Jittered points may land in
+    /// different bins, so the copy is binned from scratch with the same bin widths,
+    /// point radii and reset backup setting, and starts with no points removed.
+    ///
+    /// # Arguments
+    /// * `std_dev` - Standard deviation of the noise
+    /// * `rng` - Random number generator to draw the noise from
+    ///
+    /// # Returns
+    /// A new PointBin3D over the jittered points
+    pub fn clone_with_jitter(&self, std_dev: f64, rng: &mut impl Rng) -> Self {
+        let mut jittered: Array2<f64> = self.original_points.clone();
+        for value in jittered.iter_mut() {
+            *value += std_dev * standard_normal(rng);
+        }
+
+        let mut builder = Self::builder(jittered, self.bin_widths.clone())
+            .keep_reset_backups(self.original_first_member.is_some());
+        if let Some(point_radii) = self.original_order_point_radii() {
+            builder = builder.point_radii(point_radii);
+        }
+        builder.build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn test_clone_with_jitter() {
+        let mut points = Array2::<f64>::zeros((2000, 3));
+        for i in 0..2000 {
+            points[[i, 0]] = (i % 10) as f64;
+        }
+        let point_bin = PointBin3D::new(points.clone(), array![1.0, 1.0, 1.0]);
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let jittered = point_bin.clone_with_jitter(0.1, &mut rng);
+        assert_eq!(jittered.bin_widths, point_bin.bin_widths);
+
+        let noise = jittered.original_points() - &points;
+        let mean = noise.mean().unwrap();
+        let std_dev = (noise.mapv(|d| (d - mean) * (d - mean)).mean().unwrap()).sqrt();
+        assert!(mean.abs() < 0.01);
+        assert!((std_dev - 0.1).abs() < 0.01);
+
+        assert_eq!(point_bin.clone_with_jitter(0.0, &mut rng).original_points(), &points);
+    }
+
+    #[test]
+    fn test_with_seed_is_reproducible() {
+        let domain = [[0.0, 10.0], [-5.0, 5.0], [2.0, 2.5]];
+        let a = PointBin3D::with_seed(500, domain, array![1.0, 1.0, 1.0], 42);
+        let b = PointBin3D::with_seed(500, domain, array![1.0, 1.0, 1.0], 42);
+        let c = PointBin3D::with_seed(500, domain, array![1.0, 1.0, 1.0], 43);
+
+        assert_eq!(a.original_points(), b.original_points());
+        assert_ne!(a.original_points(), c.original_points());
+        for (j, [lo, hi]) in domain.iter().enumerate() {
+            assert!(a.original_points().column(j).iter().all(|v| lo <= v && v < hi));
+        }
+    }
+}
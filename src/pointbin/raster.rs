@@ -0,0 +1,167 @@
+//! Top-down rasterization of the points into images
+
+use image::{GrayImage, ImageBuffer, Luma};
+
+use super::PointBin3D;
+
+/// Per-pixel maximum Z over a regular XY grid covering the remaining points
+struct HeightGrid {
+    width: u32,
+    height: u32,
+    /// Row-major maximum Z per pixel, `None` where no point falls
+    max_z: Vec<Option<f64>>,
+}
+
+impl PointBin3D {
+    /// Rasterize the remaining points into a top-down grayscale height image
+    ///
+    /// The XY bounding box of the points is divided into square pixels of side
+    /// `resolution`, with x increasing to the right and y increasing upwards. Each pixel
+    /// takes the maximum Z of the points falling in it, scaled so the lowest such value
+    /// maps to 0 and the highest to 255. Pixels without points are 0.
+    ///
+    /// # Arguments
+    /// * `resolution` - Pixel size in world units
+    ///
+    /// # Returns
+    /// A grayscale image, or an empty image if no points remain
+    ///
+    /// # Panics
+    /// Panics if resolution is not positive
+    pub fn to_heightmap_image(&self, resolution: f64) -> GrayImage {
+        let grid = self.height_grid(resolution);
+
+        let (z_min, z_max) = grid
+            .max_z
+            .iter()
+            .flatten()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &z| (lo.min(z), hi.max(z)));
+        let z_range = z_max - z_min;
+
+        let pixels = grid
+            .max_z
+            .iter()
+            .map(|z| match z {
+                Some(z) if z_range > 0.0 => ((z - z_min) / z_range * 255.0).round() as u8,
+                Some(_) => 255,
+                None => 0,
+            })
+            .collect();
+
+        GrayImage::from_raw(grid.width, grid.height, pixels).expect("buffer matches image size")
+    }
+
+    /// Rasterize the remaining points into a top-down 16-bit depth image
+    ///
+    /// Uses the same pixel layout as `to_heightmap_image`, but instead of normalizing
+    /// to the observed range each pixel's maximum Z is mapped linearly from
+    /// `[z_near, z_far]` to `[0, 65535]`, clamping values outside that interval.
+    /// Pixels without points are 0.
+    ///
+    /// # Arguments
+    /// * `resolution` - Pixel size in world units
+    /// * `z_near` - Z value mapped to 0
+    /// * `z_far` - Z value mapped to 65535
+    ///
+    /// # Returns
+    /// A 16-bit grayscale image, or an empty image if no points remain
+    ///
+    /// # Panics
+    /// Panics if resolution is not positive or z_near equals z_far
+    pub fn to_depth_image(&self, resolution: f64, z_near: f64, z_far: f64) -> ImageBuffer<Luma<u16>, Vec<u16>> {
+        assert!(z_near != z_far, "z_near and z_far must differ");
+        let grid = self.height_grid(resolution);
+
+        let pixels = grid
+            .max_z
+            .iter()
+            .map(|z| match z {
+                Some(z) => ((z - z_near) / (z_far - z_near)).clamp(0.0, 1.0) * 65535.0,
+                None => 0.0,
+            })
+            .map(|depth| depth.round() as u16)
+            .collect();
+
+        ImageBuffer::from_raw(grid.width, grid.height, pixels).expect("buffer matches image size")
+    }
+
+    fn height_grid(&self, resolution: f64) -> HeightGrid {
+        assert!(resolution > 0.0, "Resolution must be positive");
+
+        let live: Vec<usize> = (0..self.points.nrows())
+            .filter(|&i| !self.is_removed(i))
+            .collect();
+        if live.is_empty() {
+            return HeightGrid { width: 0, height: 0, max_z: Vec::new() };
+        }
+
+        let mut min_xy = [f64::INFINITY; 2];
+        let mut max_xy = [f64::NEG_INFINITY; 2];
+        for &i in &live {
+            for j in 0..2 {
+                min_xy[j] = min_xy[j].min(self.points[[i, j]]);
+                max_xy[j] = max_xy[j].max(self.points[[i, j]]);
+            }
+        }
+
+        let width = ((max_xy[0] - min_xy[0]) / resolution).floor() as u32 + 1;
+        let height = ((max_xy[1] - min_xy[1]) / resolution).floor() as u32 + 1;
+
+        let mut max_z: Vec<Option<f64>> = vec![None; width as usize * height as usize];
+        for &i in &live {
+            let col = (((self.points[[i, 0]] - min_xy[0]) / resolution).floor() as u32).min(width - 1);
+            let row_from_bottom = (((self.points[[i, 1]] - min_xy[1]) / resolution).floor() as u32).min(height - 1);
+            let pixel = (height - 1 - row_from_bottom) as usize * width as usize + col as usize;
+
+            let z = self.points[[i, 2]];
+            max_z[pixel] = Some(max_z[pixel].map_or(z, |current| current.max(z)));
+        }
+
+        HeightGrid { width, height, max_z }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    #[test]
+    fn test_to_heightmap_image() {
+        let points = array![
+            [0.0, 0.0, 1.0],
+            [0.2, 0.3, 3.0],
+            [1.5, 0.0, 5.0],
+            [1.5, 1.5, 2.0],
+        ];
+        let bin_widths = array![1.0, 1.0, 1.0];
+        let point_bin = PointBin3D::new(points, bin_widths);
+
+        let image = point_bin.to_heightmap_image(1.0);
+        assert_eq!(image.dimensions(), (2, 2));
+
+        // Row 0 is the top of the image (largest y)
+        assert_eq!(image.get_pixel(0, 0)[0], 0);
+        assert_eq!(image.get_pixel(1, 0)[0], 0);
+        assert_eq!(image.get_pixel(0, 1)[0], 85);
+        assert_eq!(image.get_pixel(1, 1)[0], 255);
+    }
+
+    #[test]
+    fn test_to_depth_image() {
+        let points = array![
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 5.0],
+            [2.0, 0.0, 10.0],
+            [3.0, 0.0, 20.0],
+        ];
+        let bin_widths = array![1.0, 1.0, 1.0];
+        let point_bin = PointBin3D::new(points, bin_widths);
+
+        let image = point_bin.to_depth_image(1.0, 0.0, 10.0);
+        assert_eq!(image.dimensions(), (4, 1));
+
+        let depths: Vec<u16> = image.pixels().map(|p| p[0]).collect();
+        assert_eq!(depths, vec![0, 32768, 65535, 65535]);
+    }
+}
@@ -0,0 +1,96 @@
+//! Removing points by their original index
+
+use std::collections::HashSet;
+use std::fmt;
+
+use super::PointBin3D;
+
+/// Error returned when points cannot be removed by index
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoveError {
+    /// An index doesn't refer to a point of the structure
+    IndexOutOfRange { index: usize, n_points: usize },
+}
+
+impl fmt::Display for RemoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RemoveError::IndexOutOfRange { index, n_points } => {
+                write!(f, "Index {} out of range for {} points", index, n_points)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RemoveError {}
+
+impl PointBin3D {
+    /// Remove a set of points by original index without reporting them as found
+    ///
+    /// Like `remove_region`, the points are unlinked so later searches skip them and
+    /// `remaining_count()` drops, but they are not added to the found indices, and
+    /// `reset()` restores them. The indices are collected into a set and only the bins
+    /// holding them are walked, each once. Indices may repeat or refer to points that
+    /// were already removed; those are skipped.
+    ///
+    /// # Arguments
+    /// * `indices` - Original indices of the points to remove
+    ///
+    /// # Returns
+    /// The number of points removed by this call, or an error leaving the structure
+    /// untouched if any index is out of range
+    pub fn remove_by_indices(&mut self, indices: &[usize]) -> Result<usize, RemoveError> {
+        let n_points = self.points.nrows();
+        if let Some(&index) = indices.iter().find(|&&index| index >= n_points) {
+            return Err(RemoveError::IndexOutOfRange { index, n_points });
+        }
+
+        let wanted: HashSet<usize> = indices.iter().copied().collect();
+        let targets: HashSet<usize> = (0..n_points)
+            .filter(|&i| !self.is_removed(i) && wanted.contains(&(self.original_indices[i] as usize)))
+            .collect();
+        let bins: HashSet<[usize; 3]> = targets
+            .iter()
+            .map(|&i| self.bin_of(&self.points.row(i)).map(|b| b as usize))
+            .collect();
+
+        let removed_before = self.removed_count;
+        for bin in bins {
+            self.unlink_matching_in_bin(bin, false, |i, _| targets.contains(&i));
+        }
+        Ok(self.removed_count - removed_before)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pointbin::scattered_points;
+    use ndarray::array;
+
+    #[test]
+    fn test_remove_by_indices() {
+        let points = scattered_points(100, [4.0; 3]);
+        let mut point_bin = PointBin3D::new(points, array![1.0, 1.0, 1.0]);
+
+        // Out of range indices leave everything untouched
+        let err = point_bin.remove_by_indices(&[3, 100]).unwrap_err();
+        assert_eq!(err, RemoveError::IndexOutOfRange { index: 100, n_points: 100 });
+        assert_eq!(point_bin.remaining_count(), 100);
+
+        assert_eq!(point_bin.remove_by_indices(&[3, 7, 7, 42]), Ok(3));
+        assert_eq!(point_bin.remove_by_indices(&[7, 8]), Ok(1));
+        assert_eq!(point_bin.remaining_count(), 96);
+        assert_eq!(point_bin.found_count(), 0);
+
+        // Removed points are skipped by searches until a reset
+        point_bin.radius_search(&array![2.0, 2.0, 2.0].view(), 10.0);
+        let mut found = point_bin.found_indices().to_vec();
+        found.sort();
+        let expected: Vec<i64> = (0..100).filter(|i| ![3, 7, 8, 42].contains(i)).collect();
+        assert_eq!(found, expected);
+
+        point_bin.reset();
+        assert_eq!(point_bin.remaining_count(), 100);
+    }
+}
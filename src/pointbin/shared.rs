@@ -0,0 +1,96 @@
+//! Shared, read-only handles for querying from several threads at once
+
+use std::ops::Deref;
+use std::sync::Arc;
+
+use super::PointBin3D;
+
+/// Cheaply clonable, read-only handle to a PointBin3D
+///
+/// Created by `PointBin3D::concurrent_read_wrapper`. Handles dereference to the
+/// structure, so every `&self` method, such as `radius_query`, `neighbor_stream` or
+/// `found_indices`, can be called from many threads at once, e.g. one handle per task
+/// of a `rayon::scope`. `radius_search` and the other methods that remove points need
+/// exclusive access and aren't available through a handle; recover the structure with
+/// `try_unwrap` once the other handles are dropped.
+#[derive(Clone)]
+pub struct SharedPointBin3D(Arc<PointBin3D>);
+
+impl SharedPointBin3D {
+    /// Another handle to the same structure, without copying any points
+    pub fn read_only_clone(&self) -> SharedPointBin3D {
+        self.clone()
+    }
+
+    /// Take the structure back for mutation if this is its only handle
+    ///
+    /// # Returns
+    /// The structure, or this handle unchanged if other handles still exist
+    pub fn try_unwrap(self) -> Result<PointBin3D, SharedPointBin3D> {
+        Arc::try_unwrap(self.0).map_err(SharedPointBin3D)
+    }
+}
+
+impl Deref for SharedPointBin3D {
+    type Target = PointBin3D;
+
+    fn deref(&self) -> &PointBin3D {
+        &self.0
+    }
+}
+
+impl PointBin3D {
+    /// Wrap the structure for concurrent read-only queries
+    ///
+    /// The found state is kept, so handles see the points remaining after any earlier
+    /// destructive searches.
+    pub fn concurrent_read_wrapper(self) -> SharedPointBin3D {
+        SharedPointBin3D(Arc::new(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pointbin::scattered_points;
+    use ndarray::array;
+
+    #[test]
+    fn test_concurrent_read_wrapper() {
+        let points = scattered_points(300, [6.0; 3]);
+        let mut point_bin = PointBin3D::new(points, array![1.0, 1.0, 1.0]);
+        point_bin.radius_search(&array![0.0, 0.0, 0.0].view(), 2.0);
+        let found = point_bin.found_indices();
+
+        let queries = [[1.0, 1.0, 1.0], [3.0, 3.0, 3.0], [5.0, 1.0, 2.0], [2.0, 4.5, 0.5]];
+        let expected: Vec<_> = queries
+            .iter()
+            .map(|q| point_bin.radius_query(&ndarray::ArrayView1::from(q), 1.5))
+            .collect();
+
+        let shared = point_bin.concurrent_read_wrapper();
+        let found = &found;
+        let results: Vec<_> = std::thread::scope(|scope| {
+            let handles: Vec<_> = queries
+                .iter()
+                .map(|q| {
+                    let handle = shared.read_only_clone();
+                    scope.spawn(move || {
+                        assert_eq!(&handle.found_indices(), found);
+                        handle.radius_query(&ndarray::ArrayView1::from(q), 1.5)
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        assert_eq!(results, expected);
+
+        // Mutation is possible again once the structure has a single owner
+        let other = shared.read_only_clone();
+        let shared = shared.try_unwrap().err().unwrap();
+        drop(other);
+        let mut point_bin = shared.try_unwrap().ok().unwrap();
+        point_bin.reset();
+        assert_eq!(point_bin.found_count(), 0);
+    }
+}
@@ -0,0 +1,487 @@
+//! Summary statistics over the remaining points
+
+use std::ops::ControlFlow;
+
+use ndarray::{Array1, Array2, ArrayView1};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use super::PointBin3D;
+#[cfg(feature = "linalg")]
+use crate::linalg::symmetric_eigen3;
+
+/// Aggregate of a per-point attribute over the points within a radius
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RadiusAgg {
+    /// Number of points within the radius
+    pub count: usize,
+    /// Sum of their values (0 if there are none)
+    pub sum: f64,
+    /// Smallest value (infinity if there are none)
+    pub min: f64,
+    /// Largest value (negative infinity if there are none)
+    pub max: f64,
+    /// Mean value (NaN if there are none)
+    pub mean: f64,
+}
+
+impl PointBin3D {
+    /// Spatial covariance of the remaining points
+    ///
+    /// Computes the sample covariance `C = Σ (p - μ)(p - μ)ᵀ / (n - 1)` over the points
+    /// not yet removed by a search, in a single pass. The result is symmetric and can be
+    /// fed to an eigen-decomposition to find the orientation of an oriented bounding box.
+    ///
+    /// # Returns
+    /// The 3x3 covariance matrix, or `None` if fewer than two points remain
+    pub fn covariance_matrix(&self) -> Option<Array2<f64>> {
+        let n = self.remaining_count();
+        if n < 2 {
+            return None;
+        }
+
+        let live: Vec<usize> = (0..self.points.nrows())
+            .filter(|&i| !self.is_removed(i))
+            .collect();
+
+        let mut mean = Array1::<f64>::zeros(3);
+        for &i in &live {
+            mean += &self.points.row(i);
+        }
+        mean /= n as f64;
+
+        let mut covariance = Array2::<f64>::zeros((3, 3));
+        for &i in &live {
+            let d = &self.points.row(i) - &mean;
+            for a in 0..3 {
+                for b in a..3 {
+                    covariance[[a, b]] += d[a] * d[b];
+                }
+            }
+        }
+        for a in 0..3 {
+            for b in a..3 {
+                covariance[[a, b]] /= (n - 1) as f64;
+                covariance[[b, a]] = covariance[[a, b]];
+            }
+        }
+
+        Some(covariance)
+    }
+
+    /// Number of other points within `radius` of each point
+    ///
+    /// Only remaining points are counted, and points removed by searches get a count of
+    /// 0. With the `rayon` feature the points are processed in parallel.
+    ///
+    /// # Arguments
+    /// * `radius` - Neighbourhood radius
+    ///
+    /// # Returns
+    /// 1D array of shape (n_points,) with the neighbour counts in original order
+    pub fn all_radius_counts(&self, radius: f64) -> Array1<u32> {
+        let mut counts = Array1::<u32>::zeros(self.points.nrows());
+        for (sorted_idx, count) in self.sorted_radius_counts(radius).into_iter().enumerate() {
+            counts[self.original_indices[sorted_idx] as usize] = count;
+        }
+        counts
+    }
+
+    /// Histogram of how many points have each number of neighbours within `radius`
+    ///
+    /// Entry `d` is the number of remaining points with exactly `d` other remaining
+    /// points within `radius`, i.e. the degree distribution of the radius graph that
+    /// `all_radius_counts` describes. Removed points are not counted.
+    ///
+    /// # Arguments
+    /// * `radius` - Neighbourhood radius
+    ///
+    /// # Returns
+    /// 1D array whose length is one more than the largest degree (empty if no points remain)
+    pub fn degree_distribution(&self, radius: f64) -> Array1<u64> {
+        let mut histogram: Vec<u64> = Vec::new();
+        for (sorted_idx, degree) in self.sorted_radius_counts(radius).into_iter().enumerate() {
+            if self.is_removed(sorted_idx) {
+                continue;
+            }
+            let degree = degree as usize;
+            if histogram.len() <= degree {
+                histogram.resize(degree + 1, 0);
+            }
+            histogram[degree] += 1;
+        }
+        Array1::from(histogram)
+    }
+
+    /// Neighbour count of each point in sorted order, 0 for removed points
+    fn sorted_radius_counts(&self, radius: f64) -> Vec<u32> {
+        let count_neighbours = |i: usize| -> u32 {
+            if self.is_removed(i) {
+                return 0;
+            }
+            // Skip the point itself rather than subtracting it, as a negative or NaN
+            // radius doesn't find it
+            let mut count = 0;
+            let _ = self.visit_within(&self.points.row(i), radius, |j, _| {
+                if j != i {
+                    count += 1;
+                }
+                ControlFlow::Continue(())
+            });
+            count
+        };
+
+        #[cfg(feature = "rayon")]
+        let counts = (0..self.points.nrows()).into_par_iter().map(count_neighbours).collect();
+        #[cfg(not(feature = "rayon"))]
+        let counts = (0..self.points.nrows()).map(count_neighbours).collect();
+        counts
+    }
+
+    /// Find the points with no other point within `radius`
+    ///
+    /// Runs a read-only query around each remaining point that stops at the first
+    /// neighbour, so dense regions are cheap. Points removed by searches are neither
+    /// reported nor count as neighbours. With the `rayon` feature the points are
+    /// processed in parallel.
+    ///
+    /// # Arguments
+    /// * `radius` - Neighbourhood radius
+    ///
+    /// # Returns
+    /// Original indices of the isolated points, ascending
+    pub fn isolated_points(&self, radius: f64) -> Array1<i64> {
+        let is_isolated = |&i: &usize| {
+            !self.is_removed(i)
+                && self
+                    .visit_within(&self.points.row(i), radius, |j, _| {
+                        if j == i {
+                            ControlFlow::Continue(())
+                        } else {
+                            ControlFlow::Break(())
+                        }
+                    })
+                    .is_continue()
+        };
+
+        let sorted: Vec<usize> = (0..self.points.nrows()).collect();
+        #[cfg(feature = "rayon")]
+        let isolated = sorted.par_iter().filter(|i| is_isolated(i));
+        #[cfg(not(feature = "rayon"))]
+        let isolated = sorted.iter().filter(|i| is_isolated(i));
+
+        let mut indices: Vec<i64> = isolated.map(|&i| self.original_indices[i]).collect();
+        indices.sort_unstable();
+        Array1::from(indices)
+    }
+
+    /// Fraction of all points within `radius` of each point
+    ///
+    /// `all_radius_counts` divided by the total number of points, giving a local
+    /// density normalized to `[0, 1)`.
+    ///
+    /// # Arguments
+    /// * `radius` - Neighbourhood radius
+    ///
+    /// # Returns
+    /// 1D array of shape (n_points,) with the density fractions in original order
+    pub fn local_density_fraction(&self, radius: f64) -> Array1<f64> {
+        let n_points = self.points.nrows() as f64;
+        self.all_radius_counts(radius).mapv(|count| count as f64 / n_points)
+    }
+
+    /// Count, sum, min, max and mean of a per-point attribute within a radius
+    ///
+    /// Aggregates `values[original_index]` over the remaining points within `radius`
+    /// of the query in a single traversal, without collecting their indices. Nothing is
+    /// removed from the structure.
+    ///
+    /// # Arguments
+    /// * `query_point` - 3D point to search around
+    /// * `radius` - Search radius
+    /// * `values` - 1D array of shape (n_points,) with one value per original point
+    ///
+    /// # Panics
+    /// Panics if query_point doesn't have exactly 3 elements or values doesn't have one
+    /// entry per point
+    pub fn radius_aggregate(&self, query_point: &ArrayView1<f64>, radius: f64, values: &ArrayView1<f64>) -> RadiusAgg {
+        assert_eq!(query_point.len(), 3, "Query point must have 3 dimensions");
+        assert_eq!(values.len(), self.points.nrows(), "Values must have one entry per point");
+
+        let mut agg = RadiusAgg { count: 0, sum: 0.0, min: f64::INFINITY, max: f64::NEG_INFINITY, mean: f64::NAN };
+        let _ = self.visit_within(query_point, radius, |i, _| {
+            let value = values[self.original_indices[i] as usize];
+            agg.count += 1;
+            agg.sum += value;
+            agg.min = agg.min.min(value);
+            agg.max = agg.max.max(value);
+            ControlFlow::Continue(())
+        });
+        if agg.count > 0 {
+            agg.mean = agg.sum / agg.count as f64;
+        }
+        agg
+    }
+
+    /// Principal axes of the remaining points
+    ///
+    /// Eigen-decomposes `covariance_matrix`. The eigenvectors give the orientation of
+    /// the point cloud's oriented bounding box and the eigenvalues the variance along
+    /// each of those axes. Eigenvector signs are arbitrary. The covariance matrix is
+    /// symmetric positive semi-definite, so this matches its SVD; see the `linalg`
+    /// module for why it doesn't go through `ndarray-linalg`.
+    ///
+    /// # Returns
+    /// `(axes, variances)` with unit axes as the columns of a 3x3 matrix, ordered by
+    /// descending variance, or `None` if fewer than two points remain
+    #[cfg(feature = "linalg")]
+    pub fn principal_axes(&self) -> Option<(Array2<f64>, Array1<f64>)> {
+        self.covariance_matrix().map(|covariance| symmetric_eigen3(&covariance))
+    }
+
+    /// Surface normal of every occupied bin, estimated from its remaining points
+    ///
+    /// Each bin's normal is the eigenvector of the smallest eigenvalue of the
+    /// covariance of its points, the direction in which they vary least. For points
+    /// sampled from a surface this is perpendicular to the surface, as long as the bins
+    /// are small enough for the surface to be roughly flat inside each one. Normal signs
+    /// are arbitrary.
+    ///
+    /// # Returns
+    /// `(bins, normals)`: the (n_occupied, 3) bin indices in row-major order and their
+    /// (n_occupied, 3) unit normals. Bins with fewer than 3 points don't determine a
+    /// plane and get a normal of NaNs.
+    #[cfg(feature = "linalg")]
+    pub fn bin_normals(&self) -> (Array2<i64>, Array2<f64>) {
+        let mut bins = Vec::new();
+        let mut normals = Vec::new();
+        self.for_each_bin(|ix, iy, iz, members| {
+            bins.extend([ix as i64, iy as i64, iz as i64]);
+            if members.len() < 3 {
+                normals.extend([f64::NAN; 3]);
+                return;
+            }
+
+            let mut mean = [0.0; 3];
+            for &i in members {
+                for (m, &p) in mean.iter_mut().zip(self.points.row(i as usize)) {
+                    *m += p;
+                }
+            }
+            let n = members.len() as f64;
+            mean.iter_mut().for_each(|m| *m /= n);
+
+            let mut covariance = Array2::<f64>::zeros((3, 3));
+            for &i in members {
+                let d = [
+                    self.points[[i as usize, 0]] - mean[0],
+                    self.points[[i as usize, 1]] - mean[1],
+                    self.points[[i as usize, 2]] - mean[2],
+                ];
+                for a in 0..3 {
+                    for b in 0..3 {
+                        covariance[[a, b]] += d[a] * d[b] / n;
+                    }
+                }
+            }
+            let (vectors, _) = symmetric_eigen3(&covariance);
+            normals.extend(vectors.column(2).iter());
+        });
+
+        let n_bins = bins.len() / 3;
+        (
+            Array2::from_shape_vec((n_bins, 3), bins).expect("three indices per bin"),
+            Array2::from_shape_vec((n_bins, 3), normals).expect("three components per bin"),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    #[test]
+    fn test_covariance_matrix() {
+        let points = array![
+            [0.0, 0.0, 0.0],
+            [2.0, 0.0, 1.0],
+            [4.0, 0.0, 2.0],
+            [6.0, 0.0, 3.0],
+        ];
+        let mut point_bin = PointBin3D::new(points, array![1.0, 1.0, 1.0]);
+
+        let covariance = point_bin.covariance_matrix().unwrap();
+        let expected = array![
+            [20.0 / 3.0, 0.0, 10.0 / 3.0],
+            [0.0, 0.0, 0.0],
+            [10.0 / 3.0, 0.0, 5.0 / 3.0],
+        ];
+        for (c, e) in covariance.iter().zip(expected.iter()) {
+            assert!((c - e).abs() < 1e-12);
+        }
+
+        // Removing all but one point leaves nothing to estimate from
+        point_bin.radius_search(&array![0.0, 0.0, 0.0].view(), 5.0);
+        assert_eq!(point_bin.remaining_count(), 1);
+        assert!(point_bin.covariance_matrix().is_none());
+    }
+
+    #[cfg(feature = "linalg")]
+    #[test]
+    fn test_principal_axes() {
+        // Points spread along (1, 1, 0), a little along (1, -1, 0) and not at all in z
+        let mut points = Array2::<f64>::zeros((40, 3));
+        for i in 0..40 {
+            let along = i as f64 - 19.5;
+            let across = if i % 4 == 0 || i % 4 == 3 { 0.5 } else { -0.5 };
+            points[[i, 0]] = along + across;
+            points[[i, 1]] = along - across;
+            points[[i, 2]] = 2.0;
+        }
+        let point_bin = PointBin3D::new(points, array![4.0, 4.0, 4.0]);
+
+        let (axes, variances) = point_bin.principal_axes().unwrap();
+        assert!(variances[0] > variances[1] && variances[1] > variances[2]);
+        assert!(variances[2].abs() < 1e-9);
+
+        let inv_sqrt2 = 0.5_f64.sqrt();
+        assert!((axes[[0, 0]].abs() - inv_sqrt2).abs() < 1e-9);
+        assert!((axes[[0, 0]] - axes[[1, 0]]).abs() < 1e-9);
+        assert!((axes[[0, 1]] + axes[[1, 1]]).abs() < 1e-9);
+        assert!((axes[[2, 2]].abs() - 1.0).abs() < 1e-9);
+    }
+
+    #[cfg(feature = "linalg")]
+    #[test]
+    fn test_bin_normals_planar() {
+        // Points on the plane z = 0.3x - 0.2y + 1, plus one stray point alone in its bin
+        let mut points = Array2::<f64>::zeros((101, 3));
+        for i in 0..100 {
+            let x = (i % 10) as f64 * 0.39 + 0.05;
+            let y = (i / 10) as f64 * 0.37 + 0.05;
+            points[[i, 0]] = x;
+            points[[i, 1]] = y;
+            points[[i, 2]] = 0.3 * x - 0.2 * y + 1.0;
+        }
+        points.row_mut(100).assign(&array![3.9, 0.0, 5.0]);
+        let point_bin = PointBin3D::new(points.clone(), array![2.0, 2.0, 2.0]);
+
+        let (bins, normals) = point_bin.bin_normals();
+        assert_eq!(bins.ncols(), 3);
+        assert_eq!(bins.nrows(), normals.nrows());
+
+        let plane_normal = array![-0.3_f64, 0.2, 1.0];
+        let plane_normal = &plane_normal / plane_normal.dot(&plane_normal).sqrt();
+        let mut n_planar = 0;
+        for (bin, normal) in bins.rows().into_iter().zip(normals.rows()) {
+            if bin[2] == 2 {
+                assert!(normal.iter().all(|c| c.is_nan()));
+                continue;
+            }
+            assert!((normal.dot(&normal) - 1.0).abs() < 1e-9);
+            assert!((normal.dot(&plane_normal).abs() - 1.0).abs() < 1e-9);
+            n_planar += 1;
+        }
+        assert_eq!(n_planar, 4);
+        assert_eq!(bins.nrows(), 5);
+    }
+
+    #[test]
+    fn test_radius_aggregate() {
+        let points = array![
+            [0.0, 0.0, 0.0],
+            [0.5, 0.0, 0.0],
+            [0.0, 0.9, 0.0],
+            [0.0, 0.0, 2.0],
+            [0.3, 0.3, 0.3],
+        ];
+        let values = array![4.0, -1.5, 10.0, 100.0, 2.5];
+        let mut point_bin = PointBin3D::new(points, array![1.0, 1.0, 1.0]);
+        point_bin.radius_search(&array![0.3, 0.3, 0.3].view(), 0.0);
+
+        // Neighbours within 1 of the origin: points 0, 1 and 2 (point 4 was removed)
+        let agg = point_bin.radius_aggregate(&array![0.0, 0.0, 0.0].view(), 1.0, &values.view());
+        let neighbour_values = [4.0, -1.5, 10.0];
+        assert_eq!(agg.count, 3);
+        assert_eq!(agg.sum, neighbour_values.iter().sum::<f64>());
+        assert_eq!(agg.min, -1.5);
+        assert_eq!(agg.max, 10.0);
+        assert!((agg.mean - 12.5 / 3.0).abs() < 1e-12);
+
+        let empty = point_bin.radius_aggregate(&array![5.0, 5.0, 5.0].view(), 0.5, &values.view());
+        assert_eq!((empty.count, empty.sum), (0, 0.0));
+        assert!(empty.mean.is_nan());
+    }
+
+    #[test]
+    fn test_local_density_fraction() {
+        let points = array![
+            [0.0, 0.0, 0.0],
+            [0.5, 0.0, 0.0],
+            [0.0, 0.5, 0.0],
+            [3.0, 3.0, 3.0],
+            [3.2, 3.0, 3.0],
+            [9.0, 9.0, 9.0],
+        ];
+        let point_bin = PointBin3D::new(points, array![1.0, 1.0, 1.0]);
+
+        let counts = point_bin.all_radius_counts(0.6);
+        assert_eq!(counts, array![2, 1, 1, 1, 1, 0]);
+
+        let fractions = point_bin.local_density_fraction(0.6);
+        assert!(fractions.iter().all(|&f| (0.0..=1.0).contains(&f)));
+        for (fraction, count) in fractions.iter().zip(counts.iter()) {
+            assert_eq!(*fraction, *count as f64 / 6.0);
+        }
+    }
+
+    #[test]
+    fn test_isolated_points() {
+        // Dense 4x4x4 lattice with spacing 0.5, plus one point far away
+        let mut points = Array2::<f64>::zeros((65, 3));
+        for i in 0..64 {
+            points[[i, 0]] = (i % 4) as f64 * 0.5;
+            points[[i, 1]] = ((i / 4) % 4) as f64 * 0.5;
+            points[[i, 2]] = (i / 16) as f64 * 0.5;
+        }
+        points.row_mut(64).assign(&array![6.0, 6.0, 6.0]);
+        let mut point_bin = PointBin3D::new(points, array![1.0, 1.0, 1.0]);
+
+        assert_eq!(point_bin.isolated_points(0.6), array![64]);
+        assert_eq!(point_bin.isolated_points(0.4).len(), 65);
+
+        // Removing a corner's neighbours isolates it
+        point_bin.radius_search(&array![0.5, 0.0, 0.0].view(), 0.0);
+        point_bin.radius_search(&array![0.0, 0.5, 0.0].view(), 0.0);
+        point_bin.radius_search(&array![0.0, 0.0, 0.5].view(), 0.0);
+        assert_eq!(point_bin.isolated_points(0.6), array![0, 64]);
+    }
+
+    #[test]
+    fn test_degree_distribution_lattice() {
+        // 5x5x5 lattice with unit spacing: only the six face neighbours are within 1
+        let mut points = Array2::<f64>::zeros((125, 3));
+        for i in 0..125 {
+            points[[i, 0]] = (i % 5) as f64;
+            points[[i, 1]] = ((i / 5) % 5) as f64;
+            points[[i, 2]] = (i / 25) as f64;
+        }
+        let point_bin = PointBin3D::new(points, array![1.5, 1.5, 1.5]);
+
+        // Corners, edges, faces and the 3x3x3 interior
+        let histogram = point_bin.degree_distribution(1.0);
+        assert_eq!(histogram, array![0, 0, 0, 8, 36, 54, 27]);
+        assert_eq!(histogram.sum(), 125);
+    }
+
+    #[test]
+    fn test_radius_counts_negative_or_nan_radius() {
+        let point_bin = PointBin3D::new(array![[0.0, 0.0, 0.0], [0.5, 0.0, 0.0]], array![1.0, 1.0, 1.0]);
+        for radius in [-1.0, f64::NAN] {
+            assert_eq!(point_bin.all_radius_counts(radius), array![0, 0]);
+            assert_eq!(point_bin.local_density_fraction(radius), array![0.0, 0.0]);
+            assert_eq!(point_bin.degree_distribution(radius), array![2]);
+        }
+    }
+}
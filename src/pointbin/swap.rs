@@ -0,0 +1,272 @@
+//! Radius searches that move the matches to new coordinates
+
+use std::fmt;
+use std::ops::ControlFlow;
+
+use ndarray::{Array1, Array2, Array3, ArrayView1};
+
+use super::PointBin3D;
+
+/// Replacement coordinates whose shape doesn't match the points they replace
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DimensionMismatch {
+    /// Shape the replacements needed, `(n_found, 3)`
+    pub expected: (usize, usize),
+    /// Shape the replacements had
+    pub actual: (usize, usize),
+}
+
+impl fmt::Display for DimensionMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Expected replacements of shape {:?}, got {:?}",
+            self.expected, self.actual
+        )
+    }
+}
+
+impl std::error::Error for DimensionMismatch {}
+
+/// Error returned when `radius_search_and_swap` cannot move the matches
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SwapError {
+    /// `replacements` doesn't have one row per match
+    Shape(DimensionMismatch),
+    /// A replacement lies outside the grid, where searches couldn't find it
+    OutsideGrid { row: usize, point: [f64; 3] },
+}
+
+impl From<DimensionMismatch> for SwapError {
+    fn from(err: DimensionMismatch) -> Self {
+        SwapError::Shape(err)
+    }
+}
+
+impl fmt::Display for SwapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SwapError::Shape(err) => err.fmt(f),
+            SwapError::OutsideGrid { row, point } => {
+                write!(f, "Replacement {} at {:?} lies outside the grid", row, point)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SwapError {}
+
+impl PointBin3D {
+    /// Radius search that moves every match to new coordinates
+    ///
+    /// Finds the points within `radius` of the query, then gives the i-th match the
+    /// coordinates in row i of `replacements`, matches being ordered as `radius_search`
+    /// would report them. Each moved point is unlinked from its old bin and linked into
+    /// the bin of its new coordinates at its sorted position, and both `points` and
+    /// `original_points` are updated in place, so its original index is unchanged. The
+    /// moved points stay in the structure and are appended to `found_indices()`, which
+    /// gives the original index each replacement row went to. The reset backups are
+    /// updated too, so `reset()` keeps the new coordinates. Replacements must lie
+    /// inside the grid, whose bins are the only ones searches visit.
+    ///
+    /// Because matches stay in the structure, a point moved within `radius` of a later
+    /// query is matched again.
+    ///
+    /// # Arguments
+    /// * `query_point` - 3D point to search around
+    /// * `radius` - Search radius
+    /// * `replacements` - 2D array of shape (n_found, 3) with the new coordinates
+    ///
+    /// # Returns
+    /// The number of points moved, or an error leaving the structure untouched if
+    /// `replacements` doesn't have one row per match or a row lies outside the grid
+    ///
+    /// # Panics
+    /// Panics if query_point doesn't have exactly 3 elements
+    pub fn radius_search_and_swap(
+        &mut self,
+        query_point: &ArrayView1<f64>,
+        radius: f64,
+        replacements: Array2<f64>,
+    ) -> Result<usize, SwapError> {
+        assert_eq!(query_point.len(), 3, "Query point must have 3 dimensions");
+
+        let mut matches = Vec::new();
+        let _ = self.visit_within(query_point, radius, |i, _| {
+            matches.push(i);
+            ControlFlow::Continue(())
+        });
+
+        if replacements.dim() != (matches.len(), 3) {
+            return Err(DimensionMismatch { expected: (matches.len(), 3), actual: replacements.dim() }.into());
+        }
+        for (row, replacement) in replacements.rows().into_iter().enumerate() {
+            let inside = (0..3).all(|j| {
+                let bin = ((replacement[j] - self.grid_origin[j]) / self.bin_widths[j]).floor();
+                bin >= 0.0 && bin < self.bin_shape[j] as f64
+            });
+            if !inside {
+                let point = [replacement[0], replacement[1], replacement[2]];
+                return Err(SwapError::OutsideGrid { row, point });
+            }
+        }
+
+        for (&i, replacement) in matches.iter().zip(replacements.rows()) {
+            let old_bin = self.bin_of(&self.points.row(i)).map(|b| b as usize);
+            let new_bin = self.bin_of(&replacement).map(|b| b as usize);
+
+            self.points.row_mut(i).assign(&replacement);
+            self.original_points
+                .row_mut(self.original_indices[i] as usize)
+                .assign(&replacement);
+            self.found_indices_buffer.push(i as i64);
+
+            if old_bin != new_bin {
+                self.relink(i, old_bin, new_bin);
+            }
+        }
+
+        Ok(matches.len())
+    }
+
+    /// Move a point from one bin's linked list to another's, and in the reset backups
+    ///
+    /// Removed points aren't in the live lists, so only their backup entry moves.
+    pub(super) fn relink(&mut self, sorted_idx: usize, old_bin: [usize; 3], new_bin: [usize; 3]) {
+        if !self.is_removed(sorted_idx) {
+            move_between_lists(&mut self.first_member, &mut self.next_member, sorted_idx, old_bin, new_bin);
+        }
+        if let (Some(first_member), Some(next_member)) = (&mut self.original_first_member, &mut self.original_next_member) {
+            move_between_lists(first_member, next_member, sorted_idx, old_bin, new_bin);
+        }
+    }
+}
+
+/// Move a sorted index from one bin's linked list to another's
+///
+/// Lists run from the highest sorted index down, and the index is inserted where it
+/// keeps its new list in that order.
+fn move_between_lists(
+    first_member: &mut Array3<i64>,
+    next_member: &mut Array1<i64>,
+    sorted_idx: usize,
+    old_bin: [usize; 3],
+    new_bin: [usize; 3],
+) {
+    let target = sorted_idx as i64;
+
+    let mut prev: i64 = -1;
+    let mut i = first_member[old_bin];
+    while i != target {
+        prev = i;
+        i = next_member[i as usize];
+    }
+    if prev == -1 {
+        first_member[old_bin] = next_member[sorted_idx];
+    } else {
+        next_member[prev as usize] = next_member[sorted_idx];
+    }
+
+    let mut prev: i64 = -1;
+    let mut i = first_member[new_bin];
+    while i > target {
+        prev = i;
+        i = next_member[i as usize];
+    }
+    next_member[sorted_idx] = i;
+    if prev == -1 {
+        first_member[new_bin] = target;
+    } else {
+        next_member[prev as usize] = target;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    #[test]
+    fn test_radius_search_and_swap() {
+        let points = array![
+            [0.5, 0.5, 0.5],
+            [0.6, 0.5, 0.5],
+            [3.5, 3.5, 3.5],
+            [2.5, 0.5, 0.5],
+        ];
+        let bin_widths = array![1.0, 1.0, 1.0];
+        let mut point_bin = PointBin3D::new(points, bin_widths);
+
+        // Wrong number of replacements leaves everything untouched
+        let err = point_bin
+            .radius_search_and_swap(&array![0.5, 0.5, 0.5].view(), 0.2, array![[1.0, 1.0, 1.0]])
+            .unwrap_err();
+        assert_eq!(err, SwapError::Shape(DimensionMismatch { expected: (2, 3), actual: (1, 3) }));
+        assert_eq!(point_bin.found_count(), 0);
+
+        let swapped = point_bin
+            .radius_search_and_swap(
+                &array![0.5, 0.5, 0.5].view(),
+                0.2,
+                array![[3.4, 3.4, 3.4], [0.7, 0.5, 0.5]],
+            )
+            .unwrap();
+        assert_eq!(swapped, 2);
+        assert_eq!(point_bin.remaining_count(), 4);
+
+        // Rows went to the matches in found order
+        assert_eq!(point_bin.found_indices(), array![1, 0]);
+        assert_eq!(point_bin.original_points().row(1), array![3.4, 3.4, 3.4]);
+        assert_eq!(point_bin.original_points().row(0), array![0.7, 0.5, 0.5]);
+
+        // The moved point is found at its new location, also after a reset
+        for _ in 0..2 {
+            point_bin.reset();
+            point_bin.radius_search(&array![3.5, 3.5, 3.5].view(), 0.5);
+            assert_eq!(point_bin.found_indices_sorted_unique(), array![1, 2]);
+        }
+
+        let mut n_members = 0;
+        point_bin.reset();
+        point_bin.for_each_bin(|_, _, _, members| {
+            assert!(members.windows(2).all(|w| w[0] < w[1]));
+            n_members += members.len();
+        });
+        assert_eq!(n_members, 4);
+
+        // Point 2 now sorts after point 3 but sits in an earlier bin
+        point_bin
+            .radius_search_and_swap(&array![3.5, 3.5, 3.5].view(), 0.05, array![[1.9, 0.5, 0.5]])
+            .unwrap();
+        assert_eq!(point_bin.radius_pairs(0.7), vec![(2, 3)]);
+    }
+
+    #[test]
+    fn test_swap_rejects_replacements_outside_grid() {
+        let points = array![
+            [0.5, 0.5, 0.5],
+            [0.6, 0.5, 0.5],
+            [3.5, 3.5, 3.5],
+            [2.5, 0.5, 0.5],
+        ];
+        let mut point_bin = PointBin3D::new(points.clone(), array![1.0, 1.0, 1.0]);
+        let err = point_bin
+            .radius_search_and_swap(
+                &array![0.5, 0.5, 0.5].view(),
+                0.2,
+                array![[1.0, 1.0, 1.0], [-5.0, 0.5, 0.5]],
+            )
+            .unwrap_err();
+        assert_eq!(err, SwapError::OutsideGrid { row: 1, point: [-5.0, 0.5, 0.5] });
+
+        // Nothing moved
+        assert_eq!(point_bin.original_points(), &points);
+        assert_eq!(point_bin.found_count(), 0);
+        assert_eq!(point_bin.radius_query(&array![0.5, 0.5, 0.5].view(), 0.2).len(), 2);
+
+        let err = point_bin
+            .radius_search_and_swap(&array![3.5, 3.5, 3.5].view(), 0.1, array![[4.0, 0.5, f64::NAN]])
+            .unwrap_err();
+        assert!(matches!(err, SwapError::OutsideGrid { row: 0, .. }));
+    }
+}
@@ -0,0 +1,326 @@
+//! Reader for the Potree 1.7 binary point cloud format
+//!
+//! A Potree dataset is a directory holding `cloud.js` metadata and an octree of
+//! node files under `<octreeDir>/r/`. Each node file packs fixed-size point records
+//! whose position is stored as three little-endian `u32` values, scaled by `scale`
+//! and offset by the minimum corner of the node's bounding box.
+
+use std::ffi::OsStr;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use ndarray::{Array1, Array2};
+
+use crate::json::{self, JsonValue};
+use crate::{BucketError, PointBin3D};
+
+/// Errors that can occur while reading a Potree dataset
+#[derive(Debug)]
+pub enum PotreeError {
+    /// Reading a metadata or node file failed
+    Io(io::Error),
+    /// `cloud.js` is not valid JSON or lacks a required field
+    InvalidMetadata(String),
+    /// The dataset uses a feature this reader does not handle
+    Unsupported(String),
+    /// A node file's size or name does not match the metadata
+    InvalidNode(String),
+    /// The bin widths were rejected by `PointBin3D::try_new`
+    InvalidInput(BucketError),
+}
+
+impl fmt::Display for PotreeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PotreeError::Io(err) => write!(f, "I/O error: {}", err),
+            PotreeError::InvalidMetadata(msg) => write!(f, "Invalid cloud.js: {}", msg),
+            PotreeError::Unsupported(msg) => write!(f, "Unsupported Potree dataset: {}", msg),
+            PotreeError::InvalidNode(msg) => write!(f, "Invalid Potree node: {}", msg),
+            PotreeError::InvalidInput(err) => write!(f, "Invalid input: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for PotreeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PotreeError::Io(err) => Some(err),
+            PotreeError::InvalidInput(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for PotreeError {
+    fn from(err: io::Error) -> Self {
+        PotreeError::Io(err)
+    }
+}
+
+impl From<BucketError> for PotreeError {
+    fn from(err: BucketError) -> Self {
+        PotreeError::InvalidInput(err)
+    }
+}
+
+/// Size in bytes of each Potree 1.7 point attribute
+fn attribute_size(name: &str) -> Option<usize> {
+    let size = match name {
+        "POSITION_CARTESIAN" => 12,
+        "COLOR_PACKED" | "RGBA_PACKED" => 4,
+        "NORMAL_FLOATS" | "NORMAL" => 12,
+        "FILLER" | "CLASSIFICATION" | "RETURN_NUMBER" | "NUMBER_OF_RETURNS" => 1,
+        "INTENSITY" | "SOURCE_ID" | "NORMAL_SPHEREMAPPED" | "NORMAL_OCT16" => 2,
+        "INDICES" | "SPACING" => 4,
+        "GPS_TIME" => 8,
+        _ => return None,
+    };
+    Some(size)
+}
+
+/// Layout information read from `cloud.js`
+struct PotreeMetadata {
+    octree_dir: PathBuf,
+    bounding_min: [f64; 3],
+    bounding_max: [f64; 3],
+    scale: f64,
+    point_size: usize,
+    position_offset: usize,
+}
+
+impl PotreeMetadata {
+    fn read(root: &Path) -> Result<Self, PotreeError> {
+        let text = fs::read_to_string(root.join("cloud.js"))?;
+        let doc = json::parse(&text).map_err(|err| PotreeError::InvalidMetadata(err.to_string()))?;
+
+        let field = |name: &str| {
+            doc.get(name)
+                .ok_or_else(|| PotreeError::InvalidMetadata(format!("missing field '{}'", name)))
+        };
+        let number = |value: &JsonValue, name: &str| {
+            value
+                .as_f64()
+                .ok_or_else(|| PotreeError::InvalidMetadata(format!("field '{}' must be a number", name)))
+        };
+
+        if let Some(version) = doc.get("version").and_then(JsonValue::as_str) {
+            if version != "1.7" {
+                return Err(PotreeError::Unsupported(format!("version {} (only 1.7 is supported)", version)));
+            }
+        }
+
+        let octree_dir = field("octreeDir")?
+            .as_str()
+            .ok_or_else(|| PotreeError::InvalidMetadata("field 'octreeDir' must be a string".into()))?;
+
+        let bbox = field("boundingBox")?;
+        let mut bounding_min = [0.0; 3];
+        let mut bounding_max = [0.0; 3];
+        for (j, axis) in ["x", "y", "z"].iter().enumerate() {
+            let lower = format!("l{}", axis);
+            let upper = format!("u{}", axis);
+            bounding_min[j] = number(bbox.get(&lower).unwrap_or(&JsonValue::Null), &lower)?;
+            bounding_max[j] = number(bbox.get(&upper).unwrap_or(&JsonValue::Null), &upper)?;
+        }
+
+        let scale = number(field("scale")?, "scale")?;
+
+        let attributes = match field("pointAttributes")? {
+            JsonValue::Array(items) => items,
+            JsonValue::String(format) => {
+                return Err(PotreeError::Unsupported(format!("'{}' point data (only binary is supported)", format)));
+            }
+            _ => {
+                return Err(PotreeError::InvalidMetadata("field 'pointAttributes' must be an array".into()));
+            }
+        };
+
+        let mut point_size = 0;
+        let mut position_offset = None;
+        for attribute in attributes {
+            let name = attribute.as_str().ok_or_else(|| {
+                PotreeError::InvalidMetadata("point attributes must be strings".into())
+            })?;
+            if name == "POSITION_CARTESIAN" {
+                position_offset = Some(point_size);
+            }
+            point_size += attribute_size(name)
+                .ok_or_else(|| PotreeError::Unsupported(format!("point attribute '{}'", name)))?;
+        }
+        let position_offset = position_offset.ok_or_else(|| {
+            PotreeError::InvalidMetadata("point attributes lack POSITION_CARTESIAN".into())
+        })?;
+
+        Ok(Self {
+            octree_dir: root.join(octree_dir),
+            bounding_min,
+            bounding_max,
+            scale,
+            point_size,
+            position_offset,
+        })
+    }
+
+    /// Minimum corner of a node's bounding box, derived from its name (e.g. `r062`)
+    fn node_min_corner(&self, name: &str) -> Result<[f64; 3], PotreeError> {
+        let mut min = self.bounding_min;
+        let mut max = self.bounding_max;
+
+        for digit in name.chars().skip(1) {
+            let child = digit
+                .to_digit(8)
+                .ok_or_else(|| PotreeError::InvalidNode(format!("bad node name '{}'", name)))?;
+            for (j, bit) in [4, 2, 1].into_iter().enumerate() {
+                let half = 0.5 * (max[j] - min[j]);
+                if child & bit != 0 {
+                    min[j] += half;
+                } else {
+                    max[j] -= half;
+                }
+            }
+        }
+
+        Ok(min)
+    }
+}
+
+/// Recursively collect `(node name, path)` for every `.bin` node file below a directory
+fn collect_node_files(dir: &Path, nodes: &mut Vec<(String, PathBuf)>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_node_files(&path, nodes)?;
+        } else if path.extension() == Some(OsStr::new("bin")) {
+            if let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) {
+                if name.starts_with('r') {
+                    nodes.push((name.to_string(), path.clone()));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+impl PointBin3D {
+    /// Build a PointBin3D from a Potree 1.7 dataset
+    ///
+    /// Reads `cloud.js` and every binary node file, concatenating the decoded
+    /// coordinates level by level (root first). Attributes other than position are
+    /// skipped. LAS/LAZ node data is not supported.
+    ///
+    /// # Arguments
+    /// * `root` - Directory containing `cloud.js`
+    /// * `bin_widths` - 1D array of shape (3,) with bin widths for x, y, z
+    ///
+    /// # Returns
+    /// A new PointBin3D over all points of the dataset
+    pub fn from_potree<P: AsRef<Path>>(root: P, bin_widths: Array1<f64>) -> Result<Self, PotreeError> {
+        let metadata = PotreeMetadata::read(root.as_ref())?;
+
+        let mut nodes = Vec::new();
+        collect_node_files(&metadata.octree_dir, &mut nodes)?;
+        nodes.sort_by(|(a, _), (b, _)| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+
+        let mut coords: Vec<f64> = Vec::new();
+        for (name, path) in &nodes {
+            let bytes = fs::read(path)?;
+            if bytes.len() % metadata.point_size != 0 {
+                return Err(PotreeError::InvalidNode(format!(
+                    "'{}' has {} bytes, not a multiple of the {}-byte point size",
+                    name,
+                    bytes.len(),
+                    metadata.point_size
+                )));
+            }
+
+            let node_min = metadata.node_min_corner(name)?;
+            for record in bytes.chunks_exact(metadata.point_size) {
+                for (j, &offset) in node_min.iter().enumerate() {
+                    let start = metadata.position_offset + 4 * j;
+                    let raw = u32::from_le_bytes(record[start..start + 4].try_into().unwrap());
+                    coords.push(raw as f64 * metadata.scale + offset);
+                }
+            }
+        }
+
+        if coords.is_empty() {
+            return Err(PotreeError::InvalidNode("dataset contains no points".into()));
+        }
+
+        let n_points = coords.len() / 3;
+        let points = Array2::from_shape_vec((n_points, 3), coords)
+            .expect("coordinate buffer holds whole points");
+
+        Ok(Self::try_new(points, bin_widths)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+    use ndarray::array;
+
+    fn write_node(path: &Path, positions: &[[u32; 3]]) {
+        let mut bytes = Vec::new();
+        for position in positions {
+            for value in position {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+            // COLOR_PACKED attribute
+            bytes.extend_from_slice(&[255, 0, 0, 255]);
+        }
+        fs::write(path, bytes).unwrap();
+    }
+
+    #[test]
+    fn test_from_potree() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("cloud.js"),
+            r#"{
+                "version": "1.7",
+                "octreeDir": "data",
+                "boundingBox": {"lx": 0, "ly": 0, "lz": 0, "ux": 8, "uy": 8, "uz": 8},
+                "pointAttributes": ["POSITION_CARTESIAN", "COLOR_PACKED"],
+                "scale": 0.01,
+                "hierarchyStepSize": 5
+            }"#,
+        )
+        .unwrap();
+        let node_dir = dir.path().join("data").join("r");
+        fs::create_dir_all(&node_dir).unwrap();
+        write_node(&node_dir.join("r.bin"), &[[100, 200, 300]]);
+        // Child 7 covers the upper octant starting at (4, 4, 4)
+        write_node(&node_dir.join("r7.bin"), &[[50, 0, 100], [0, 0, 0]]);
+
+        let point_bin = PointBin3D::from_potree(dir.path(), array![1.0, 1.0, 1.0]).unwrap();
+
+        let points = point_bin.original_points();
+        let expected = array![
+            [1.0, 2.0, 3.0],
+            [4.5, 4.0, 5.0],
+            [4.0, 4.0, 4.0],
+        ];
+        assert_eq!(points.dim(), (3, 3));
+        for (p, e) in points.iter().zip(expected.iter()) {
+            assert_abs_diff_eq!(p, e, epsilon = 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_from_potree_rejects_laz() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("cloud.js"),
+            r#"{"octreeDir": "data", "boundingBox": {"lx": 0, "ly": 0, "lz": 0, "ux": 1, "uy": 1, "uz": 1},
+                "pointAttributes": "LAZ", "scale": 0.01}"#,
+        )
+        .unwrap();
+
+        let result = PointBin3D::from_potree(dir.path(), array![1.0, 1.0, 1.0]);
+        assert!(matches!(result, Err(PotreeError::Unsupported(_))));
+    }
+}
@@ -1,9 +1,11 @@
 //! Python bindings for the bucket-search library
 
-use ndarray::{Array1, Array2};
-use numpy::{IntoPyArray, PyArray1, PyArray2, PyReadonlyArray1, PyReadonlyArray2};
+mod arrow;
+
+use ndarray::Array2;
+use numpy::{IntoPyArray, PyArray1, PyArray2, PyArray3, PyReadonlyArray1, PyReadonlyArray2};
 use pyo3::prelude::*;
-use pyo3::types::PyModule;
+use pyo3::types::{PyBytes, PyDict, PyModule, PyType};
 
 use crate::PointBin3D as RustPointBin3D;
 
@@ -49,33 +51,72 @@ impl PyPointBin3D {
     /// -------
     /// PointBin3D
     ///     New spatial indexing structure
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     If points don't have 3 columns or are empty, or bin_widths doesn't have 3
+    ///     positive elements
     #[new]
     pub fn new(
         points: PyReadonlyArray2<f64>,
         bin_widths: PyReadonlyArray1<f64>,
     ) -> PyResult<Self> {
-        let points_array = points.as_array();
-        let bin_widths_array = bin_widths.as_array();
-
-        // Validate dimensions
-        if points_array.ncols() != 3 {
-            return Err(pyo3::exceptions::PyValueError::new_err(
-                "Points must have exactly 3 columns (x, y, z)",
-            ));
-        }
+        let inner = RustPointBin3D::try_new(points.as_array().to_owned(), bin_widths.as_array().to_owned())
+            .map_err(|err| pyo3::exceptions::PyValueError::new_err(err.to_string()))?;
 
-        if bin_widths_array.len() != 3 {
-            return Err(pyo3::exceptions::PyValueError::new_err(
-                "Bin widths must have exactly 3 elements",
-            ));
-        }
-
-        // Convert to owned arrays
-        let points_owned: Array2<f64> = points_array.to_owned();
-        let bin_widths_owned: Array1<f64> = bin_widths_array.to_owned();
+        Ok(PyPointBin3D { inner })
+    }
 
-        let inner = RustPointBin3D::new(points_owned, bin_widths_owned);
+    /// Create a PointBin3D from three columns of a pyarrow Table
+    ///
+    /// The columns are read through the Arrow C data interface, chunk by chunk, into
+    /// one buffer per column without going through pandas or numpy, and then
+    /// interleaved into the structure's points. Numeric columns that aren't float64 are
+    /// cast first.
+    ///
+    /// Parameters
+    /// ----------
+    /// table : pyarrow.Table
+    ///     Table holding the coordinates, e.g. from ``pyarrow.parquet.read_table``
+    /// x_col, y_col, z_col : str
+    ///     Names of the x, y and z columns
+    /// bin_widths : numpy.ndarray
+    ///     1D array of shape (3,) with bin widths for x, y, z
+    ///
+    /// Returns
+    /// -------
+    /// PointBin3D
+    ///     New spatial indexing structure, with one point per table row
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     If a column contains nulls, the table is empty, or ``bin_widths`` doesn't
+    ///     have 3 positive elements
+    /// TypeError
+    ///     If pyarrow can't cast a column to float64
+    /// KeyError
+    ///     If a column doesn't exist
+    #[classmethod]
+    pub fn from_arrow(
+        _cls: &PyType,
+        table: &PyAny,
+        x_col: &str,
+        y_col: &str,
+        z_col: &str,
+        bin_widths: PyReadonlyArray1<f64>,
+    ) -> PyResult<Self> {
+        let columns = [
+            arrow::read_float64_column(table, x_col)?,
+            arrow::read_float64_column(table, y_col)?,
+            arrow::read_float64_column(table, z_col)?,
+        ];
+        let n_points = columns[0].len();
+        let points = Array2::from_shape_fn((n_points, 3), |(i, j)| columns[j][i]);
 
+        let inner = RustPointBin3D::try_new(points, bin_widths.as_array().to_owned())
+            .map_err(|err| pyo3::exceptions::PyValueError::new_err(err.to_string()))?;
         Ok(PyPointBin3D { inner })
     }
 
@@ -109,6 +150,33 @@ impl PyPointBin3D {
         indices.into_pyarray(py)
     }
 
+    /// Get the coordinates of the found points as separate columns
+    ///
+    /// Returns
+    /// -------
+    /// tuple of numpy.ndarray
+    ///     ``(x, y, z)``, each a 1D array in the same order as ``found_indices()``
+    pub fn found_points_columns<'py>(
+        &self,
+        py: Python<'py>,
+    ) -> (&'py PyArray1<f64>, &'py PyArray1<f64>, &'py PyArray1<f64>) {
+        let (x, y, z) = self.inner.found_points_columns();
+        (x.into_pyarray(py), y.into_pyarray(py), z.into_pyarray(py))
+    }
+
+    /// Get the axis-aligned bounding box of the found points
+    ///
+    /// Returns
+    /// -------
+    /// tuple of numpy.ndarray or None
+    ///     ``(min_corner, max_corner)``, each a 1D array of shape (3,), or None if no
+    ///     points have been found
+    pub fn found_bounds<'py>(&self, py: Python<'py>) -> Option<(&'py PyArray1<f64>, &'py PyArray1<f64>)> {
+        self.inner
+            .found_bounds()
+            .map(|(min_corner, max_corner)| (min_corner.into_pyarray(py), max_corner.into_pyarray(py)))
+    }
+
     /// Reset the structure for a fresh search
     ///
     /// Restores all points and clears the found indices buffer.
@@ -156,6 +224,127 @@ impl PyPointBin3D {
         self.inner.origin().clone().into_pyarray(py)
     }
 
+    /// Get the occupancy of every bin as a boolean voxel grid
+    ///
+    /// Returns
+    /// -------
+    /// numpy.ndarray
+    ///     3D bool array with the bin shape, True where a point not yet removed by a
+    ///     search lies in the bin
+    pub fn to_occupancy_grid<'py>(&self, py: Python<'py>) -> &'py PyArray3<bool> {
+        self.inner.to_occupancy_grid().into_pyarray(py)
+    }
+
+    /// Get the bin widths
+    ///
+    /// Returns
+    /// -------
+    /// numpy.ndarray
+    ///     1D array of shape (3,) with bin widths for x, y, z
+    pub fn bin_widths<'py>(&self, py: Python<'py>) -> &'py PyArray1<f64> {
+        self.inner.bin_widths().clone().into_pyarray(py)
+    }
+
+    /// Get the origin, bin widths, bin shape and maximum corner of the grid
+    ///
+    /// Returns
+    /// -------
+    /// dict
+    ///     Dictionary with keys ``origin``, ``bin_widths``, ``bin_shape`` and
+    ///     ``max_corner``, each a 1D array of shape (3,)
+    pub fn grid_metadata<'py>(&self, py: Python<'py>) -> PyResult<&'py PyDict> {
+        let metadata = self.inner.grid_metadata();
+        let dict = PyDict::new(py);
+        dict.set_item("origin", metadata.origin.into_pyarray(py))?;
+        dict.set_item("bin_widths", metadata.bin_widths.into_pyarray(py))?;
+        dict.set_item("bin_shape", metadata.bin_shape.into_pyarray(py))?;
+        dict.set_item("max_corner", metadata.max_corner.into_pyarray(py))?;
+        Ok(dict)
+    }
+
+    /// Occupancy statistics over every bin of the grid
+    ///
+    /// Returns
+    /// -------
+    /// dict
+    ///     Dictionary with keys ``total_bins``, ``occupied_bins``, ``empty_bins``,
+    ///     ``max_occupancy``, ``min_occupancy_nonempty``, ``mean_occupancy``,
+    ///     ``std_occupancy`` and ``histogram``, a list of ``(occupancy, n_bins)`` tuples
+    #[getter]
+    pub fn bin_stats<'py>(&self, py: Python<'py>) -> PyResult<&'py PyDict> {
+        let stats = self.inner.bin_stats();
+        let dict = PyDict::new(py);
+        dict.set_item("total_bins", stats.total_bins)?;
+        dict.set_item("occupied_bins", stats.occupied_bins)?;
+        dict.set_item("empty_bins", stats.empty_bins)?;
+        dict.set_item("max_occupancy", stats.max_occupancy)?;
+        dict.set_item("min_occupancy_nonempty", stats.min_occupancy_nonempty)?;
+        dict.set_item("mean_occupancy", stats.mean_occupancy)?;
+        dict.set_item("std_occupancy", stats.std_occupancy)?;
+        dict.set_item("histogram", stats.histogram)?;
+        Ok(dict)
+    }
+
+    /// Number of bins holding at least one point not yet removed by a search
+    ///
+    /// Returns
+    /// -------
+    /// int
+    ///     Number of occupied bins
+    pub fn occupied_bin_count(&self) -> usize {
+        self.inner.occupied_bin_count()
+    }
+
+    /// Fraction of the grid's bins that hold at least one point
+    ///
+    /// A very low value means the dense grid spends most of its memory on empty bins.
+    ///
+    /// Returns
+    /// -------
+    /// float
+    ///     Occupied bins divided by the total number of bins
+    pub fn grid_utilization(&self) -> f64 {
+        self.inner.grid_utilization()
+    }
+
+    /// Support pickling, e.g. to pass the structure to ``multiprocessing`` workers
+    ///
+    /// Returns ``(PointBin3D, (points, bin_widths), state)``. Unpickling builds a new
+    /// structure from the points and bin widths, then ``__setstate__`` restores the
+    /// grid and the search state (removed points and found indices) from ``state``.
+    pub fn __reduce__<'py>(
+        &self,
+        py: Python<'py>,
+    ) -> (&'py PyType, (&'py PyArray2<f64>, &'py PyArray1<f64>), &'py PyBytes) {
+        (
+            py.get_type::<Self>(),
+            (self.original_points(py), self.bin_widths(py)),
+            self.__getstate__(py),
+        )
+    }
+
+    /// Binary snapshot of the structure, including its search state
+    ///
+    /// Returns
+    /// -------
+    /// bytes
+    ///     The snapshot written by the Rust ``to_bytes``
+    pub fn __getstate__<'py>(&self, py: Python<'py>) -> &'py PyBytes {
+        PyBytes::new(py, &self.inner.to_bytes())
+    }
+
+    /// Restore the structure from a ``__getstate__`` snapshot
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///     If ``state`` isn't a valid snapshot
+    pub fn __setstate__(&mut self, state: &PyBytes) -> PyResult<()> {
+        self.inner = RustPointBin3D::from_bytes(state.as_bytes())
+            .map_err(|err| pyo3::exceptions::PyValueError::new_err(err.to_string()))?;
+        Ok(())
+    }
+
     fn __repr__(&self) -> String {
         format!(
             "PointBin3D(n_points={}, found_count={})",
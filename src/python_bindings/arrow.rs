@@ -0,0 +1,130 @@
+//! Reading float64 columns from pyarrow through the Arrow C data interface
+//!
+//! Only the two C structs of the interface are needed, so they are declared here
+//! rather than pulling in an Arrow crate. pyarrow fills them with `_export_to_c`, the
+//! values are copied straight out of the Arrow buffers, and the structs are released.
+//! See <https://arrow.apache.org/docs/format/CDataInterface.html>.
+
+use std::ffi::{c_char, c_void, CStr};
+
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+
+/// `struct ArrowSchema` of the C data interface
+#[repr(C)]
+struct ArrowSchema {
+    format: *const c_char,
+    name: *const c_char,
+    metadata: *const c_char,
+    flags: i64,
+    n_children: i64,
+    children: *mut *mut ArrowSchema,
+    dictionary: *mut ArrowSchema,
+    release: Option<unsafe extern "C" fn(*mut ArrowSchema)>,
+    private_data: *mut c_void,
+}
+
+/// `struct ArrowArray` of the C data interface
+#[repr(C)]
+struct ArrowArray {
+    length: i64,
+    null_count: i64,
+    offset: i64,
+    n_buffers: i64,
+    n_children: i64,
+    buffers: *mut *const c_void,
+    children: *mut *mut ArrowArray,
+    dictionary: *mut ArrowArray,
+    release: Option<unsafe extern "C" fn(*mut ArrowArray)>,
+    private_data: *mut c_void,
+}
+
+/// An array exported by pyarrow, released when dropped
+struct ExportedArray {
+    array: Box<ArrowArray>,
+    schema: Box<ArrowSchema>,
+}
+
+impl ExportedArray {
+    /// Export a `pyarrow.Array` into freshly allocated structs
+    fn export(array: &PyAny) -> PyResult<Self> {
+        // SAFETY: all-zero is a valid empty struct: null pointers and no release callback
+        let mut exported = ExportedArray {
+            array: Box::new(unsafe { std::mem::zeroed() }),
+            schema: Box::new(unsafe { std::mem::zeroed() }),
+        };
+        let array_addr = &mut *exported.array as *mut ArrowArray as usize;
+        let schema_addr = &mut *exported.schema as *mut ArrowSchema as usize;
+        array.call_method1("_export_to_c", (array_addr, schema_addr))?;
+        Ok(exported)
+    }
+
+    /// Append the values of an exported float64 array to `out`
+    fn extend_f64(&self, out: &mut Vec<f64>) -> PyResult<()> {
+        if self.schema.release.is_none() || self.array.release.is_none() {
+            return Err(PyValueError::new_err("pyarrow did not export the column"));
+        }
+        // SAFETY: an exported schema has a valid NUL-terminated format string
+        let format = unsafe { CStr::from_ptr(self.schema.format) };
+        if format.to_bytes() != b"g" {
+            return Err(PyTypeError::new_err("Column must be float64"));
+        }
+
+        let array = &*self.array;
+        if array.length == 0 {
+            return Ok(());
+        }
+        // SAFETY: a primitive array has two buffers, validity then values
+        let (validity, values) = unsafe { (*array.buffers, *array.buffers.add(1)) };
+        if array.null_count != 0 && !validity.is_null() {
+            return Err(PyValueError::new_err("Column must not contain nulls"));
+        }
+
+        // SAFETY: the values buffer holds at least offset + length float64s
+        let values = unsafe {
+            std::slice::from_raw_parts((values as *const f64).add(array.offset as usize), array.length as usize)
+        };
+        out.extend_from_slice(values);
+        Ok(())
+    }
+}
+
+impl Drop for ExportedArray {
+    fn drop(&mut self) {
+        // SAFETY: release callbacks are set by the producer and may be called once
+        unsafe {
+            if let Some(release) = self.array.release {
+                release(&mut *self.array);
+            }
+            if let Some(release) = self.schema.release {
+                release(&mut *self.schema);
+            }
+        }
+    }
+}
+
+/// Read a whole column of a `pyarrow.Table` as float64 values
+///
+/// Chunks are read one by one without combining them first. Numeric columns of other
+/// types are cast to float64 by pyarrow, with a failed cast raised as `TypeError`;
+/// columns with nulls are rejected.
+pub(super) fn read_float64_column(table: &PyAny, name: &str) -> PyResult<Vec<f64>> {
+    let column = table.call_method1("column", (name,))?;
+    let chunks: Vec<&PyAny> = if column.hasattr("chunks")? {
+        column.getattr("chunks")?.extract()?
+    } else {
+        vec![column]
+    };
+
+    let mut values = Vec::with_capacity(column.len()?);
+    for mut chunk in chunks {
+        if chunk.getattr("type")?.str()?.to_str()? != "double" {
+            // pyarrow raises ArrowInvalid or ArrowNotImplementedError, reported as a TypeError
+            chunk = chunk
+                .call_method1("cast", ("float64",))
+                .map_err(|err| PyTypeError::new_err(format!("Column '{}' can't be cast to float64: {}", name, err)))?;
+        }
+        ExportedArray::export(chunk)?.extend_f64(&mut values)?;
+    }
+    Ok(values)
+}
@@ -0,0 +1,467 @@
+//! Reader for `sensor_msgs/PointCloud2` messages stored in ROS 1 bag files
+//!
+//! A version 2.0 bag is a sequence of records, each made of a length-prefixed header
+//! of `name=value` fields followed by a length-prefixed data block. Messages live in
+//! chunk records and refer to the topic they were published on through connection
+//! records. Only uncompressed chunks are handled.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use ndarray::{Array1, Array2};
+
+use crate::{BucketError, PointBin3D};
+
+const BAG_MAGIC: &[u8] = b"#ROSBAG V2.0\n";
+
+const OP_MESSAGE_DATA: u8 = 0x02;
+const OP_CHUNK: u8 = 0x05;
+const OP_CONNECTION: u8 = 0x07;
+
+const POINTCLOUD2_TYPE: &[u8] = b"sensor_msgs/PointCloud2";
+
+/// Errors that can occur while reading a point cloud from a ROS bag
+#[derive(Debug)]
+pub enum RosBagError {
+    /// Reading the bag file failed
+    Io(io::Error),
+    /// The file is not a well-formed version 2.0 bag
+    InvalidBag(String),
+    /// The bag uses a feature this reader does not handle
+    Unsupported(String),
+    /// No connection in the bag publishes on the requested topic
+    TopicNotFound(String),
+    /// The topic has fewer messages than the requested frame index
+    FrameOutOfRange { frame_index: usize, n_frames: usize },
+    /// A PointCloud2 message could not be decoded
+    InvalidMessage(String),
+    /// The bin widths were rejected by `PointBin3D::try_new`
+    InvalidInput(BucketError),
+}
+
+impl fmt::Display for RosBagError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RosBagError::Io(err) => write!(f, "I/O error: {}", err),
+            RosBagError::InvalidBag(msg) => write!(f, "Invalid ROS bag: {}", msg),
+            RosBagError::Unsupported(msg) => write!(f, "Unsupported ROS bag: {}", msg),
+            RosBagError::TopicNotFound(topic) => write!(f, "Topic '{}' not found in bag", topic),
+            RosBagError::FrameOutOfRange { frame_index, n_frames } => write!(
+                f,
+                "Frame index {} out of range for topic with {} messages",
+                frame_index, n_frames
+            ),
+            RosBagError::InvalidMessage(msg) => write!(f, "Invalid PointCloud2 message: {}", msg),
+            RosBagError::InvalidInput(err) => write!(f, "Invalid input: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for RosBagError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RosBagError::Io(err) => Some(err),
+            RosBagError::InvalidInput(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for RosBagError {
+    fn from(err: io::Error) -> Self {
+        RosBagError::Io(err)
+    }
+}
+
+impl From<BucketError> for RosBagError {
+    fn from(err: BucketError) -> Self {
+        RosBagError::InvalidInput(err)
+    }
+}
+
+/// Little-endian cursor over a byte slice
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let end = self.pos.checked_add(len)?;
+        let slice = self.bytes.get(self.pos..end)?;
+        self.pos = end;
+        Some(slice)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|b| b[0])
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4).map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+    }
+
+    /// A `u32` length followed by that many bytes
+    fn block(&mut self) -> Option<&'a [u8]> {
+        let len = self.u32()? as usize;
+        self.take(len)
+    }
+}
+
+/// `(name, value)` pairs of a record header or connection header
+type Fields<'a> = Vec<(&'a [u8], &'a [u8])>;
+
+/// One bag record: its header fields and data block
+struct Record<'a> {
+    fields: Fields<'a>,
+    data: &'a [u8],
+}
+
+impl<'a> Record<'a> {
+    fn read(reader: &mut Reader<'a>) -> Result<Self, RosBagError> {
+        let truncated = || RosBagError::InvalidBag("truncated record".into());
+        let header = reader.block().ok_or_else(truncated)?;
+        let data = reader.block().ok_or_else(truncated)?;
+        Ok(Self { fields: parse_fields(header)?, data })
+    }
+
+    fn field(&self, name: &str) -> Option<&'a [u8]> {
+        self.fields
+            .iter()
+            .find(|(key, _)| *key == name.as_bytes())
+            .map(|(_, value)| *value)
+    }
+
+    fn op(&self) -> Result<u8, RosBagError> {
+        match self.field("op") {
+            Some([op]) => Ok(*op),
+            _ => Err(RosBagError::InvalidBag("record without a valid 'op' field".into())),
+        }
+    }
+
+    fn conn(&self) -> Result<u32, RosBagError> {
+        self.field("conn")
+            .and_then(|value| value.try_into().ok())
+            .map(u32::from_le_bytes)
+            .ok_or_else(|| RosBagError::InvalidBag("record without a valid 'conn' field".into()))
+    }
+}
+
+/// Split a block of length-prefixed `name=value` fields
+fn parse_fields(block: &[u8]) -> Result<Fields<'_>, RosBagError> {
+    let mut reader = Reader::new(block);
+    let mut fields = Vec::new();
+    while !reader.is_empty() {
+        let field = reader
+            .block()
+            .ok_or_else(|| RosBagError::InvalidBag("truncated header field".into()))?;
+        let split = field
+            .iter()
+            .position(|&b| b == b'=')
+            .ok_or_else(|| RosBagError::InvalidBag("header field without '='".into()))?;
+        fields.push((&field[..split], &field[split + 1..]));
+    }
+    Ok(fields)
+}
+
+/// Collects the messages published on one topic, in file order
+struct TopicScan<'a> {
+    topic: &'a str,
+    connections: Vec<u32>,
+    messages: Vec<&'a [u8]>,
+}
+
+impl<'a> TopicScan<'a> {
+    fn visit(&mut self, record: &Record<'a>) -> Result<(), RosBagError> {
+        match record.op()? {
+            OP_CONNECTION if record.field("topic") == Some(self.topic.as_bytes()) => {
+                let conn = record.conn()?;
+                let message_type = parse_fields(record.data)?
+                    .into_iter()
+                    .find(|(key, _)| *key == b"type")
+                    .map(|(_, value)| value);
+                if message_type != Some(POINTCLOUD2_TYPE) {
+                    return Err(RosBagError::Unsupported(format!(
+                        "topic '{}' does not carry {} messages",
+                        self.topic,
+                        String::from_utf8_lossy(POINTCLOUD2_TYPE)
+                    )));
+                }
+                if !self.connections.contains(&conn) {
+                    self.connections.push(conn);
+                }
+            }
+            OP_MESSAGE_DATA if self.connections.contains(&record.conn()?) => {
+                self.messages.push(record.data);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Byte layout of one coordinate field within a point record
+struct CoordField {
+    offset: usize,
+    datatype: u8,
+}
+
+const FLOAT32: u8 = 7;
+const FLOAT64: u8 = 8;
+
+/// Decode the finite x, y, z coordinates of a serialized PointCloud2 message
+fn decode_pointcloud2(message: &[u8]) -> Result<Vec<f64>, RosBagError> {
+    let truncated = || RosBagError::InvalidMessage("message is truncated".into());
+    let mut reader = Reader::new(message);
+
+    // std_msgs/Header: seq, stamp (secs, nsecs), frame_id
+    reader.take(12).ok_or_else(truncated)?;
+    reader.block().ok_or_else(truncated)?;
+
+    let height = reader.u32().ok_or_else(truncated)? as usize;
+    let width = reader.u32().ok_or_else(truncated)? as usize;
+
+    let mut coords: [Option<CoordField>; 3] = [None, None, None];
+    let n_fields = reader.u32().ok_or_else(truncated)?;
+    for _ in 0..n_fields {
+        let name = reader.block().ok_or_else(truncated)?;
+        let offset = reader.u32().ok_or_else(truncated)? as usize;
+        let datatype = reader.u8().ok_or_else(truncated)?;
+        reader.u32().ok_or_else(truncated)?;
+
+        let axis = match name {
+            b"x" => 0,
+            b"y" => 1,
+            b"z" => 2,
+            _ => continue,
+        };
+        if datatype != FLOAT32 && datatype != FLOAT64 {
+            return Err(RosBagError::Unsupported(format!(
+                "field '{}' has datatype {} (only FLOAT32 and FLOAT64 are supported)",
+                String::from_utf8_lossy(name),
+                datatype
+            )));
+        }
+        coords[axis] = Some(CoordField { offset, datatype });
+    }
+    let [Some(x), Some(y), Some(z)] = coords else {
+        return Err(RosBagError::InvalidMessage("missing x, y or z field".into()));
+    };
+
+    let is_bigendian = reader.u8().ok_or_else(truncated)? != 0;
+    let point_step = reader.u32().ok_or_else(truncated)? as usize;
+    let row_step = reader.u32().ok_or_else(truncated)? as usize;
+    let data = reader.block().ok_or_else(truncated)?;
+
+    for field in [&x, &y, &z] {
+        let size = if field.datatype == FLOAT32 { 4 } else { 8 };
+        if field.offset + size > point_step {
+            return Err(RosBagError::InvalidMessage("field extends past point_step".into()));
+        }
+    }
+    if height > 0 && (row_step < width * point_step || data.len() < (height - 1) * row_step + width * point_step) {
+        return Err(RosBagError::InvalidMessage("data is smaller than height * row_step".into()));
+    }
+
+    let read = |record: &[u8], field: &CoordField| -> f64 {
+        let start = field.offset;
+        if field.datatype == FLOAT32 {
+            let bytes: [u8; 4] = record[start..start + 4].try_into().unwrap();
+            (if is_bigendian { f32::from_be_bytes(bytes) } else { f32::from_le_bytes(bytes) }) as f64
+        } else {
+            let bytes: [u8; 8] = record[start..start + 8].try_into().unwrap();
+            if is_bigendian { f64::from_be_bytes(bytes) } else { f64::from_le_bytes(bytes) }
+        }
+    };
+
+    let mut values = Vec::with_capacity(3 * height * width);
+    for row in 0..height {
+        for col in 0..width {
+            let start = row * row_step + col * point_step;
+            let record = &data[start..start + point_step];
+            let point = [read(record, &x), read(record, &y), read(record, &z)];
+            // Organized clouds mark missing returns with NaN
+            if point.iter().all(|v| v.is_finite()) {
+                values.extend_from_slice(&point);
+            }
+        }
+    }
+
+    Ok(values)
+}
+
+impl PointBin3D {
+    /// Build a PointBin3D from a `sensor_msgs/PointCloud2` message in a ROS bag
+    ///
+    /// Scans a version 2.0 bag for messages published on `topic` and decodes the
+    /// `frame_index`-th one, counting in file order. The `x`, `y` and `z` fields must be
+    /// FLOAT32 or FLOAT64; points with a non-finite coordinate are dropped. Bags with
+    /// compressed chunks are not supported.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the `.bag` file
+    /// * `topic` - Topic the point clouds were published on
+    /// * `frame_index` - Zero-based index of the message to load
+    /// * `bin_widths` - 1D array of shape (3,) with bin widths for x, y, z
+    ///
+    /// # Returns
+    /// A new PointBin3D over the points of the selected message
+    pub fn from_rosbag_pointcloud2<P: AsRef<Path>>(
+        path: P,
+        topic: &str,
+        frame_index: usize,
+        bin_widths: Array1<f64>,
+    ) -> Result<Self, RosBagError> {
+        let bytes = fs::read(path)?;
+        let body = bytes
+            .strip_prefix(BAG_MAGIC)
+            .ok_or_else(|| RosBagError::InvalidBag("missing '#ROSBAG V2.0' header line".into()))?;
+
+        let mut scan = TopicScan { topic, connections: Vec::new(), messages: Vec::new() };
+        let mut reader = Reader::new(body);
+        while !reader.is_empty() {
+            let record = Record::read(&mut reader)?;
+            if record.op()? != OP_CHUNK {
+                scan.visit(&record)?;
+                continue;
+            }
+
+            match record.field("compression") {
+                Some(b"none") => {}
+                Some(other) => {
+                    return Err(RosBagError::Unsupported(format!(
+                        "'{}' chunk compression (only uncompressed bags are supported)",
+                        String::from_utf8_lossy(other)
+                    )));
+                }
+                None => return Err(RosBagError::InvalidBag("chunk without 'compression' field".into())),
+            }
+            let mut chunk = Reader::new(record.data);
+            while !chunk.is_empty() {
+                scan.visit(&Record::read(&mut chunk)?)?;
+            }
+        }
+
+        if scan.connections.is_empty() {
+            return Err(RosBagError::TopicNotFound(topic.to_string()));
+        }
+        let message = scan.messages.get(frame_index).ok_or(RosBagError::FrameOutOfRange {
+            frame_index,
+            n_frames: scan.messages.len(),
+        })?;
+
+        let coords = decode_pointcloud2(message)?;
+        if coords.is_empty() {
+            return Err(RosBagError::InvalidMessage("message contains no finite points".into()));
+        }
+
+        let n_points = coords.len() / 3;
+        let points = Array2::from_shape_vec((n_points, 3), coords)
+            .expect("coordinate buffer holds whole points");
+
+        Ok(Self::try_new(points, bin_widths)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    fn push_block(out: &mut Vec<u8>, block: &[u8]) {
+        out.extend_from_slice(&(block.len() as u32).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+
+    fn fields(pairs: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut out = Vec::new();
+        for (name, value) in pairs {
+            let mut field = format!("{}=", name).into_bytes();
+            field.extend_from_slice(value);
+            push_block(&mut out, &field);
+        }
+        out
+    }
+
+    fn record(out: &mut Vec<u8>, header: &[(&str, &[u8])], data: &[u8]) {
+        push_block(out, &fields(header));
+        push_block(out, data);
+    }
+
+    /// Serialize an unorganized FLOAT32 PointCloud2 with x, y, z fields
+    fn pointcloud2(points: &[[f32; 3]]) -> Vec<u8> {
+        let mut msg = Vec::new();
+        msg.extend_from_slice(&[0; 12]);
+        push_block(&mut msg, b"lidar");
+        msg.extend_from_slice(&1u32.to_le_bytes());
+        msg.extend_from_slice(&(points.len() as u32).to_le_bytes());
+        msg.extend_from_slice(&3u32.to_le_bytes());
+        for (i, name) in ["x", "y", "z"].iter().enumerate() {
+            push_block(&mut msg, name.as_bytes());
+            msg.extend_from_slice(&(4 * i as u32).to_le_bytes());
+            msg.push(FLOAT32);
+            msg.extend_from_slice(&1u32.to_le_bytes());
+        }
+        msg.push(0);
+        msg.extend_from_slice(&12u32.to_le_bytes());
+        msg.extend_from_slice(&(12 * points.len() as u32).to_le_bytes());
+        let data: Vec<u8> = points.iter().flatten().flat_map(|v| v.to_le_bytes()).collect();
+        push_block(&mut msg, &data);
+        msg.push(1);
+        msg
+    }
+
+    fn write_bag(path: &Path) {
+        let conn = 0u32.to_le_bytes();
+        let mut chunk = Vec::new();
+        record(
+            &mut chunk,
+            &[("op", &[OP_CONNECTION]), ("conn", &conn), ("topic", b"/points")],
+            &fields(&[("topic", b"/points"), ("type", POINTCLOUD2_TYPE)]),
+        );
+        for frame in [
+            pointcloud2(&[[0.0, 0.0, 0.0]]),
+            pointcloud2(&[[1.0, 2.0, 3.0], [f32::NAN, 0.0, 0.0], [4.0, 5.0, 6.0]]),
+        ] {
+            record(&mut chunk, &[("op", &[OP_MESSAGE_DATA]), ("conn", &conn), ("time", &[0; 8])], &frame);
+        }
+
+        let mut bag = BAG_MAGIC.to_vec();
+        record(&mut bag, &[("op", &[OP_CHUNK]), ("compression", b"none")], &chunk);
+        fs::write(path, bag).unwrap();
+    }
+
+    #[test]
+    fn test_from_rosbag_pointcloud2() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("scan.bag");
+        write_bag(&path);
+
+        let point_bin = PointBin3D::from_rosbag_pointcloud2(&path, "/points", 1, array![1.0, 1.0, 1.0]).unwrap();
+        assert_eq!(point_bin.original_points(), &array![[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+    }
+
+    #[test]
+    fn test_from_rosbag_pointcloud2_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("scan.bag");
+        write_bag(&path);
+        let bin_widths = array![1.0, 1.0, 1.0];
+
+        let result = PointBin3D::from_rosbag_pointcloud2(&path, "/camera", 0, bin_widths.clone());
+        assert!(matches!(result, Err(RosBagError::TopicNotFound(_))));
+
+        let result = PointBin3D::from_rosbag_pointcloud2(&path, "/points", 2, bin_widths);
+        assert!(matches!(result, Err(RosBagError::FrameOutOfRange { frame_index: 2, n_frames: 2 })));
+    }
+}
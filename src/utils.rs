@@ -1,6 +1,6 @@
 //! Utility functions for array operations
 
-use ndarray::{Array1, ArrayView2};
+use ndarray::{array, Array1, Array2, ArrayView1, ArrayView2};
 
 /// Computes the minimum value along axis 0 (column-wise minimum)
 ///
@@ -8,8 +8,17 @@ use ndarray::{Array1, ArrayView2};
 /// * `arr` - 2D array view of shape (n_points, n_dimensions)
 ///
 /// # Returns
-/// 1D array of minimum values for each column
+/// 1D array of minimum values for each column. NaNs are skipped, except that a NaN in
+/// the first row is kept as that column's result, whatever the memory layout.
 pub fn min_along_axis0(arr: &ArrayView2<f64>) -> Array1<f64> {
+    if is_column_major(arr) {
+        return arr
+            .columns()
+            .into_iter()
+            .map(|col| col.iter().fold(col[0], |out, &x| if x < out { x } else { out }))
+            .collect();
+    }
+
     let n_cols = arr.ncols();
     let mut out = arr.row(0).to_owned();
 
@@ -30,8 +39,17 @@ pub fn min_along_axis0(arr: &ArrayView2<f64>) -> Array1<f64> {
 /// * `arr` - 2D array view of shape (n_points, n_dimensions)
 ///
 /// # Returns
-/// 1D array of maximum values for each column
+/// 1D array of maximum values for each column. NaNs are skipped, except that a NaN in
+/// the first row is kept as that column's result, whatever the memory layout.
 pub fn max_along_axis0(arr: &ArrayView2<f64>) -> Array1<f64> {
+    if is_column_major(arr) {
+        return arr
+            .columns()
+            .into_iter()
+            .map(|col| col.iter().fold(col[0], |out, &x| if x > out { x } else { out }))
+            .collect();
+    }
+
     let n_cols = arr.ncols();
     let mut out = arr.row(0).to_owned();
 
@@ -68,6 +86,51 @@ pub fn max_along_axis0_i64(arr: &ArrayView2<i64>) -> Array1<i64> {
     out
 }
 
+/// Whether a 2D array is laid out column-major (Fortran order) rather than row-major
+pub(crate) fn is_column_major<T>(arr: &ArrayView2<T>) -> bool {
+    !arr.is_standard_layout() && arr.t().is_standard_layout()
+}
+
+/// Computes the squared Euclidean distance between two 3D points
+pub(crate) fn squared_distance(a: &ArrayView1<f64>, b: &ArrayView1<f64>) -> f64 {
+    let mut dist_sq = 0.0;
+    for j in 0..3 {
+        let diff = a[j] - b[j];
+        dist_sq += diff * diff;
+    }
+    dist_sq
+}
+
+/// Inverts a 3x3 matrix using its adjugate
+///
+/// Returns `None` if the matrix is singular.
+pub(crate) fn inverse_3x3(m: &ArrayView2<f64>) -> Option<Array2<f64>> {
+    let cofactor = |r0: usize, r1: usize, c0: usize, c1: usize| m[[r0, c0]] * m[[r1, c1]] - m[[r0, c1]] * m[[r1, c0]];
+    let adjugate = array![
+        [cofactor(1, 2, 1, 2), -cofactor(0, 2, 1, 2), cofactor(0, 1, 1, 2)],
+        [-cofactor(1, 2, 0, 2), cofactor(0, 2, 0, 2), -cofactor(0, 1, 0, 2)],
+        [cofactor(1, 2, 0, 1), -cofactor(0, 2, 0, 1), cofactor(0, 1, 0, 1)],
+    ];
+    let det = m[[0, 0]] * adjugate[[0, 0]] + m[[0, 1]] * adjugate[[1, 0]] + m[[0, 2]] * adjugate[[2, 0]];
+    if det == 0.0 || !det.is_finite() {
+        return None;
+    }
+    Some(adjugate / det)
+}
+
+/// Computes the row-major linear index of a bin in a grid of shape `bin_shape`
+///
+/// Uses `i128` so the index cannot overflow for any grid whose dimensions fit in `i64`.
+pub(crate) fn ravel_bin_index(bin: [i64; 3], bin_shape: &Array1<i64>) -> i128 {
+    let (ny, nz) = (bin_shape[1] as i128, bin_shape[2] as i128);
+    (bin[0] as i128 * ny + bin[1] as i128) * nz + bin[2] as i128
+}
+
+/// Checks that a sequence of bin coordinates is in non-decreasing row-major order
+pub(crate) fn is_bin_sorted(bins: &[[i64; 3]]) -> bool {
+    bins.windows(2).all(|w| w[0] <= w[1])
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -103,4 +166,59 @@ mod tests {
             assert_abs_diff_eq!(r, e, epsilon = 1e-10);
         }
     }
+
+    #[test]
+    fn test_ravel_bin_index_near_overflow() {
+        // bin_shape[1] * bin_shape[2] = 2^63 overflows i64
+        let bin_shape = array![2, 1_i64 << 31, 1_i64 << 32];
+        let mut bins = vec![[0, (1 << 31) - 1, (1 << 32) - 1], [1, 0, 0]];
+
+        // The old i64 key wraps negative, putting bin (1, 0, 0) first
+        let old_key = |b: &[i64; 3]| {
+            b[0].wrapping_mul(bin_shape[1]).wrapping_mul(bin_shape[2])
+                .wrapping_add(b[1].wrapping_mul(bin_shape[2]))
+                .wrapping_add(b[2])
+        };
+        bins.sort_by_key(old_key);
+        assert!(!is_bin_sorted(&bins));
+
+        bins.sort_by_key(|&b| ravel_bin_index(b, &bin_shape));
+        assert!(is_bin_sorted(&bins));
+        assert_eq!(ravel_bin_index([1, 0, 0], &bin_shape), 1_i128 << 63);
+    }
+
+    #[test]
+    fn test_inverse_3x3() {
+        let m = array![[2.0, 0.0, 1.0], [1.0, 3.0, 0.0], [0.0, 1.0, 4.0]];
+        let inverse = inverse_3x3(&m.view()).unwrap();
+        let identity = m.dot(&inverse);
+        for ((r, c), &v) in identity.indexed_iter() {
+            let expected = if r == c { 1.0 } else { 0.0 };
+            assert!((v - expected).abs() < 1e-12);
+        }
+
+        let singular = array![[1.0, 2.0, 3.0], [2.0, 4.0, 6.0], [0.0, 1.0, 0.0]];
+        assert!(inverse_3x3(&singular.view()).is_none());
+    }
+
+    #[test]
+    fn test_min_max_along_axis0_column_major() {
+        let arr = array![[1.0, 5.0, -3.0], [4.0, 2.0, 0.0], [-1.0, 8.0, 2.0]];
+        let fortran = arr.t().as_standard_layout().into_owned().reversed_axes();
+        assert!(is_column_major(&fortran.view()));
+        assert!(!is_column_major(&arr.view()));
+
+        assert_eq!(min_along_axis0(&fortran.view()), min_along_axis0(&arr.view()));
+        assert_eq!(max_along_axis0(&fortran.view()), max_along_axis0(&arr.view()));
+
+        // Both layouts treat NaN the same way: skipped, unless it is in the first row
+        let arr = array![[f64::NAN, 5.0, -3.0], [4.0, f64::NAN, 0.0], [-1.0, 8.0, 2.0]];
+        let fortran = arr.t().as_standard_layout().into_owned().reversed_axes();
+        let bits = |a: Array1<f64>| a.mapv(f64::to_bits);
+        assert_eq!(bits(min_along_axis0(&fortran.view())), bits(min_along_axis0(&arr.view())));
+        assert_eq!(bits(max_along_axis0(&fortran.view())), bits(max_along_axis0(&arr.view())));
+        let min = min_along_axis0(&arr.view());
+        assert!(min[0].is_nan());
+        assert_eq!((min[1], min[2]), (5.0, -3.0));
+    }
 }
@@ -0,0 +1,240 @@
+//! Readers for whitespace-separated `x y z` and `x y z r g b` point cloud text
+
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead, BufWriter, Write};
+use std::path::Path;
+
+use ndarray::{Array1, Array2};
+
+use crate::{BucketError, PointBin3D};
+
+/// Errors that can occur while parsing a point cloud text file
+#[derive(Debug)]
+pub enum ParseError {
+    /// Reading the file failed
+    Io(io::Error),
+    /// A line could not be parsed (`line` is 1-based)
+    InvalidLine { line: usize, message: String },
+    /// The file contains no points
+    Empty,
+    /// The bin widths were rejected by `PointBin3D::try_new`
+    InvalidInput(BucketError),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Io(err) => write!(f, "I/O error: {}", err),
+            ParseError::InvalidLine { line, message } => write!(f, "Line {}: {}", line, message),
+            ParseError::Empty => write!(f, "File contains no points"),
+            ParseError::InvalidInput(err) => write!(f, "Invalid input: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParseError::Io(err) => Some(err),
+            ParseError::InvalidInput(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ParseError {
+    fn from(err: io::Error) -> Self {
+        ParseError::Io(err)
+    }
+}
+
+impl From<BucketError> for ParseError {
+    fn from(err: BucketError) -> Self {
+        ParseError::InvalidInput(err)
+    }
+}
+
+impl PointBin3D {
+    /// Build a PointBin3D from an `x y z r g b` text file
+    ///
+    /// Each non-empty line holds three coordinates followed by three integer color
+    /// channels in 0-255, separated by whitespace. Lines starting with `#` are skipped.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the text file
+    /// * `bin_widths` - 1D array of shape (3,) with bin widths for x, y, z
+    ///
+    /// # Returns
+    /// The structure and an (n_points, 3) array of RGB colors. Row `i` of the colors
+    /// belongs to row `i` of `original_points()`, i.e. colors follow file order and not
+    /// the internal bin-sorted order.
+    pub fn from_xyzrgb<P: AsRef<Path>>(path: P, bin_widths: Array1<f64>) -> Result<(Self, Array2<u8>), ParseError> {
+        let text = fs::read_to_string(path)?;
+
+        let mut coords: Vec<f64> = Vec::new();
+        let mut colors: Vec<u8> = Vec::new();
+        for (line_idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = |message: String| ParseError::InvalidLine { line: line_idx + 1, message };
+
+            let values: Vec<&str> = line.split_whitespace().collect();
+            if values.len() != 6 {
+                return Err(invalid(format!("expected 6 values, found {}", values.len())));
+            }
+            for value in &values[..3] {
+                let coord = value
+                    .parse::<f64>()
+                    .map_err(|_| invalid(format!("invalid coordinate '{}'", value)))?;
+                coords.push(coord);
+            }
+            for value in &values[3..] {
+                let channel = value
+                    .parse::<u8>()
+                    .map_err(|_| invalid(format!("invalid color channel '{}' (expected 0-255)", value)))?;
+                colors.push(channel);
+            }
+        }
+
+        if coords.is_empty() {
+            return Err(ParseError::Empty);
+        }
+
+        let n_points = coords.len() / 3;
+        let points = Array2::from_shape_vec((n_points, 3), coords)
+            .expect("coordinate buffer holds whole points");
+        let colors = Array2::from_shape_vec((n_points, 3), colors)
+            .expect("color buffer holds whole points");
+
+        Ok((Self::try_new(points, bin_widths)?, colors))
+    }
+
+    /// Build a PointBin3D from `x y z` lines on standard input
+    ///
+    /// For command-line pipelines such as `cat cloud.xyz | tool`. See `from_xyz_reader`
+    /// for the format.
+    ///
+    /// # Arguments
+    /// * `bin_widths` - 1D array of shape (3,) with bin widths for x, y, z
+    pub fn from_stdin(bin_widths: Array1<f64>) -> Result<Self, ParseError> {
+        Self::from_xyz_reader(io::stdin().lock(), bin_widths)
+    }
+
+    /// Build a PointBin3D from `x y z` lines read from any buffered reader
+    ///
+    /// Each non-empty line holds three whitespace-separated coordinates. Lines starting
+    /// with `#` are skipped. Points keep their input order in `original_points()`.
+    ///
+    /// # Arguments
+    /// * `reader` - Source of the text
+    /// * `bin_widths` - 1D array of shape (3,) with bin widths for x, y, z
+    pub fn from_xyz_reader<R: BufRead>(reader: R, bin_widths: Array1<f64>) -> Result<Self, ParseError> {
+        let mut coords: Vec<f64> = Vec::new();
+        for (line_idx, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = |message: String| ParseError::InvalidLine { line: line_idx + 1, message };
+
+            let values: Vec<&str> = line.split_whitespace().collect();
+            if values.len() != 3 {
+                return Err(invalid(format!("expected 3 values, found {}", values.len())));
+            }
+            for (axis, value) in ["x", "y", "z"].iter().zip(&values) {
+                let coord = value
+                    .parse::<f64>()
+                    .map_err(|_| invalid(format!("invalid {} coordinate '{}'", axis, value)))?;
+                coords.push(coord);
+            }
+        }
+
+        if coords.is_empty() {
+            return Err(ParseError::Empty);
+        }
+
+        let points = Array2::from_shape_vec((coords.len() / 3, 3), coords)
+            .expect("coordinate buffer holds whole points");
+        Ok(Self::try_new(points, bin_widths)?)
+    }
+
+    /// Write the remaining points to standard output as `x y z` lines
+    ///
+    /// The counterpart of `from_stdin`; see `write_xyz` for the format.
+    pub fn write_stdout_xyz(&self) -> io::Result<()> {
+        let mut writer = BufWriter::new(io::stdout().lock());
+        self.write_xyz(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Write the remaining points as `x y z` lines
+    ///
+    /// One line per point not removed by a search, in original index order. Coordinates
+    /// are written with full precision, so reading the output back with
+    /// `from_xyz_reader` reproduces them exactly.
+    ///
+    /// # Arguments
+    /// * `writer` - Destination of the text
+    pub fn write_xyz<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        for orig_idx in self.remaining_original_indices() {
+            let p = self.original_points().row(orig_idx);
+            writeln!(writer, "{} {} {}", p[0], p[1], p[2])?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    #[test]
+    fn test_from_xyzrgb() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cloud.xyz");
+        fs::write(&path, "# x y z r g b\n5.0 5.0 5.0 255 0 0\n\n0.0 0.5 1.0 0 128 255\n").unwrap();
+
+        let (point_bin, colors) = PointBin3D::from_xyzrgb(&path, array![1.0, 1.0, 1.0]).unwrap();
+        assert_eq!(point_bin.original_points(), &array![[5.0, 5.0, 5.0], [0.0, 0.5, 1.0]]);
+        assert_eq!(colors, array![[255, 0, 0], [0, 128, 255]]);
+    }
+
+    #[test]
+    fn test_from_xyzrgb_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cloud.xyz");
+
+        fs::write(&path, "0 0 0 1 2 3\n1 1 1 256 0 0\n").unwrap();
+        let result = PointBin3D::from_xyzrgb(&path, array![1.0, 1.0, 1.0]);
+        assert!(matches!(result, Err(ParseError::InvalidLine { line: 2, .. })));
+
+        fs::write(&path, "# only a comment\n").unwrap();
+        let result = PointBin3D::from_xyzrgb(&path, array![1.0, 1.0, 1.0]);
+        assert!(matches!(result, Err(ParseError::Empty)));
+
+        fs::write(&path, "0 0 0 1 2 3\n").unwrap();
+        let result = PointBin3D::from_xyzrgb(&path, array![1.0, 0.0, 1.0]);
+        assert!(matches!(result, Err(ParseError::InvalidInput(BucketError::NonPositiveBinWidth { axis: 1, .. }))));
+    }
+
+    #[test]
+    fn test_from_xyz_reader() {
+        let text = "# x y z\n5.0 5.0 5.0\n\n  0.0 0.5 1.0\n";
+        let point_bin = PointBin3D::from_xyz_reader(text.as_bytes(), array![1.0, 1.0, 1.0]).unwrap();
+        assert_eq!(point_bin.original_points(), &array![[5.0, 5.0, 5.0], [0.0, 0.5, 1.0]]);
+
+        let result = PointBin3D::from_xyz_reader("0 0 0\n1 oops 1\n".as_bytes(), array![1.0, 1.0, 1.0]);
+        match result {
+            Err(ParseError::InvalidLine { line, message }) => {
+                assert_eq!(line, 2);
+                assert_eq!(message, "invalid y coordinate 'oops'");
+            }
+            _ => panic!("expected an invalid line error"),
+        }
+    }
+}
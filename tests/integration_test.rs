@@ -148,3 +148,34 @@ fn test_cumulative_searches() {
     sorted_results.sort();
     assert_eq!(sorted_results, vec![0, 2]);
 }
+
+#[test]
+fn test_xyz_pipeline_round_trip() {
+    use std::fs::File;
+    use std::io::{BufReader, BufWriter, Write};
+
+    let original_points = array![
+        [0.1, -2.5, 3.0],
+        [1e-7, 123456.789, -0.3],
+        [1.0 / 3.0, 2.0 / 3.0, 5.0],
+        [10.0, 10.0, 10.0],
+    ];
+    let bin_widths = array![1.0, 1.0, 1.0];
+    let mut point_bin = PointBin3D::new(original_points.clone(), bin_widths.clone());
+
+    // Drop one point so only the remaining ones go through the pipe
+    point_bin.radius_search(&array![10.0, 10.0, 10.0].view(), 0.1);
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("cloud.xyz");
+    let mut writer = BufWriter::new(File::create(&path).unwrap());
+    point_bin.write_xyz(&mut writer).unwrap();
+    writer.flush().unwrap();
+    drop(writer);
+
+    // Same code path as `from_stdin`, fed from the file instead
+    let reader = BufReader::new(File::open(&path).unwrap());
+    let round_trip = PointBin3D::from_xyz_reader(reader, bin_widths).unwrap();
+
+    assert_eq!(round_trip.original_points(), &original_points.slice(ndarray::s![..3, ..]));
+}
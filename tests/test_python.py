@@ -154,6 +154,18 @@ def test_error_handling():
         bin_widths = np.array([1.0, 1.0], dtype=np.float64)
         PointBin3D(points, bin_widths)
 
+    # Test non-positive bin widths
+    with pytest.raises(ValueError, match="positive"):
+        points = np.array([[0.0, 0.0, 0.0], [1.0, 1.0, 1.0]], dtype=np.float64)
+        bin_widths = np.array([1.0, 0.0, 1.0], dtype=np.float64)
+        PointBin3D(points, bin_widths)
+
+    # Test empty points
+    with pytest.raises(ValueError, match="at least one point"):
+        points = np.zeros((0, 3), dtype=np.float64)
+        bin_widths = np.array([1.0, 1.0, 1.0], dtype=np.float64)
+        PointBin3D(points, bin_widths)
+
 
 def test_repr():
     """Test string representation"""
@@ -176,6 +188,231 @@ def test_repr():
     assert "found_count=0" in repr_str
 
 
+
+def test_grid_metadata():
+    """Test that grid_metadata agrees with the individual accessors"""
+    try:
+        from bucket_search import PointBin3D
+    except ImportError:
+        pytest.skip("bucket_search not built")
+
+    points = np.array([
+        [1.0, 2.0, 3.0],
+        [4.5, 2.5, 3.5],
+    ], dtype=np.float64)
+    bin_widths = np.array([1.0, 0.5, 2.0], dtype=np.float64)
+
+    point_bin = PointBin3D(points, bin_widths)
+    metadata = point_bin.grid_metadata()
+
+    assert np.allclose(metadata["origin"], point_bin.origin())
+    assert np.allclose(metadata["bin_widths"], point_bin.bin_widths())
+    assert np.array_equal(metadata["bin_shape"], point_bin.bin_shape())
+    assert np.allclose(metadata["max_corner"], np.array([5.0, 3.0, 5.0]))
+
+
+def test_found_bounds():
+    """Test the bounding box of the found points"""
+    try:
+        from bucket_search import PointBin3D
+    except ImportError:
+        pytest.skip("bucket_search not built")
+
+    points = np.array([
+        [0.0, 0.0, 0.0],
+        [1.0, -0.5, 0.25],
+        [-0.5, 0.75, 0.0],
+        [5.0, 5.0, 5.0],
+    ], dtype=np.float64)
+    bin_widths = np.array([1.0, 1.0, 1.0], dtype=np.float64)
+
+    point_bin = PointBin3D(points, bin_widths)
+    assert point_bin.found_bounds() is None
+
+    point_bin.radius_search(np.array([0.0, 0.0, 0.0], dtype=np.float64), 1.5)
+    min_corner, max_corner = point_bin.found_bounds()
+    assert np.allclose(min_corner, np.array([-0.5, -0.5, 0.0]))
+    assert np.allclose(max_corner, np.array([1.0, 0.75, 0.25]))
+
+
+def test_to_occupancy_grid():
+    """Test the boolean occupancy grid"""
+    try:
+        from bucket_search import PointBin3D
+    except ImportError:
+        pytest.skip("bucket_search not built")
+
+    points = np.array([
+        [0.5, 0.5, 0.5],
+        [2.5, 0.5, 1.5],
+        [2.5, 1.5, 0.5],
+    ], dtype=np.float64)
+    bin_widths = np.array([1.0, 1.0, 1.0], dtype=np.float64)
+
+    point_bin = PointBin3D(points, bin_widths)
+    grid = point_bin.to_occupancy_grid()
+
+    assert grid.dtype == np.bool_
+    assert grid.shape == tuple(point_bin.bin_shape())
+    assert grid.sum() == 3
+    assert grid[0, 0, 0] and grid[2, 0, 1] and grid[2, 1, 0]
+
+
+def test_found_points_columns():
+    """Test the found points as separate x, y, z columns"""
+    try:
+        from bucket_search import PointBin3D
+    except ImportError:
+        pytest.skip("bucket_search not built")
+
+    points = np.array([
+        [0.0, 0.1, 0.2],
+        [0.5, 0.6, 0.7],
+        [5.0, 5.0, 5.0],
+        [-0.3, 0.2, -0.1],
+    ], dtype=np.float64)
+    bin_widths = np.array([1.0, 1.0, 1.0], dtype=np.float64)
+
+    point_bin = PointBin3D(points, bin_widths)
+    point_bin.radius_search(np.array([0.0, 0.0, 0.0], dtype=np.float64), 1.5)
+
+    x, y, z = point_bin.found_points_columns()
+    assert np.allclose(np.column_stack([x, y, z]), points[point_bin.found_indices()])
+
+
+def test_bin_stats():
+    """Test the bin occupancy statistics property"""
+    try:
+        from bucket_search import PointBin3D
+    except ImportError:
+        pytest.skip("bucket_search not built")
+
+    points = np.array([
+        [0.1, 0.1, 0.1],
+        [0.2, 0.2, 0.2],
+        [0.3, 0.3, 0.3],
+        [1.5, 0.5, 0.5],
+    ], dtype=np.float64)
+    bin_widths = np.array([1.0, 1.0, 1.0], dtype=np.float64)
+
+    point_bin = PointBin3D(points, bin_widths)
+    stats = point_bin.bin_stats
+
+    assert stats["total_bins"] == 2
+    assert stats["occupied_bins"] == 2
+    assert stats["max_occupancy"] == 3
+    assert stats["mean_occupancy"] == 2.0
+    assert stats["histogram"] == [(1, 1), (3, 1)]
+
+
+def test_grid_utilization():
+    """Test the occupied bin count and grid utilization"""
+    try:
+        from bucket_search import PointBin3D
+    except ImportError:
+        pytest.skip("bucket_search not built")
+
+    points = np.array([
+        [0.0, 0.0, 0.0],
+        [0.5, 0.5, 0.5],
+        [3.5, 1.5, 4.5],
+    ], dtype=np.float64)
+    bin_widths = np.array([1.0, 1.0, 1.0], dtype=np.float64)
+
+    point_bin = PointBin3D(points, bin_widths)
+
+    assert point_bin.occupied_bin_count() == 2
+    assert np.isclose(point_bin.grid_utilization(), 2 / 40)
+
+def _found_state(point_bin):
+    """Worker for test_pickle_multiprocessing, run in a child process"""
+    return point_bin.found_indices(), point_bin.found_count(), repr(point_bin)
+
+
+def test_pickle_multiprocessing():
+    """Test that a PointBin3D keeps its found state when sent to a worker process"""
+    try:
+        from bucket_search import PointBin3D
+    except ImportError:
+        pytest.skip("bucket_search not built")
+
+    import multiprocessing
+    import pickle
+
+    points = np.array([
+        [0.0, 0.0, 0.0],
+        [0.5, 0.0, 0.0],
+        [3.0, 3.0, 3.0],
+        [3.2, 3.0, 3.0],
+    ], dtype=np.float64)
+    bin_widths = np.array([1.0, 1.0, 1.0], dtype=np.float64)
+
+    point_bin = PointBin3D(points, bin_widths)
+    point_bin.radius_search(np.array([3.0, 3.0, 3.0]), 0.5)
+
+    restored = pickle.loads(pickle.dumps(point_bin))
+    assert np.array_equal(restored.found_indices(), point_bin.found_indices())
+    assert np.array_equal(restored.original_points(), points)
+
+    with multiprocessing.Pool(2) as pool:
+        results = pool.map(_found_state, [point_bin, point_bin])
+
+    for found, count, text in results:
+        assert np.array_equal(found, point_bin.found_indices())
+        assert count == 2
+        assert text == repr(point_bin)
+
+    # Removed points stay removed in the copy
+    restored.radius_search(np.array([3.0, 3.0, 3.0]), 0.5)
+    assert restored.found_count() == 2
+    restored.radius_search(np.array([0.0, 0.0, 0.0]), 1.0)
+    assert sorted(restored.found_indices()) == [0, 1, 2, 3]
+
+def test_from_arrow_matches_numpy():
+    """Test building from a pyarrow Table gives the same results as from numpy"""
+    try:
+        from bucket_search import PointBin3D
+    except ImportError:
+        pytest.skip("bucket_search not built")
+    pa = pytest.importorskip("pyarrow")
+
+    rng = np.random.default_rng(7)
+    points = rng.uniform(0.0, 10.0, size=(500, 3))
+    bin_widths = np.array([1.0, 1.0, 1.0], dtype=np.float64)
+
+    # Several chunks, and an integer-typed label column that is ignored
+    half = 250
+    table = pa.concat_tables([
+        pa.table({"x": points[:half, 0], "y": points[:half, 1], "z": points[:half, 2],
+                  "label": np.arange(half)}),
+        pa.table({"x": points[half:, 0], "y": points[half:, 1], "z": points[half:, 2],
+                  "label": np.arange(half, 500)}),
+    ])
+    assert table.column("x").num_chunks == 2
+
+    from_arrow = PointBin3D.from_arrow(table, "x", "y", "z", bin_widths)
+    from_numpy = PointBin3D(points, bin_widths)
+    assert np.array_equal(from_arrow.original_points(), points)
+    assert np.array_equal(from_arrow.bin_shape(), from_numpy.bin_shape())
+
+    for query in [np.array([5.0, 5.0, 5.0]), np.array([1.0, 8.0, 3.0])]:
+        from_arrow.radius_search(query, 2.0)
+        from_numpy.radius_search(query, 2.0)
+    assert np.array_equal(from_arrow.found_indices(), from_numpy.found_indices())
+
+    # Integer columns are cast, nulls and uncastable columns are rejected
+    int_table = pa.table({"x": [0, 1], "y": [0, 1], "z": [0, 2]})
+    assert np.array_equal(
+        PointBin3D.from_arrow(int_table, "x", "y", "z", bin_widths).original_points(),
+        np.array([[0.0, 0.0, 0.0], [1.0, 1.0, 2.0]]),
+    )
+    null_table = pa.table({"x": [0.0, None], "y": [0.0, 1.0], "z": [0.0, 1.0]})
+    with pytest.raises(ValueError):
+        PointBin3D.from_arrow(null_table, "x", "y", "z", bin_widths)
+    text_table = pa.table({"x": ["a", "b"], "y": [0.0, 1.0], "z": [0.0, 1.0]})
+    with pytest.raises(TypeError, match="can't be cast to float64"):
+        PointBin3D.from_arrow(text_table, "x", "y", "z", bin_widths)
+
 if __name__ == "__main__":
     # Run tests directly
     test_pointbin_basic_workflow()
@@ -183,4 +420,12 @@ if __name__ == "__main__":
     test_multiple_points_in_radius()
     test_error_handling()
     test_repr()
+    test_grid_metadata()
+    test_found_bounds()
+    test_to_occupancy_grid()
+    test_found_points_columns()
+    test_bin_stats()
+    test_grid_utilization()
+    test_pickle_multiprocessing()
+    test_from_arrow_matches_numpy()
     print("\n=== All Python tests passed ===")